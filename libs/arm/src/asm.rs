@@ -17,6 +17,41 @@
 
 pub use self::imp::*;
 
+/// Data synchronization barrier. Blocks until every memory access before it has completed, e.g.
+/// so a write to a system register has taken effect before the code after it runs.
+#[inline(always)]
+pub fn data_barrier() {
+    unsafe { dsb() };
+}
+
+/// Instruction synchronization barrier. Flushes the pipeline so the instructions after it are
+/// fetched again, needed after a change that affects instruction fetches such as the flash wait
+/// states or the memory mapped at the vector table.
+#[inline(always)]
+pub fn instruction_barrier() {
+    unsafe { isb() };
+}
+
+/// Data memory barrier. Memory accesses before it are observed before any memory access after
+/// it, without waiting for them to complete.
+#[inline(always)]
+pub fn memory_barrier() {
+    unsafe { dmb() };
+}
+
+/// The number of processor cycles each iteration of `spin_loops` takes: one for the subtract and
+/// three for the taken branch.
+pub const CYCLES_PER_SPIN_LOOP: u32 = 4;
+
+/// Busy wait for `loops` iterations of a counted loop, each taking `CYCLES_PER_SPIN_LOOP`
+/// processor cycles when run from zero wait state memory.
+#[inline(always)]
+pub fn spin_loops(loops: u32) {
+    if loops != 0 {
+        unsafe { spin(loops) };
+    }
+}
+
 #[cfg(target_arch="arm")]
 mod imp {
     #[inline(always)]
@@ -39,6 +74,16 @@ mod imp {
         );
     }
 
+    #[inline(always)]
+    pub unsafe fn isb() {
+        asm!("isb"
+            : /* no outputs */
+            : /* no inputs */
+            : /* no clobbers */
+            : "volatile"
+        );
+    }
+
     #[inline(always)]
     pub unsafe fn bkpt() {
         asm!("bkpt"
@@ -49,6 +94,20 @@ mod imp {
         );
     }
 
+    // Loops must be nonzero, 0 would wrap around and spin for 2^32 iterations.
+    #[inline(always)]
+    pub unsafe fn spin(loops: u32) {
+        let _remaining: u32;
+        asm!("1:
+            subs $0, $0, #1
+            bne 1b"
+            : "=r"(_remaining)
+            : "0"(loops)
+            : "cc"
+            : "volatile"
+        );
+    }
+
     #[inline(always)]
     pub unsafe fn enable_interrupts() {
         asm!("cpsie i"
@@ -99,9 +158,15 @@ mod imp {
     #[inline(always)]
     pub unsafe fn dsb() {}
 
+    #[inline(always)]
+    pub unsafe fn isb() {}
+
     #[inline(always)]
     pub unsafe fn bkpt() {}
 
+    #[inline(always)]
+    pub unsafe fn spin(_loops: u32) {}
+
     #[inline(always)]
     pub unsafe fn enable_interrupts() {}
 
@@ -58,12 +58,12 @@ pub static EXCEPTIONS: [Option<unsafe extern "C" fn()>; 46] = [
     Some(default_handler),  // Real Time Clock: 17
     Some(default_handler),  // Flash global: 18
     Some(default_handler),  // RCC and CRS global: 19
-    Some(default_handler),  // EXTI Line[1:0]: 20
-    Some(default_handler),  // EXTI Line[3:2]: 21
-    Some(default_handler),  // EXTI Line[15:4]: 22
+    Some(exti0_1_handler),  // EXTI Line[1:0]: 20
+    Some(exti2_3_handler),  // EXTI Line[3:2]: 21
+    Some(exti4_15_handler),  // EXTI Line[15:4]: 22
     Some(default_handler),  // Touch Sensing: 23
-    Some(default_handler),  // DMA channel 1: 24
-    Some(default_handler),  // DMA channel 2 and 3 and DMA2 channel 1 and 2: 25
+    Some(dma_chan1_handler),  // DMA channel 1: 24
+    Some(dma_chan2_3_handler),  // DMA channel 2 and 3 and DMA2 channel 1 and 2: 25
     Some(dma_chan4plus_handler),  // DMA channel 4,5,6,7 and DMA2 channel 3,4,5: 26
     Some(default_handler),  // ADC and COMP (ADC combined with EXTI lines 21 and 22): 27
     Some(default_handler),  // TIM1 break, update, trigger, communication: 28
@@ -80,7 +80,7 @@ pub static EXCEPTIONS: [Option<unsafe extern "C" fn()>; 46] = [
     Some(default_handler),  // I2C2 global: 39
     Some(default_handler),  // SPI1 global: 40
     Some(default_handler),  // SPI2 global: 41
-    Some(default_handler),  // USART1 global (combined with EXTI Line 25): 42
+    Some(usart1_handler),  // USART1 global (combined with EXTI Line 25): 42
     Some(usart2_handler),  // USART2 global (combined with EXTI Line 26): 43
     Some(default_handler),  // USART3,4,5,6,7,8 (combined with EXTI Line 28): 44
     Some(default_handler),  // CEC and CAN global (combined with EXTI Line 27): 45
@@ -218,9 +218,11 @@ unsafe extern "C" fn sv_call_handler() {
 
 unsafe extern "C" fn systick_handler() {
     use time;
+    use peripheral::systick;
 
     syscall::system_tick();
     time::system_tick();
+    systick::run_handler();
 }
 
 /// Tell OS to context switch tasks, this should be set to the lowest priority so that all
@@ -294,6 +296,15 @@ unsafe extern "C" fn pend_sv_handler() {
     );
 }
 
+// Interrupt handler for Usart1, which only drains an attached `TxQueue`.
+unsafe extern "C" fn usart1_handler() {
+    use peripheral::usart::{self, UsartX};
+
+    if !usart::queue_on_txe(UsartX::Usart1) {
+        default_handler();
+    }
+}
+
 // Interrupt handler for Usart2
 unsafe extern "C" fn usart2_handler() {
     #[cfg(feature="serial")]
@@ -305,8 +316,64 @@ unsafe extern "C" fn usart2_handler() {
         let usart2 = Usart::new(UsartX::Usart2);
         usart_rx(usart2);
         usart_tx(usart2);
+        #[cfg(feature="dma")]
+        ::io::dma_receive_idle(usart2);
     }
     #[cfg(not(feature="serial"))]
+    {
+        use peripheral::usart::{self, UsartX};
+
+        if !usart::queue_on_txe(UsartX::Usart2) {
+            default_handler();
+        }
+    }
+}
+
+// Interrupt handler for EXTI lines 0 and 1.
+unsafe extern "C" fn exti0_1_handler() {
+    use peripheral::gpio;
+
+    gpio::dispatch_interrupt(0, 1);
+}
+
+// Interrupt handler for EXTI lines 2 and 3.
+unsafe extern "C" fn exti2_3_handler() {
+    use peripheral::gpio;
+
+    gpio::dispatch_interrupt(2, 3);
+}
+
+// Interrupt handler for EXTI lines 4 through 15, these all share one interrupt so every line is
+// checked.
+unsafe extern "C" fn exti4_15_handler() {
+    use peripheral::gpio;
+
+    gpio::dispatch_interrupt(4, 15);
+}
+
+// Interrupt handler for DMA Channel 1.
+unsafe extern "C" fn dma_chan1_handler() {
+    #[cfg(feature="dma")]
+    {
+        use peripheral::dma::{DMA, DMAChannel};
+
+        let mut dma = DMA::new();
+        dma.dispatch_complete(&[DMAChannel::One]);
+    }
+    #[cfg(not(feature="dma"))]
+    default_handler();
+}
+
+// Interrupt handler for DMA Channels 2 and 3.
+unsafe extern "C" fn dma_chan2_3_handler() {
+    #[cfg(feature="dma")]
+    {
+        use peripheral::dma::{DMA, DMAChannel};
+
+        let mut dma = DMA::new();
+        dma.dispatch_complete(&[DMAChannel::Two, DMAChannel::Three]);
+    }
+    #[cfg(not(feature="dma"))]
     default_handler();
 }
 
@@ -317,7 +384,8 @@ unsafe extern "C" fn dma_chan4plus_handler() {
         use peripheral::dma::{DMA, DMAChannel};
         use self::dma::{dma_tx};
 
-        let dma = DMA::new();
+        let mut dma = DMA::new();
+        dma.dispatch_complete(&[DMAChannel::Four, DMAChannel::Five]);
         dma_tx(dma, DMAChannel::Four);
     }
     #[cfg(not(feature="dma"))]
@@ -16,7 +16,7 @@
 */
 extern crate arm;
 
-use peripheral::usart::{Usart, USART2_TX_CHAN, USART2_RX_CHAN};
+use peripheral::usart::{self, Usart, UsartX, USART2_TX_CHAN, USART2_RX_CHAN};
 use altos_core::syscall;
 use io::{TX_BUFFER, RX_BUFFER};
 
@@ -27,7 +27,11 @@ pub fn usart_tx(mut usart: Usart) {
             usart.transmit_byte(byte);
         }
         else {
-            usart.disable_transmit_interrupt();
+            // A `TxQueue` attached to the Usart shares the interrupt, keep it going until both
+            // have run dry
+            if !usart::send_queued(UsartX::Usart2) {
+                usart.disable_transmit_interrupt();
+            }
             syscall::sys_wake(USART2_TX_CHAN);
         }
     }
@@ -41,10 +45,12 @@ pub fn usart_tx(mut usart: Usart) {
 
 /// Handles receiving any bytes when an interrupt is generated
 pub fn usart_rx(mut usart: Usart) {
-    // Clears overrun error flag.
-    // If not cleared, interrupt gets repeatedly generated
+    // Counts and clears the overrun flag.
+    // If the overrun flag is not cleared, interrupt gets repeatedly generated
     // when it doesn't need to be, and loops infinitely.
-    usart.clear_ore_flag();
+    usart::record_overrun(&mut usart);
+    // The error flags belong to the byte in the RDR, count them before it's read
+    usart::record_line_errors(&usart);
     if usart.is_rx_reg_full() {
         let byte = usart.load_byte();
         unsafe { RX_BUFFER.insert(byte) };
@@ -15,7 +15,9 @@
 * along with this program. If not, see <http://www.gnu.org/licenses/>.
 */
 
-pub const NVIC_ADDR: *const u32 = 0xE000E100 as *const _;
+use peripheral::addresses;
+
+pub const NVIC_ADDR: *const u32 = addresses::NVIC_ADDR;
 
 // Interrupt Numbers
 pub const NVIC_WWDG_INT: isize = 0;
@@ -15,7 +15,7 @@
 * along with this program. If not, see <http://www.gnu.org/licenses/>.
 */
 
-use interrupt::Hardware;
+use interrupt::Interrupt;
 
 #[derive(Copy, Clone, Debug)]
 pub struct ISER(u32);
@@ -23,21 +23,21 @@ pub struct ISER(u32);
 pub struct ICER(u32);
 
 impl ISER {
-    pub fn enable_interrupt(&mut self, hardware: Hardware) {
-        let interrupt = hardware as u8;
+    pub fn enable_interrupt(&mut self, hardware: Interrupt) {
+        let interrupt = hardware.number();
 
         self.0 |= 0b1 << interrupt;
     }
 
-    pub fn interrupt_is_enabled(&self, hardware: Hardware) -> bool {
-        let interrupt = hardware as u8;
+    pub fn interrupt_is_enabled(&self, hardware: Interrupt) -> bool {
+        let interrupt = hardware.number();
         self.0 & (0b1 << interrupt) != 0
     }
 }
 
 impl ICER {
-    pub fn disable_interrupt(&mut self, hardware: Hardware) {
-        let interrupt = hardware as u8;
+    pub fn disable_interrupt(&mut self, hardware: Interrupt) {
+        let interrupt = hardware.number();
 
         self.0 |= 0b1 << interrupt;
     }
@@ -46,6 +46,7 @@ impl ICER {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use interrupt::Hardware;
 
     #[test]
     fn test_iser_enable_interrupt() {
@@ -70,4 +71,13 @@ mod tests {
         icer.disable_interrupt(Hardware::Flash);
         assert_eq!(icer.0, 0b1 << 3);
     }
+
+    #[test]
+    fn test_iser_enable_interrupt_by_new_name() {
+        let mut iser = ISER(0);
+
+        iser.enable_interrupt(Interrupt::Usart2);
+        assert!(iser.interrupt_is_enabled(Hardware::Usart2));
+        assert_eq!(iser.0, 0b1 << 28);
+    }
 }
@@ -30,10 +30,10 @@ use self::defs::*;
 use core::ops::{Deref, DerefMut};
 pub use self::priority::Priority;
 
-// Defines all the perpherials that have interrupts.
+/// Defines all the perpherials that have interrupts, each value is the peripheral's IRQ number.
 #[allow(missing_docs)]
-#[derive(Copy, Clone)]
-pub enum Hardware {
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Interrupt {
     Wwdg = NVIC_WWDG_INT,
     Pvdvddio2 = NVIC_PVDVDDIO2_INT,
     Rtc = NVIC_RTC_INT,
@@ -68,6 +68,18 @@ pub enum Hardware {
     Usb = NVIC_USB_INT,
 }
 
+impl Interrupt {
+    /// Get the IRQ number of the interrupt, this is its position in the vector table after the
+    /// system exceptions.
+    pub fn number(self) -> u8 {
+        self as u8
+    }
+}
+
+// The name the interrupts went by before they were shared with the peripheral modules.
+#[doc(hidden)]
+pub use self::Interrupt as Hardware;
+
 /// Get an instance of the nested vector interrupt control.
 pub fn nvic() -> Nvic {
     Nvic::new()
@@ -116,17 +128,17 @@ impl DerefMut for Nvic {
 
 impl RawNvic {
     /// Enable the interrupt for the specified peripheral.
-    pub fn enable_interrupt(&mut self, hardware: Hardware) {
+    pub fn enable_interrupt(&mut self, hardware: Interrupt) {
         self.iser.enable_interrupt(hardware);
     }
 
     /// Disable the interrupt for the specified peripheral.
-    pub fn disable_interrupt(&mut self, hardware: Hardware) {
+    pub fn disable_interrupt(&mut self, hardware: Interrupt) {
         self.icer.disable_interrupt(hardware);
     }
 
     /// Check if the interrupt for the peripheral is enabled.
-    pub fn interrupt_is_enabled(&self, hardware: Hardware) -> bool {
+    pub fn interrupt_is_enabled(&self, hardware: Interrupt) -> bool {
         self.iser.interrupt_is_enabled(hardware)
     }
 
@@ -135,33 +147,56 @@ impl RawNvic {
     /// If the interrupt is enabled, the interrupt handler will be called.
     /// Otherwise, no interrupt will be generated until the interrupt is enabled
     /// for the specified peripheral.
-    pub fn set_pending(&mut self, hardware: Hardware) {
+    pub fn set_pending(&mut self, hardware: Interrupt) {
         self.ispr.set_pending(hardware);
     }
 
     /// Clear the pending interrupt for the specified peripheral.
-    pub fn clear_pending(&mut self, hardware: Hardware) {
+    pub fn clear_pending(&mut self, hardware: Interrupt) {
         self.icpr.clear_pending(hardware);
     }
 
     /// Check if interrupt is pending for the specified peripheral.
-    pub fn interrupt_is_pending(&self, hardware: Hardware) -> bool {
+    pub fn interrupt_is_pending(&self, hardware: Interrupt) -> bool {
         self.ispr.interrupt_is_pending(hardware)
     }
 
     /// Set the priority of the interrupt for the specified peripheral.
-    pub fn set_priority(&mut self, priority: Priority, hardware: Hardware) {
-        let interrupt = hardware as u8;
+    pub fn set_priority(&mut self, priority: Priority, hardware: Interrupt) {
+        let interrupt = hardware.number();
         let ipr_offset = interrupt / 4;
         let priority_offset = interrupt % 4;
         self.ipr[ipr_offset as usize].set_priority(priority, priority_offset);
     }
 
     /// Get the priority of the interrupt for the specified peripheral.
-    pub fn get_priority(&self, hardware: Hardware) -> Priority {
-        let interrupt = hardware as u8;
+    pub fn get_priority(&self, hardware: Interrupt) -> Priority {
+        let interrupt = hardware.number();
         let ipr_offset = interrupt / 4;
         let priority_offset = interrupt % 4;
         self.ipr[ipr_offset as usize].get_priority(priority_offset)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interrupt_number() {
+        assert_eq!(Interrupt::Wwdg.number(), 0);
+        assert_eq!(Interrupt::Exti01.number(), 5);
+        assert_eq!(Interrupt::Dmach1.number(), 9);
+        assert_eq!(Interrupt::Dmach4Plus.number(), 11);
+        assert_eq!(Interrupt::Usart1.number(), 27);
+        assert_eq!(Interrupt::Usart2.number(), 28);
+        assert_eq!(Interrupt::Usb.number(), 31);
+    }
+
+    #[test]
+    fn test_hardware_is_interrupt() {
+        let hardware: Hardware = Hardware::Usart2;
+
+        assert_eq!(hardware, Interrupt::Usart2);
+    }
+}
@@ -15,7 +15,7 @@
 * along with this program. If not, see <http://www.gnu.org/licenses/>.
 */
 
-use interrupt::Hardware;
+use interrupt::Interrupt;
 
 #[derive(Copy, Clone, Debug)]
 pub struct ISPR(u32);
@@ -23,22 +23,22 @@ pub struct ISPR(u32);
 pub struct ICPR(u32);
 
 impl ISPR {
-    pub fn set_pending(&mut self, hardware: Hardware) {
-        let interrupt = hardware as u8;
+    pub fn set_pending(&mut self, hardware: Interrupt) {
+        let interrupt = hardware.number();
 
         self.0 |= 0b1 << interrupt;
     }
 
-    pub fn interrupt_is_pending(&self, hardware: Hardware) -> bool {
-        let interrupt = hardware as u8;
+    pub fn interrupt_is_pending(&self, hardware: Interrupt) -> bool {
+        let interrupt = hardware.number();
 
         self.0 & (0b1 << interrupt) != 0
     }
 }
 
 impl ICPR {
-    pub fn clear_pending(&mut self, hardware: Hardware) {
-        let interrupt = hardware as u8;
+    pub fn clear_pending(&mut self, hardware: Interrupt) {
+        let interrupt = hardware.number();
 
         self.0 |= 0b1 << interrupt;
     }
@@ -47,6 +47,7 @@ impl ICPR {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use interrupt::Hardware;
 
     #[test]
     fn test_ispr_set_pending() {
@@ -71,4 +72,13 @@ mod tests {
         icpr.clear_pending(Hardware::Flash);
         assert_eq!(icpr.0, 0b1 << 3);
     }
+
+    #[test]
+    fn test_ispr_set_pending_by_new_name() {
+        let mut ispr = ISPR(0);
+
+        ispr.set_pending(Interrupt::Usart2);
+        assert!(ispr.interrupt_is_pending(Hardware::Usart2));
+        assert_eq!(ispr.0, 0b1 << 28);
+    }
 }
@@ -0,0 +1,96 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Line ending translation for text sent to a terminal on the serial port.
+
+// Everything the translation needs to send bytes, so it can be checked without the hardware.
+pub trait ByteSink {
+    // Send a single byte.
+    fn put(&mut self, byte: u8);
+}
+
+// Send `s`, with each `\n` sent as `\r\n` so a terminal returns to the start of the line. With
+// the `minicom` feature a `\r` is also preceded by a `\n`.
+pub fn write_str_crlf<S: ByteSink>(sink: &mut S, s: &str) {
+    for &byte in s.as_bytes() {
+        if byte == b'\n' {
+            sink.put(b'\r');
+        }
+        #[cfg(feature="minicom")]
+        {
+            if byte == b'\r' {
+                sink.put(b'\n');
+            }
+        }
+        sink.put(byte);
+    }
+}
+
+// Send `s` and end the line with `\r\n`.
+pub fn write_line<S: ByteSink>(sink: &mut S, s: &str) {
+    write_str_crlf(sink, s);
+    sink.put(b'\r');
+    sink.put(b'\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    struct MockSink(Vec<u8>);
+
+    impl ByteSink for MockSink {
+        fn put(&mut self, byte: u8) {
+            self.0.push(byte);
+        }
+    }
+
+    #[test]
+    fn test_write_str_crlf_without_newlines_is_unchanged() {
+        let mut sink = MockSink(Vec::new());
+
+        write_str_crlf(&mut sink, "hello");
+        assert_eq!(sink.0, b"hello");
+    }
+
+    #[test]
+    #[cfg(not(feature="minicom"))]
+    fn test_write_str_crlf_translates_embedded_newlines() {
+        let mut sink = MockSink(Vec::new());
+
+        write_str_crlf(&mut sink, "one\ntwo\n\nthree");
+        assert_eq!(sink.0, b"one\r\ntwo\r\n\r\nthree");
+    }
+
+    #[test]
+    #[cfg(not(feature="minicom"))]
+    fn test_write_line_appends_crlf() {
+        let mut sink = MockSink(Vec::new());
+
+        write_line(&mut sink, "a\nb");
+        assert_eq!(sink.0, b"a\r\nb\r\n");
+    }
+
+    #[test]
+    fn test_write_line_empty() {
+        let mut sink = MockSink(Vec::new());
+
+        write_line(&mut sink, "");
+        assert_eq!(sink.0, b"\r\n");
+    }
+}
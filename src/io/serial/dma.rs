@@ -16,11 +16,23 @@
 */
 
 
-use altos_core::sync::CriticalSection;
+use altos_core::sync::{CriticalSection, MutexGuard};
 use core::fmt::{self, Write};
-use peripheral::dma::{self, DMAChannel, DMA_TX_CHAN4PLUS};
-use peripheral::usart::{Usart, UsartX};
-use super::WRITE_LOCK;
+use peripheral::dma::{self, DMA, DMAChannel, DMA_TX_CHAN4PLUS};
+use peripheral::usart::{Usart, UsartX, DMAMode};
+use super::{Serial, WRITE_LOCK};
+
+// A frame being received by DMA, waiting for the line to go idle.
+struct FrameReceive {
+    chan: DMAChannel,
+    buf: *mut u8,
+    capacity: usize,
+    received: Option<usize>,
+}
+
+// The frame currently being received, only touched inside a critical section or from the Usart2
+// interrupt handler.
+static mut FRAME_RECEIVE: Option<FrameReceive> = None;
 
 struct DMASerial {
     usart: Usart,
@@ -47,6 +59,96 @@ impl Write for DMASerial {
     }
 }
 
+// How far a DMA transmit has got. The DMA is done once it has written the last byte to TDR, but
+// the Usart is only done once that byte has left the shift register.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum TransmitStage {
+    Transferring,
+    Draining,
+    Done,
+}
+
+// TC can be set before the DMA finishes if the DMA falls behind the Usart between two bytes, so it
+// only means the transmit is done once the DMA has finished as well.
+fn transmit_stage(dma_complete: bool, usart_complete: bool) -> TransmitStage {
+    match (dma_complete, usart_complete) {
+        (false, _) => TransmitStage::Transferring,
+        (true, false) => TransmitStage::Draining,
+        (true, true) => TransmitStage::Done,
+    }
+}
+
+impl Serial {
+    // The TC interrupt is left off on the channel so the DMA interrupt handler doesn't clear the
+    // TCIF that `transmit_dma_stage` polls for.
+    fn transmit_dma(&mut self, chan: DMAChannel, buf: &'static [u8]) {
+        use peripheral::usart::defs::*;
+
+        let mut dma = DMA::new();
+        let tdr = unsafe { USART2_ADDR.offset((TDR_OFFSET/4) as isize) } as *mut u8;
+
+        dma.abort(chan);
+        dma[chan].disable_transmit_complete_interrupt();
+        self.usart.clear_tc_flag();
+        self.usart.set_dma_mode(DMAMode::All);
+        dma.start_transfer(chan, buf, tdr);
+    }
+
+    fn transmit_dma_stage(&self, chan: DMAChannel) -> TransmitStage {
+        let dma = DMA::new();
+        transmit_stage(dma.channel_transfer_complete_flag(chan),
+                       self.usart.is_transmission_complete())
+    }
+
+    fn flush_dma(&mut self, chan: DMAChannel) {
+        while self.transmit_dma_stage(chan) != TransmitStage::Done {}
+        DMA::new().abort(chan);
+    }
+}
+
+/// A transmit started by `transmit_dma`.
+///
+/// The serial port's write lock is held until this is flushed or dropped, so anything printed in
+/// the meantime waits rather than getting mixed into the stream on the TDR.
+pub struct DmaTransmit {
+    serial: Serial,
+    chan: DMAChannel,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl DmaTransmit {
+    /// Check if the transmit is done. This is only true once the last byte has left the Usart as
+    /// well as the DMA, so e.g. an RS-485 driver can be turned off straight after.
+    pub fn is_complete(&self) -> bool {
+        self.serial.transmit_dma_stage(self.chan) == TransmitStage::Done
+    }
+
+    /// Wait for the transmit to be done, as in `is_complete`, then release the channel and the
+    /// serial port.
+    pub fn flush(mut self) {
+        self.serial.flush_dma(self.chan);
+    }
+}
+
+/// Start transmitting `buf` on the serial port by DMA on the specified channel (channel 4 serves
+/// the Usart2 transmitter), returning as soon as the transfer has started. Other writes to the
+/// serial port are held off until the returned `DmaTransmit` is flushed or dropped.
+///
+/// # Panics
+///
+/// The DMA can't transfer more than 65535 bytes at once, a longer `buf` will cause the kernel to
+/// panic.
+pub fn transmit_dma(chan: DMAChannel, buf: &'static [u8]) -> DmaTransmit {
+    let lock = WRITE_LOCK.lock();
+    let mut serial = Serial::new(Usart::new(UsartX::Usart2));
+    serial.transmit_dma(chan, buf);
+    DmaTransmit {
+        serial: serial,
+        chan: chan,
+        _lock: lock,
+    }
+}
+
 #[doc(hidden)]
 pub fn dma_write_str(s: &str) {
     let usart2 = Usart::new(UsartX::Usart2);
@@ -55,3 +157,133 @@ pub fn dma_write_str(s: &str) {
     let _g = WRITE_LOCK.lock();
     dma_serial.write_str(s).ok();
 }
+
+/// Start receiving a frame of unknown length into `buf` by DMA on the specified channel
+/// (channel 5 serves the Usart2 receiver). The frame ends when the line goes idle after it, then
+/// `take_received_frame` hands back the part of `buf` that was filled.
+///
+/// A frame longer than `buf` is cut off at the end of `buf`. Starting a new receive abandons a
+/// frame that hasn't been taken yet. The receive interrupt is off while the frame is being
+/// received, so nothing arrives through the regular receive buffer in the meantime.
+///
+/// # Panics
+///
+/// The DMA can't transfer more than 65535 bytes at once, a longer `buf` will cause the kernel to
+/// panic.
+pub fn receive_frame_dma(chan: DMAChannel, buf: &'static mut [u8]) {
+    use peripheral::usart::defs::*;
+
+    let mut usart2 = Usart::new(UsartX::Usart2);
+    let _g = CriticalSection::begin();
+    let mut dma = DMA::new();
+    let rdr = unsafe { USART2_ADDR.offset((RDR_OFFSET/4) as isize) } as *const u8;
+
+    // The receive interrupt would read RDR before the DMA gets to it, it is turned back on once
+    // the frame ends.
+    usart2.disable_receiver_not_empty_interrupt();
+    dma.start_receive(chan, rdr, buf);
+    unsafe {
+        FRAME_RECEIVE = Some(FrameReceive {
+            chan: chan,
+            buf: buf.as_mut_ptr(),
+            capacity: buf.len(),
+            received: None,
+        });
+    }
+    // The end of the last frame turned off receive requests, put back both directions as init
+    // set them up so the transmit side keeps working.
+    usart2.set_dma_mode(DMAMode::All);
+    usart2.clear_idle_flag();
+    usart2.enable_idle_interrupt();
+}
+
+/// Take the frame started by `receive_frame_dma` once the line has gone idle after it, `None`
+/// if it's still being received.
+pub fn take_received_frame() -> Option<&'static mut [u8]> {
+    let _g = CriticalSection::begin();
+    // UNSAFE: Accessing mutable static
+    let done = match unsafe { &FRAME_RECEIVE } {
+        &Some(FrameReceive { received: Some(_), .. }) => true,
+        _ => false,
+    };
+    if !done {
+        return None;
+    }
+    match unsafe { FRAME_RECEIVE.take() } {
+        Some(FrameReceive { buf, received: Some(len), .. }) => {
+            Some(unsafe { ::core::slice::from_raw_parts_mut(buf, len) })
+        },
+        _ => None,
+    }
+}
+
+// Ends the frame being received when the line goes idle, this should only be called from the
+// Usart2 interrupt handler.
+#[doc(hidden)]
+pub fn dma_receive_idle(mut usart: Usart) {
+    if !usart.is_idle_line_detected() {
+        return;
+    }
+    usart.clear_idle_flag();
+    usart.disable_idle_interrupt();
+
+    // UNSAFE: Accessing mutable static, we're in the interrupt handler
+    if let Some(ref mut frame) = unsafe { FRAME_RECEIVE.as_mut() } {
+        if frame.received.is_none() {
+            let mut dma = DMA::new();
+            let remaining = dma[frame.chan].get_number_of_data();
+            dma.abort_usart(frame.chan, &mut usart);
+            frame.received = Some(received_len(frame.capacity, remaining));
+            usart.enable_receiver_not_empty_interrupt();
+        }
+    }
+}
+
+// The DMA counts CNDTR down from the buffer length as bytes arrive. The count should never be
+// above the capacity, but if it is nothing has been received.
+fn received_len(capacity: usize, remaining: u16) -> usize {
+    capacity.saturating_sub(remaining as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transmit_stage_waits_for_dma() {
+        assert_eq!(transmit_stage(false, false), TransmitStage::Transferring);
+    }
+
+    #[test]
+    fn test_transmit_stage_early_usart_tc_is_not_done() {
+        // TC set between two bytes while the DMA is still running
+        assert_eq!(transmit_stage(false, true), TransmitStage::Transferring);
+    }
+
+    #[test]
+    fn test_transmit_stage_waits_for_shift_register() {
+        assert_eq!(transmit_stage(true, false), TransmitStage::Draining);
+    }
+
+    #[test]
+    fn test_transmit_stage_done() {
+        assert_eq!(transmit_stage(true, true), TransmitStage::Done);
+    }
+
+    #[test]
+    fn test_received_len() {
+        assert_eq!(received_len(64, 64), 0);
+        assert_eq!(received_len(64, 54), 10);
+        assert_eq!(received_len(64, 0), 64);
+    }
+
+    #[test]
+    fn test_received_len_full_size_buffer() {
+        assert_eq!(received_len(0xFFFF, 1), 0xFFFE);
+    }
+
+    #[test]
+    fn test_received_len_count_above_capacity_is_zero() {
+        assert_eq!(received_len(8, 20), 0);
+    }
+}
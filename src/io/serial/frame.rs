@@ -0,0 +1,197 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Reading variable length packets off of the serial port, using an idle line to mark the end of
+//! a packet.
+
+/// Errors that can end a frame read early.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FrameError {
+    /// The timeout expired before any bytes were received.
+    Timeout,
+    /// A byte was received with a bad stop bit.
+    Framing,
+    /// A byte was received with the wrong parity.
+    Parity,
+    /// Noise was detected while receiving a byte.
+    Noise,
+}
+
+// Everything `read_frame` needs from the serial port, so the termination logic can be checked
+// without the hardware.
+pub trait FrameSource {
+    // Take the next received byte, if there is one.
+    fn next_byte(&mut self) -> Option<u8>;
+    // Check for and clear a line error.
+    fn take_error(&mut self) -> Option<FrameError>;
+    // Check for and clear the idle line flag.
+    fn take_idle(&mut self) -> bool;
+    fn timed_out(&self) -> bool;
+}
+
+// Read bytes into `buf` until the line goes idle after receiving at least one byte, the buffer
+// fills, or the source times out.
+pub fn read_frame<S: FrameSource>(source: &mut S, buf: &mut [u8]) -> Result<usize, FrameError> {
+    // Any idle flag still set belongs to a previous frame
+    source.take_idle();
+
+    let mut read = 0;
+    while read < buf.len() {
+        if let Some(byte) = source.next_byte() {
+            buf[read] = byte;
+            read += 1;
+            continue;
+        }
+        if let Some(error) = source.take_error() {
+            return Err(error);
+        }
+        if source.take_idle() && read > 0 {
+            break;
+        }
+        if source.timed_out() {
+            if read == 0 {
+                return Err(FrameError::Timeout);
+            }
+            break;
+        }
+    }
+    Ok(read)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A scripted byte source. Each poll with no byte waiting advances the clock by one step,
+    // `idle_after` and `timeout_after` are the number of empty polls before those conditions hit.
+    struct MockSource<'a> {
+        bytes: &'a [u8],
+        next: usize,
+        polls: usize,
+        idle_after: Option<usize>,
+        timeout_after: usize,
+        error: Option<FrameError>,
+        idle: bool,
+    }
+
+    impl<'a> MockSource<'a> {
+        fn new(bytes: &'a [u8], idle_after: Option<usize>, timeout_after: usize) -> Self {
+            MockSource {
+                bytes: bytes,
+                next: 0,
+                polls: 0,
+                idle_after: idle_after,
+                timeout_after: timeout_after,
+                error: None,
+                idle: false,
+            }
+        }
+    }
+
+    impl<'a> FrameSource for MockSource<'a> {
+        fn next_byte(&mut self) -> Option<u8> {
+            if self.next < self.bytes.len() {
+                self.next += 1;
+                return Some(self.bytes[self.next - 1]);
+            }
+            self.polls += 1;
+            if Some(self.polls) == self.idle_after {
+                self.idle = true;
+            }
+            None
+        }
+
+        fn take_error(&mut self) -> Option<FrameError> {
+            self.error.take()
+        }
+
+        fn take_idle(&mut self) -> bool {
+            let idle = self.idle;
+            self.idle = false;
+            idle
+        }
+
+        fn timed_out(&self) -> bool {
+            self.polls >= self.timeout_after
+        }
+    }
+
+    #[test]
+    fn test_read_frame_ends_on_idle() {
+        let mut source = MockSource::new(&[1, 2, 3], Some(2), 100);
+        let mut buf = [0; 8];
+
+        assert_eq!(read_frame(&mut source, &mut buf), Ok(3));
+        assert_eq!(&buf[..3], &[1, 2, 3]);
+        assert_eq!(source.polls, 2);
+    }
+
+    #[test]
+    fn test_read_frame_ends_when_buffer_full() {
+        let mut source = MockSource::new(&[1, 2, 3, 4, 5], None, 100);
+        let mut buf = [0; 4];
+
+        assert_eq!(read_frame(&mut source, &mut buf), Ok(4));
+        assert_eq!(buf, [1, 2, 3, 4]);
+        assert_eq!(source.next, 4);
+    }
+
+    #[test]
+    fn test_read_frame_timeout_returns_partial_frame() {
+        let mut source = MockSource::new(&[7, 8], None, 5);
+        let mut buf = [0; 8];
+
+        assert_eq!(read_frame(&mut source, &mut buf), Ok(2));
+        assert_eq!(source.polls, 5);
+    }
+
+    #[test]
+    fn test_read_frame_timeout_with_nothing_received() {
+        let mut source = MockSource::new(&[], None, 3);
+        let mut buf = [0; 8];
+
+        assert_eq!(read_frame(&mut source, &mut buf), Err(FrameError::Timeout));
+    }
+
+    #[test]
+    fn test_read_frame_ignores_stale_idle() {
+        let mut source = MockSource::new(&[9], Some(3), 100);
+        source.idle = true;
+        let mut buf = [0; 8];
+
+        assert_eq!(read_frame(&mut source, &mut buf), Ok(1));
+        assert_eq!(source.polls, 3);
+    }
+
+    #[test]
+    fn test_read_frame_idle_before_any_bytes_keeps_waiting() {
+        let mut source = MockSource::new(&[], Some(1), 4);
+        let mut buf = [0; 8];
+
+        assert_eq!(read_frame(&mut source, &mut buf), Err(FrameError::Timeout));
+        assert_eq!(source.polls, 4);
+    }
+
+    #[test]
+    fn test_read_frame_line_error() {
+        let mut source = MockSource::new(&[1], None, 100);
+        source.error = Some(FrameError::Framing);
+        let mut buf = [0; 8];
+
+        assert_eq!(read_frame(&mut source, &mut buf), Err(FrameError::Framing));
+    }
+}
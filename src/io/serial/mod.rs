@@ -17,6 +17,9 @@
 
 #[cfg(feature="dma")]
 mod dma;
+mod frame;
+mod probe;
+mod crlf;
 
 use altos_core::volatile::Volatile;
 use altos_core::syscall::sleep;
@@ -24,9 +27,17 @@ use altos_core::sync::{Mutex, CriticalSection};
 use altos_core::collections::RingBuffer;
 use core::fmt::{self, Write, Arguments};
 use peripheral::usart::{UsartX, Usart, USART2_TX_CHAN, USART2_RX_CHAN};
+use peripheral::rcc::{self, Peripheral};
+use peripheral::gpio::{self, Port};
+use time::{self, Time};
+use self::frame::FrameSource;
+use self::probe::SyncReceiver;
+use self::crlf::ByteSink;
 
 #[cfg(feature="dma")]
 pub use self::dma::*;
+pub use self::frame::FrameError;
+pub use self::probe::Framing;
 
 /// A buffer for transmitting bytes.
 pub static mut TX_BUFFER: RingBuffer = RingBuffer::new();
@@ -58,15 +69,100 @@ macro_rules! println {
     ($fmt:expr, $($arg:tt)*) => (print!(concat!($fmt, "\n"), $($arg)*));
 }
 
-struct Serial {
+/// A handle to the serial port.
+///
+/// The handle doesn't take the locks that the free functions in this module do, so it is up to
+/// the caller to make sure it isn't used at the same time as `print!` or another handle.
+pub struct Serial {
     usart: Usart,
 }
 
 impl Serial {
-    fn new(usart: Usart) -> Self {
+    /// Create a handle to the serial port.
+    ///
+    /// The transmit and receive buffers are serviced by the Usart2 interrupt, so `usart` should
+    /// always be Usart2.
+    pub fn new(usart: Usart) -> Self {
         Serial { usart: usart }
     }
 
+    /// Change the baud rate of the serial port, using the clock rate Usart2 is currently running
+    /// at.
+    ///
+    /// The Usart is briefly disabled to change the rate, so a byte that is in the middle of being
+    /// transmitted may be corrupted.
+    pub fn set_baud_rate(&mut self, baud: u32) {
+        let clock_rate = rcc::rcc().get_peripheral_clock_rate(Peripheral::USART2);
+        self.usart.set_baud_rate_hz(baud, clock_rate);
+    }
+
+    /// Set the serial port up as a node on a multidrop bus, see `configure_multidrop`.
+    ///
+    /// # Panics
+    ///
+    /// The address must be a value between [0..127] or the kernel will panic.
+    pub fn configure_multidrop(&mut self, address: u8, open_drain: bool, invert_rx: bool) {
+        // The TX pin is open-drain on a wired-OR bus, so nodes that aren't transmitting don't
+        // fight the one that is.
+        let mut tx = Port::new(2, gpio::Group::A);
+        if open_drain {
+            tx.set_type(gpio::Type::OpenDrain);
+        }
+        else {
+            tx.set_type(gpio::Type::PushPull);
+        }
+        self.usart.configure_multidrop(address, invert_rx);
+    }
+
+    /// Read a packet from the serial port into `buf`, see `read_frame`.
+    pub fn read_frame(&mut self, buf: &mut [u8], timeout: Time) -> Result<usize, FrameError> {
+        let mut reader = FrameReader {
+            usart: self.usart,
+            deadline: time::now() + timeout,
+        };
+        frame::read_frame(&mut reader, buf)
+    }
+
+    /// Find the framing used by a device that repeatedly sends a known sync byte, see
+    /// `probe_framing`.
+    pub fn probe_framing(&mut self, sync: u8, candidates: &[Framing]) -> Option<usize> {
+        let mut receiver = ProbeReceiver { usart: self.usart };
+        probe::probe_framing(&mut receiver, sync, candidates)
+    }
+
+    // Wait for everything buffered to be handed to the Usart, then for the last byte to leave
+    // the shift register.
+    fn flush(&mut self) {
+        // UNSAFE: Accessing mutable static
+        while unsafe {
+            let _g = CriticalSection::begin();
+            !Volatile::new(&TX_BUFFER).is_empty()
+        } {}
+        self.usart.wait_transmission_complete();
+    }
+
+    /// Send `s` with each `\n` translated to `\r\n`, for text going to a terminal.
+    ///
+    /// Writing through `fmt::Write` sends the bytes as they are.
+    pub fn write_str_crlf(&mut self, s: &str) {
+        crlf::write_str_crlf(self, s);
+        self.start_transmit();
+    }
+
+    /// Send `s` with each `\n` translated to `\r\n`, followed by `\r\n`.
+    pub fn write_line(&mut self, s: &str) {
+        crlf::write_line(self, s);
+        self.start_transmit();
+    }
+
+    // Let the transmit interrupt drain the buffer, sleeping until it has.
+    fn start_transmit(&mut self) {
+        let g = CriticalSection::begin();
+        self.usart.enable_transmit_interrupt();
+        ::altos_core::syscall::sys_sleep(USART2_TX_CHAN);
+        drop(g);
+    }
+
     fn buffer_byte(&mut self, byte: u8) {
         unsafe {
             while !TX_BUFFER.insert(byte) {
@@ -103,24 +199,119 @@ impl Serial {
     }
 }
 
+// Reads a frame out of the receive buffer, watching the Usart for line errors and idle.
+struct FrameReader {
+    usart: Usart,
+    deadline: Time,
+}
+
+impl FrameSource for FrameReader {
+    fn next_byte(&mut self) -> Option<u8> {
+        let _g = CriticalSection::begin();
+        // UNSAFE: Accessing mutable static
+        unsafe { RX_BUFFER.remove() }
+    }
+
+    fn take_error(&mut self) -> Option<FrameError> {
+        // Overruns are cleared by the receive interrupt, so they can't be reported here
+        if self.usart.is_framing_error() {
+            self.usart.clear_fe_flag();
+            Some(FrameError::Framing)
+        }
+        else if self.usart.is_parity_error() {
+            self.usart.clear_pe_flag();
+            Some(FrameError::Parity)
+        }
+        else if self.usart.is_noise_detected() {
+            self.usart.clear_nf_flag();
+            Some(FrameError::Noise)
+        }
+        else {
+            None
+        }
+    }
+
+    fn take_idle(&mut self) -> bool {
+        let idle = self.usart.is_idle_line_detected();
+        if idle {
+            self.usart.clear_idle_flag();
+        }
+        idle
+    }
+
+    fn timed_out(&self) -> bool {
+        time::now() >= self.deadline
+    }
+}
+
+// How long to wait for each byte while probing.
+const PROBE_TIMEOUT: Time = Time { sec: 0, msec: 100 };
+
+// Receives bytes out of the receive buffer while probing, watching the Usart for errors.
+struct ProbeReceiver {
+    usart: Usart,
+}
+
+impl SyncReceiver for ProbeReceiver {
+    fn configure(&mut self, framing: Framing) {
+        let (length, parity, stop) = framing;
+        self.usart.disable_usart();
+        self.usart.set_word_length(length);
+        self.usart.set_parity(parity);
+        self.usart.set_stop_bits(stop);
+        self.usart.clear_fe_flag();
+        self.usart.clear_pe_flag();
+        self.usart.enable_usart();
+
+        let _g = CriticalSection::begin();
+        // UNSAFE: Accessing mutable static
+        unsafe { while RX_BUFFER.remove().is_some() {} }
+    }
+
+    fn receive(&mut self) -> Option<Result<u8, ()>> {
+        let deadline = time::now() + PROBE_TIMEOUT;
+        while time::now() < deadline {
+            if self.usart.is_framing_error() || self.usart.is_parity_error() {
+                self.usart.clear_fe_flag();
+                self.usart.clear_pe_flag();
+                return Some(Err(()));
+            }
+            let g = CriticalSection::begin();
+            // UNSAFE: Accessing mutable static
+            let byte = unsafe { RX_BUFFER.remove() };
+            drop(g);
+            if let Some(byte) = byte {
+                return Some(Ok(byte));
+            }
+        }
+        None
+    }
+}
+
+impl ByteSink for Serial {
+    fn put(&mut self, byte: u8) {
+        self.buffer_byte(byte);
+    }
+}
+
+/// The bytes are sent as they are. For text going to a terminal, use `Serial::write_str_crlf` or
+/// `Serial::write_line` so line endings are sent as CRLF.
 impl Write for Serial {
     fn write_str(&mut self, string: &str) -> fmt::Result {
         for byte in string.as_bytes() {
-            if *byte == b'\n' {
-                self.buffer_byte(b'\r');
-            }
-            #[cfg(feature="minicom")]
-            {
-                if *byte == b'\r' {
-                    self.buffer_byte(b'\n');
-                }
-            }
             self.buffer_byte(*byte);
         }
-        let g = CriticalSection::begin();
-        self.usart.enable_transmit_interrupt();
-        ::altos_core::syscall::sys_sleep(USART2_TX_CHAN);
-        drop(g);
+        self.start_transmit();
+        Ok(())
+    }
+}
+
+// Formats text onto the serial port with `\n` translated to `\r\n`.
+struct Console<'a>(&'a mut Serial);
+
+impl<'a> Write for Console<'a> {
+    fn write_str(&mut self, string: &str) -> fmt::Result {
+        self.0.write_str_crlf(string);
         Ok(())
     }
 }
@@ -160,7 +351,7 @@ pub fn write_fmt(args: Arguments) {
     let mut serial = Serial::new(usart2);
 
     let _g = WRITE_LOCK.lock();
-    serial.write_fmt(args).ok();
+    Console(&mut serial).write_fmt(args).ok();
 }
 
 #[doc(hidden)]
@@ -168,6 +359,25 @@ pub fn write_str(s: &str) {
     let usart2 = Usart::new(UsartX::Usart2);
     let mut serial = Serial::new(usart2);
 
+    let _g = WRITE_LOCK.lock();
+    serial.write_str_crlf(s);
+}
+
+/// Write a line of text to the serial port, with each `\n` in it sent as `\r\n` and `\r\n` sent
+/// at the end, so a terminal shows each line starting at the left edge.
+pub fn write_line(s: &str) {
+    let usart2 = Usart::new(UsartX::Usart2);
+    let mut serial = Serial::new(usart2);
+
+    let _g = WRITE_LOCK.lock();
+    serial.write_line(s);
+}
+
+/// Write bytes to the serial port exactly as they are, without any line ending translation.
+pub fn write_raw(s: &str) {
+    let usart2 = Usart::new(UsartX::Usart2);
+    let mut serial = Serial::new(usart2);
+
     let _g = WRITE_LOCK.lock();
     serial.write_str(s).ok();
 }
@@ -192,6 +402,76 @@ pub fn debug_str(s: &str) {
     serial.write_str(s).ok();
 }
 
+/// Change the baud rate of the serial port.
+///
+/// The Usart is briefly disabled to change the rate, so a byte that is in the middle of being
+/// transmitted may be corrupted.
+pub fn set_baud_rate(baud: u32) {
+    let usart2 = Usart::new(UsartX::Usart2);
+    let mut serial = Serial::new(usart2);
+
+    let _g = WRITE_LOCK.lock();
+    serial.set_baud_rate(baud);
+}
+
+/// Set the serial port up as a node on a multidrop bus.
+///
+/// The TX pin is made open-drain if `open_drain` is set (push-pull otherwise), and the Usart is
+/// given the 7-bit `address` and put in mute mode until a frame with that address arrives. The
+/// RX pin level is inverted if `invert_rx` is set.
+///
+/// # Panics
+///
+/// The address must be a value between [0..127] or the kernel will panic.
+pub fn configure_multidrop(address: u8, open_drain: bool, invert_rx: bool) {
+    let usart2 = Usart::new(UsartX::Usart2);
+    let mut serial = Serial::new(usart2);
+
+    let _g = WRITE_LOCK.lock();
+    serial.configure_multidrop(address, open_drain, invert_rx);
+}
+
+/// Block until everything written to the serial port has been transmitted.
+///
+/// `print!` and friends return once the bytes are buffered, and the last byte may still be
+/// going out on the wire after the buffer empties. This waits until the line is actually idle,
+/// which is needed before switching the direction of an RS-485 transceiver.
+pub fn flush() {
+    let usart2 = Usart::new(UsartX::Usart2);
+    let mut serial = Serial::new(usart2);
+
+    let _g = WRITE_LOCK.lock();
+    serial.flush();
+}
+
+/// Read a packet from the serial port into `buf`, returning the number of bytes read.
+///
+/// The packet ends when the line goes idle for a frame after at least one byte has been
+/// received, when `buf` is full, or when `timeout` runs out, whichever comes first. If the
+/// timeout runs out before anything is received `FrameError::Timeout` is returned.
+///
+/// This busy waits on the receive buffer, so it will hold the CPU for up to `timeout`.
+pub fn read_frame(buf: &mut [u8], timeout: Time) -> Result<usize, FrameError> {
+    let usart2 = Usart::new(UsartX::Usart2);
+    let mut serial = Serial::new(usart2);
+
+    let _g = READ_LOCK.lock();
+    serial.read_frame(buf, timeout)
+}
+
+/// Find the framing used by a device that repeatedly sends a known sync byte.
+///
+/// Each of the candidate framings is tried in order, and the index of the first one that
+/// receives the sync byte without a framing or parity error is returned. The serial port is left
+/// configured with that framing, or with the last candidate if none of them matched.
+pub fn probe_framing(sync: u8, candidates: &[Framing]) -> Option<usize> {
+    let usart2 = Usart::new(UsartX::Usart2);
+    let mut serial = Serial::new(usart2);
+
+    let _g = READ_LOCK.lock();
+    serial.probe_framing(sync, candidates)
+}
+
 #[doc(hidden)]
 pub fn poll_char() -> Option<u8> {
     let usart2 = Usart::new(UsartX::Usart2);
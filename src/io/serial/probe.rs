@@ -0,0 +1,149 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Discovering the framing used by a device by listening for a byte it's known to send.
+
+use peripheral::usart::{WordLength, Parity, StopLength};
+
+/// A word length, parity and stop length combination to try when probing.
+pub type Framing = (WordLength, Parity, StopLength);
+
+// The number of bytes that have to come in clean and match the sync byte before a framing is
+// accepted. The first byte after reconfiguring can be a partial frame, so one isn't enough.
+const PROBE_SAMPLES: usize = 2;
+
+// Everything `probe_framing` needs from the serial port, so the candidate selection can be
+// checked without the hardware.
+pub trait SyncReceiver {
+    // Switch the receiver over to the framing, dropping anything received with the old one.
+    fn configure(&mut self, framing: Framing);
+    // Wait for the next byte. `None` if nothing arrived, `Some(Err(()))` if the byte had a
+    // framing or parity error.
+    fn receive(&mut self) -> Option<Result<u8, ()>>;
+}
+
+// Try each of the candidate framings in order, returning the index of the first one that
+// receives the sync byte cleanly.
+pub fn probe_framing<R: SyncReceiver>(receiver: &mut R, sync: u8, candidates: &[Framing])
+    -> Option<usize> {
+
+    for (index, &framing) in candidates.iter().enumerate() {
+        receiver.configure(framing);
+        if (0..PROBE_SAMPLES).all(|_| receiver.receive() == Some(Ok(sync))) {
+            return Some(index);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Pretends to be a device sending `sync` with the framing at index `actual`. Any other
+    // framing either sees a framing error or garbage, depending on `garbage`.
+    struct MockReceiver {
+        sync: u8,
+        actual: usize,
+        current: usize,
+        configured: usize,
+        garbage: bool,
+    }
+
+    impl MockReceiver {
+        fn new(sync: u8, actual: usize, garbage: bool) -> Self {
+            MockReceiver {
+                sync: sync,
+                actual: actual,
+                current: 0,
+                configured: 0,
+                garbage: garbage,
+            }
+        }
+    }
+
+    impl SyncReceiver for MockReceiver {
+        fn configure(&mut self, _framing: Framing) {
+            self.current = self.configured;
+            self.configured += 1;
+        }
+
+        fn receive(&mut self) -> Option<Result<u8, ()>> {
+            if self.current == self.actual {
+                Some(Ok(self.sync))
+            }
+            else if self.garbage {
+                Some(Ok(!self.sync))
+            }
+            else {
+                Some(Err(()))
+            }
+        }
+    }
+
+    struct SilentReceiver;
+
+    impl SyncReceiver for SilentReceiver {
+        fn configure(&mut self, _framing: Framing) {}
+
+        fn receive(&mut self) -> Option<Result<u8, ()>> {
+            None
+        }
+    }
+
+    fn candidates() -> [Framing; 3] {
+        [(WordLength::Eight, Parity::None, StopLength::One),
+         (WordLength::Eight, Parity::Even, StopLength::One),
+         (WordLength::Seven, Parity::Odd, StopLength::Two)]
+    }
+
+    #[test]
+    fn test_probe_framing_finds_matching_candidate() {
+        let mut receiver = MockReceiver::new(0x55, 1, false);
+
+        assert_eq!(probe_framing(&mut receiver, 0x55, &candidates()), Some(1));
+        // Stops as soon as it finds a match
+        assert_eq!(receiver.configured, 2);
+    }
+
+    #[test]
+    fn test_probe_framing_rejects_wrong_bytes() {
+        let mut receiver = MockReceiver::new(0x55, 2, true);
+
+        assert_eq!(probe_framing(&mut receiver, 0x55, &candidates()), Some(2));
+    }
+
+    #[test]
+    fn test_probe_framing_no_match() {
+        let mut receiver = MockReceiver::new(0x55, 5, false);
+
+        assert_eq!(probe_framing(&mut receiver, 0x55, &candidates()), None);
+        assert_eq!(receiver.configured, 3);
+    }
+
+    #[test]
+    fn test_probe_framing_nothing_received() {
+        assert_eq!(probe_framing(&mut SilentReceiver, 0x55, &candidates()), None);
+    }
+
+    #[test]
+    fn test_probe_framing_no_candidates() {
+        let mut receiver = MockReceiver::new(0x55, 0, false);
+
+        assert_eq!(probe_framing(&mut receiver, 0x55, &[]), None);
+    }
+}
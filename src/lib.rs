@@ -39,6 +39,10 @@ extern crate altos_macros;
 
 pub extern crate arm;
 extern crate volatile_ptr as volatile;
+#[cfg(feature="embedded-hal")]
+extern crate hal;
+#[cfg(feature="embedded-hal")]
+extern crate nb;
 //pub extern crate compiler_builtins; // See above comment
 
 #[macro_use]
@@ -51,6 +55,7 @@ pub mod time;
 
 use peripheral::gpio;
 use peripheral::rcc;
+use peripheral::flash;
 use peripheral::systick;
 
 #[cfg(target_arch="arm")]
@@ -214,6 +219,10 @@ fn init_clock() {
     // Set the multiplier... DO NOT EXCEED 48 MHz
     rcc.set_pll_multiplier(clock_multiplier);
 
+    // The prefetch buffer can only be turned on while we're still running at 24 MHz or less
+    let mut flash = flash::flash();
+    flash.set_prefetch(true);
+
     // Enable the PLL clock
     rcc.enable_clock(rcc::Clock::PLL);
 
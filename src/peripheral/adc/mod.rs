@@ -0,0 +1,90 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! This module provides helpers for using the ADC.
+
+use peripheral::gpio::{Port, Group, Mode, Pull};
+
+/// Configure a pin as an ADC input, returning the ADC channel it's connected to.
+///
+/// The pin is put into analog mode with its pull resistors off, since a pull would skew the
+/// reading. The STM32F0 ADC inputs are wired straight to their pins, so unlike some other parts
+/// there is no analog switch in the SYSCFG to configure. The group must already be enabled.
+///
+/// Returns `None` without touching the pin if it isn't connected to an ADC channel.
+pub fn configure_input_pin(group: Group, port: u8) -> Option<u8> {
+    let channel = match input_channel(group, port) {
+        Some(channel) => channel,
+        None => return None,
+    };
+    let mut pin = Port::new(port, group);
+    pin.set_pull(Pull::Neither);
+    pin.set_mode(Mode::Analog);
+    Some(channel)
+}
+
+/// Get the ADC channel connected to a pin, if any.
+pub fn input_channel(group: Group, port: u8) -> Option<u8> {
+    match (group, port) {
+        // PA0..PA7 are ADC_IN0..ADC_IN7
+        (Group::A, 0...7) => Some(port),
+        // PB0 and PB1 are ADC_IN8 and ADC_IN9
+        (Group::B, 0...1) => Some(port + 8),
+        // PC0..PC5 are ADC_IN10..ADC_IN15
+        (Group::C, 0...5) => Some(port + 10),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_channel_group_a() {
+        for port in 0..8 {
+            assert_eq!(input_channel(Group::A, port), Some(port));
+        }
+        assert_eq!(input_channel(Group::A, 8), None);
+    }
+
+    #[test]
+    fn test_input_channel_group_b() {
+        assert_eq!(input_channel(Group::B, 0), Some(8));
+        assert_eq!(input_channel(Group::B, 1), Some(9));
+        assert_eq!(input_channel(Group::B, 2), None);
+    }
+
+    #[test]
+    fn test_input_channel_group_c() {
+        assert_eq!(input_channel(Group::C, 0), Some(10));
+        assert_eq!(input_channel(Group::C, 5), Some(15));
+        assert_eq!(input_channel(Group::C, 6), None);
+    }
+
+    #[test]
+    fn test_input_channel_group_f_has_no_inputs() {
+        for port in 0..16 {
+            assert_eq!(input_channel(Group::F, port), None);
+        }
+    }
+
+    #[test]
+    fn test_input_channel_port_greater_than_15_is_none() {
+        assert_eq!(input_channel(Group::A, 16), None);
+    }
+}
@@ -0,0 +1,91 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! This module exposes the base addresses of the memory mapped peripherals.
+//!
+//! These are intended for advanced use where the provided interfaces don't cover what you need.
+//! Any access through these addresses should be done with volatile reads and writes.
+
+/// GPIO Group A base address.
+pub const GPIOA_ADDR: *const u32 = 0x4800_0000 as *const _;
+/// GPIO Group B base address.
+pub const GPIOB_ADDR: *const u32 = 0x4800_0400 as *const _;
+/// GPIO Group C base address.
+pub const GPIOC_ADDR: *const u32 = 0x4800_0800 as *const _;
+/// GPIO Group F base address.
+pub const GPIOF_ADDR: *const u32 = 0x4800_1400 as *const _;
+
+/// Reset and Clock Controller base address.
+pub const RCC_ADDR: *const u32 = 0x4002_1000 as *const _;
+
+/// Flash Memory Interface base address.
+pub const FLASH_ADDR: *const u32 = 0x4002_2000 as *const _;
+
+/// Power Controller base address.
+pub const PWR_ADDR: *const u32 = 0x4000_7000 as *const _;
+
+/// System Configuration Controller base address.
+pub const SYSCFG_ADDR: *const u32 = 0x4001_0000 as *const _;
+/// Extended Interrupts and Events Controller base address.
+pub const EXTI_ADDR: *const u32 = 0x4001_0400 as *const _;
+
+/// DMA base address.
+pub const DMA_ADDR: *const u32 = 0x4002_0000 as *const _;
+
+/// USART1 base address.
+pub const USART1_ADDR: *const u32 = 0x4001_3800 as *const _;
+/// USART2 base address.
+pub const USART2_ADDR: *const u32 = 0x4000_4400 as *const _;
+
+/// SysTick timer base address.
+pub const SYSTICK_ADDR: *const u32 = 0xE000_E010 as *const _;
+/// Nested Vectored Interrupt Controller base address.
+pub const NVIC_ADDR: *const u32 = 0xE000_E100 as *const _;
+/// System Control Block base address.
+pub const SCB_ADDR: *const u32 = 0xE000_ED00 as *const _;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gpio_addresses_match_memory_map() {
+        assert_eq!(GPIOA_ADDR as usize, 0x4800_0000);
+        assert_eq!(GPIOB_ADDR as usize, 0x4800_0400);
+        assert_eq!(GPIOC_ADDR as usize, 0x4800_0800);
+        assert_eq!(GPIOF_ADDR as usize, 0x4800_1400);
+    }
+
+    #[test]
+    fn test_bus_peripheral_addresses_match_memory_map() {
+        assert_eq!(RCC_ADDR as usize, 0x4002_1000);
+        assert_eq!(FLASH_ADDR as usize, 0x4002_2000);
+        assert_eq!(PWR_ADDR as usize, 0x4000_7000);
+        assert_eq!(SYSCFG_ADDR as usize, 0x4001_0000);
+        assert_eq!(EXTI_ADDR as usize, 0x4001_0400);
+        assert_eq!(DMA_ADDR as usize, 0x4002_0000);
+        assert_eq!(USART1_ADDR as usize, 0x4001_3800);
+        assert_eq!(USART2_ADDR as usize, 0x4000_4400);
+    }
+
+    #[test]
+    fn test_core_peripheral_addresses_match_memory_map() {
+        assert_eq!(SYSTICK_ADDR as usize, 0xE000_E010);
+        assert_eq!(NVIC_ADDR as usize, 0xE000_E100);
+        assert_eq!(SCB_ADDR as usize, 0xE000_ED00);
+    }
+}
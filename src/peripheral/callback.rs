@@ -0,0 +1,85 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! This module provides the slot that peripherals keep their interrupt callbacks in.
+
+use altos_core::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
+
+// An empty slot, used to initialize the statics holding the callbacks.
+pub const CALLBACK_SLOT_INIT: CallbackSlot = CallbackSlot(ATOMIC_USIZE_INIT);
+
+// Holds the address of a `fn()` so it can be registered by a task and run from an interrupt
+// handler. A value of 0 means no callback has been registered.
+pub struct CallbackSlot(AtomicUsize);
+
+impl CallbackSlot {
+    pub fn store(&self, callback: fn()) {
+        self.0.store(callback as usize, Ordering::SeqCst);
+    }
+
+    pub fn clear(&self) {
+        self.0.store(0, Ordering::SeqCst);
+    }
+
+    pub fn load(&self) -> Option<fn()> {
+        match self.0.load(Ordering::SeqCst) {
+            0 => None,
+            // `store` is the only way to put a value other than 0 in the slot, so this is always
+            // a valid `fn()` pointer
+            raw => Some(unsafe { ::core::mem::transmute::<usize, fn()>(raw) }),
+        }
+    }
+
+    // Run the callback if one has been registered.
+    pub fn run(&self) {
+        if let Some(callback) = self.load() {
+            callback();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static CALLS: AtomicUsize = ATOMIC_USIZE_INIT;
+
+    fn counting_callback() {
+        CALLS.fetch_add(1, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_empty_slot_does_nothing() {
+        let slot = CALLBACK_SLOT_INIT;
+
+        assert!(slot.load().is_none());
+        slot.run();
+    }
+
+    #[test]
+    fn test_slot_runs_stored_callback() {
+        let slot = CALLBACK_SLOT_INIT;
+
+        slot.store(counting_callback);
+        assert_eq!(slot.load().map(|f| f as usize), Some(counting_callback as usize));
+        slot.run();
+        assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+
+        slot.clear();
+        assert!(slot.load().is_none());
+    }
+}
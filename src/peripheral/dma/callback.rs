@@ -0,0 +1,218 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! This module keeps track of the callbacks to run when a DMA channel finishes a transfer.
+
+use peripheral::callback::{CallbackSlot, CALLBACK_SLOT_INIT};
+use super::{RawDMA, DMAChannel};
+
+// One slot per channel.
+static CALLBACKS: [CallbackSlot; 5] = [
+    CALLBACK_SLOT_INIT,
+    CALLBACK_SLOT_INIT,
+    CALLBACK_SLOT_INIT,
+    CALLBACK_SLOT_INIT,
+    CALLBACK_SLOT_INIT,
+];
+
+impl RawDMA {
+    /// Register a function to be called from the DMA interrupt whenever the channel completes a
+    /// transfer, or half of one if the half transfer interrupt is enabled. Registering a new
+    /// callback replaces the old one.
+    ///
+    /// The channel's flags are still set while the callback runs, so it can check which event
+    /// happened. They are cleared once it returns.
+    pub fn on_complete(&mut self, chan: DMAChannel, callback: fn()) {
+        CALLBACKS[chan as usize].store(callback);
+    }
+
+    /// Stop calling the channel's completion callback.
+    pub fn clear_on_complete(&mut self, chan: DMAChannel) {
+        CALLBACKS[chan as usize].clear();
+    }
+
+    // Check each of the channels for a pending interrupt, running the channel's callback for a
+    // completed (or half completed) transfer and then clearing the flags that were set. This
+    // should only be called from the DMA interrupt handlers.
+    #[doc(hidden)]
+    pub fn dispatch_complete(&mut self, channels: &[DMAChannel]) {
+        dispatch(self, &CALLBACKS, channels);
+    }
+}
+
+fn dispatch(dma: &mut RawDMA, callbacks: &[CallbackSlot], channels: &[DMAChannel]) {
+    for &chan in channels {
+        if !dma.any_interrupt(chan) {
+            continue;
+        }
+        let complete = dma.channel_transfer_complete_flag(chan);
+        let half = dma.channel_half_transfer_flag(chan);
+        let error = dma.channel_transfer_error_flag(chan);
+
+        if complete || half {
+            callbacks[chan as usize].run();
+        }
+
+        // Acknowledge only what was seen, a flag raised while the callback ran fires again
+        if complete {
+            dma.channel_transfer_complete_clear(chan);
+        }
+        if half {
+            dma.channel_half_transfer_clear(chan);
+        }
+        if error {
+            dma.channel_transfer_error_clear(chan);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use peripheral::raw_word;
+    use super::super::defs::*;
+    use altos_core::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
+    use core::{mem, ptr};
+
+    static CALLED_ONE: AtomicUsize = ATOMIC_USIZE_INIT;
+    static CALLED_TWO: AtomicUsize = ATOMIC_USIZE_INIT;
+    static CALLED_THREE: AtomicUsize = ATOMIC_USIZE_INIT;
+
+    fn on_one() { CALLED_ONE.fetch_add(1, Ordering::SeqCst); }
+    fn on_two() { CALLED_TWO.fetch_add(1, Ordering::SeqCst); }
+    fn on_three() { CALLED_THREE.fetch_add(1, Ordering::SeqCst); }
+
+    // Like the hardware, the GIF flag is set for every channel that has any other flag set.
+    fn mock_dma(flags: u32) -> RawDMA {
+        let mut isr = flags;
+        for chan in 0..5 {
+            if flags & ((DMA_TCIF_1 | DMA_HTIF_1 | DMA_TEIF_1) << (4 * chan)) != 0 {
+                isr |= DMA_GIF_1 << (4 * chan);
+            }
+        }
+        let mut dma: RawDMA = unsafe { mem::zeroed() };
+        unsafe { ptr::write(&mut dma.isr as *mut _ as *mut u32, isr); }
+        dma
+    }
+
+    static SEEN_IFCR: AtomicUsize = ATOMIC_USIZE_INIT;
+    static MOCK_ADDR: AtomicUsize = ATOMIC_USIZE_INIT;
+    static CALLED_HALF: AtomicUsize = ATOMIC_USIZE_INIT;
+    static CALLED_ERROR: AtomicUsize = ATOMIC_USIZE_INIT;
+
+    fn on_seen() {
+        let dma = MOCK_ADDR.load(Ordering::SeqCst) as *const RawDMA;
+        SEEN_IFCR.store(raw_word(unsafe { &(*dma).ifcr }) as usize + 1, Ordering::SeqCst);
+    }
+    fn on_half() { CALLED_HALF.fetch_add(1, Ordering::SeqCst); }
+    fn on_error() { CALLED_ERROR.fetch_add(1, Ordering::SeqCst); }
+
+    fn empty_slots() -> [CallbackSlot; 5] {
+        [CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT,
+         CALLBACK_SLOT_INIT]
+    }
+
+    fn slots() -> [CallbackSlot; 5] {
+        let slots = empty_slots();
+        slots[0].store(on_one);
+        slots[1].store(on_two);
+        slots[2].store(on_three);
+        slots
+    }
+
+    fn single_slot(chan: DMAChannel, callback: fn()) -> [CallbackSlot; 5] {
+        let slots = empty_slots();
+        slots[chan as usize].store(callback);
+        slots
+    }
+
+    #[test]
+    fn test_dispatch_runs_callback_for_completed_channel() {
+        // Transfer complete on channel 2 only
+        let mut dma = mock_dma(DMA_TCIF_1 << 4);
+        let callbacks = slots();
+
+        dispatch(&mut dma, &callbacks, &[DMAChannel::One, DMAChannel::Two, DMAChannel::Three]);
+        assert_eq!(CALLED_ONE.load(Ordering::SeqCst), 0);
+        assert_eq!(CALLED_TWO.load(Ordering::SeqCst), 1);
+        assert_eq!(CALLED_THREE.load(Ordering::SeqCst), 0);
+        assert_eq!(raw_word(&dma.ifcr), DMA_TCIF_1 << 4);
+    }
+
+    #[test]
+    fn test_dispatch_ignores_channels_not_listed() {
+        // Transfer complete on channel 1, but only channel 3 is checked
+        let mut dma = mock_dma(DMA_TCIF_1);
+        let callbacks = slots();
+
+        dispatch(&mut dma, &callbacks, &[DMAChannel::Three]);
+        assert_eq!(CALLED_ONE.load(Ordering::SeqCst), 0);
+        assert_eq!(raw_word(&dma.ifcr), 0);
+    }
+
+    #[test]
+    fn test_dispatch_without_callback_still_clears_flag() {
+        let mut dma = mock_dma(DMA_TCIF_1 << 16);
+        let callbacks = slots();
+
+        dispatch(&mut dma, &callbacks, &[DMAChannel::Five]);
+        assert_eq!(raw_word(&dma.ifcr), DMA_TCIF_1 << 16);
+    }
+
+    #[test]
+    fn test_dispatch_skips_channel_without_gif() {
+        // TCIF without GIF can't happen in hardware, the GIF check alone decides
+        let mut dma: RawDMA = unsafe { mem::zeroed() };
+        unsafe { ptr::write(&mut dma.isr as *mut _ as *mut u32, DMA_TCIF_1 << 8); }
+        let callbacks = slots();
+
+        dispatch(&mut dma, &callbacks, &[DMAChannel::Three]);
+        assert_eq!(raw_word(&dma.ifcr), 0);
+    }
+
+    #[test]
+    fn test_dispatch_runs_callback_before_clearing_flags() {
+        let mut dma = mock_dma(DMA_TCIF_1 << 12);
+        MOCK_ADDR.store(&dma as *const RawDMA as usize, Ordering::SeqCst);
+        let callbacks = single_slot(DMAChannel::Four, on_seen);
+
+        dispatch(&mut dma, &callbacks, &[DMAChannel::Four]);
+        // Nothing had been cleared yet when the callback ran
+        assert_eq!(SEEN_IFCR.load(Ordering::SeqCst), 1);
+        assert_eq!(raw_word(&dma.ifcr), DMA_TCIF_1 << 12);
+    }
+
+    #[test]
+    fn test_dispatch_half_transfer_runs_callback_and_clears_flag() {
+        let mut dma = mock_dma(DMA_HTIF_1);
+        let callbacks = single_slot(DMAChannel::One, on_half);
+
+        dispatch(&mut dma, &callbacks, &[DMAChannel::One]);
+        assert_eq!(CALLED_HALF.load(Ordering::SeqCst), 1);
+        assert_eq!(raw_word(&dma.ifcr), DMA_CHTIF_1);
+    }
+
+    #[test]
+    fn test_dispatch_transfer_error_clears_flag_without_callback() {
+        let mut dma = mock_dma(DMA_TEIF_1 << 4);
+        let callbacks = single_slot(DMAChannel::Two, on_error);
+
+        dispatch(&mut dma, &callbacks, &[DMAChannel::Two]);
+        assert_eq!(CALLED_ERROR.load(Ordering::SeqCst), 0);
+        assert_eq!(raw_word(&dma.ifcr), DMA_CTEIF_1 << 4);
+    }
+}
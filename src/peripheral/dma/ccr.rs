@@ -18,7 +18,7 @@
 use super::defs::*;
 
 /// Defines the possible directions that data can be read from.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum DataDirection {
     /// Data is read from the peripheral.
     FromPeriph,
@@ -70,6 +70,10 @@ impl CCR {
         }
     }
 
+    pub fn is_dma_enabled(&self) -> bool {
+        self.0 & CCR_EN != 0
+    }
+
     /* Bit 1 TCIE: Transfer complete interrupt enable
      *  This bit is set and cleared by software.
      *  0: TC interrupt disabled
@@ -121,6 +125,15 @@ impl CCR {
         self.0 |= mask;
     }
 
+    pub fn get_data_transfer_direction(&self) -> DataDirection {
+        if self.0 & CCR_DIR == 0 {
+            DataDirection::FromPeriph
+        }
+        else {
+            DataDirection::FromMem
+        }
+    }
+
     /* Bit 5 CIRC: Circular mode
      *  This bit is set and cleared by software.
      *  0: Circular mode disabled
@@ -229,6 +242,13 @@ impl CCR {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_ccr_is_dma_enabled() {
+        assert!(CCR(0b1).is_dma_enabled());
+        assert!(CCR(0b1 | 0b1 << 1 | 0b1 << 4).is_dma_enabled());
+        assert!(!CCR(0b1 << 1 | 0b1 << 4).is_dma_enabled());
+    }
+
     #[test]
     fn test_ccr_enable_disable_dma() {
         let mut ccr = CCR(0);
@@ -289,6 +309,15 @@ mod tests {
         assert_eq!(ccr.0, 0b0);
     }
 
+    #[test]
+    fn test_ccr_get_data_transfer_direction() {
+        let mut ccr = CCR(0);
+        assert_eq!(ccr.get_data_transfer_direction(), DataDirection::FromPeriph);
+
+        ccr.set_data_transfer_direction(DataDirection::FromMem);
+        assert_eq!(ccr.get_data_transfer_direction(), DataDirection::FromMem);
+    }
+
     #[test]
     fn test_ccr_enable_circular_mode() {
         let mut ccr = CCR(0);
@@ -0,0 +1,122 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! This module provides compile time channel selection for the DMA.
+//!
+//! Each channel is a unit type implementing `DmaChannel`, so the location of the channel's
+//! registers is known at compile time instead of being looked up from a `DMAChannel` at runtime.
+
+use super::DMAChannel;
+use super::defs::*;
+
+/// A DMA channel known at compile time.
+pub trait DmaChannel {
+    /// The runtime channel this type corresponds to.
+    const CHANNEL: DMAChannel;
+    /// The index of the channel's registers within the DMA register block.
+    const INDEX: usize;
+    /// The offset of the channel's CCR from the DMA base address.
+    const CCR_OFFSET: u32 = CCR1_OFFSET + CHANNEL_STRIDE * Self::INDEX as u32;
+    /// The offset of the channel's CNDTR from the DMA base address.
+    const CNDTR_OFFSET: u32 = CNDTR1_OFFSET + CHANNEL_STRIDE * Self::INDEX as u32;
+    /// The offset of the channel's CPAR from the DMA base address.
+    const CPAR_OFFSET: u32 = CPAR1_OFFSET + CHANNEL_STRIDE * Self::INDEX as u32;
+    /// The offset of the channel's CMAR from the DMA base address.
+    const CMAR_OFFSET: u32 = CMAR1_OFFSET + CHANNEL_STRIDE * Self::INDEX as u32;
+}
+
+/// DMA Channel 1
+#[derive(Copy, Clone, Debug)]
+pub struct Channel1;
+/// DMA Channel 2
+#[derive(Copy, Clone, Debug)]
+pub struct Channel2;
+/// DMA Channel 3
+#[derive(Copy, Clone, Debug)]
+pub struct Channel3;
+/// DMA Channel 4
+#[derive(Copy, Clone, Debug)]
+pub struct Channel4;
+/// DMA Channel 5
+#[derive(Copy, Clone, Debug)]
+pub struct Channel5;
+
+impl DmaChannel for Channel1 {
+    const CHANNEL: DMAChannel = DMAChannel::One;
+    const INDEX: usize = 0;
+}
+
+impl DmaChannel for Channel2 {
+    const CHANNEL: DMAChannel = DMAChannel::Two;
+    const INDEX: usize = 1;
+}
+
+impl DmaChannel for Channel3 {
+    const CHANNEL: DMAChannel = DMAChannel::Three;
+    const INDEX: usize = 2;
+}
+
+impl DmaChannel for Channel4 {
+    const CHANNEL: DMAChannel = DMAChannel::Four;
+    const INDEX: usize = 3;
+}
+
+impl DmaChannel for Channel5 {
+    const CHANNEL: DMAChannel = DMAChannel::Five;
+    const INDEX: usize = 4;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::RawDMA;
+    use core::mem;
+
+    fn offset_of<T>(base: &RawDMA, field: &T) -> u32 {
+        (field as *const T as usize - base as *const RawDMA as usize) as u32
+    }
+
+    fn check_channel<C: DmaChannel>() {
+        let dma: RawDMA = unsafe { mem::zeroed() };
+        let regs = &dma.channel[C::INDEX];
+
+        assert_eq!(C::CHANNEL as usize, C::INDEX);
+        assert_eq!(offset_of(&dma, &regs.ccr), C::CCR_OFFSET);
+        assert_eq!(offset_of(&dma, &regs.cndtr), C::CNDTR_OFFSET);
+        assert_eq!(offset_of(&dma, &regs.cpar), C::CPAR_OFFSET);
+        assert_eq!(offset_of(&dma, &regs.cmar), C::CMAR_OFFSET);
+    }
+
+    #[test]
+    fn test_channel_offsets_match_datasheet() {
+        assert_eq!(Channel1::CCR_OFFSET, 0x08);
+        assert_eq!(Channel1::CPAR_OFFSET, 0x10);
+        assert_eq!(Channel3::CCR_OFFSET, 0x30);
+        assert_eq!(Channel3::CPAR_OFFSET, 0x38);
+        assert_eq!(Channel5::CCR_OFFSET, 0x58);
+        assert_eq!(Channel5::CPAR_OFFSET, 0x60);
+    }
+
+    #[test]
+    fn test_channel_offsets_match_register_layout() {
+        check_channel::<Channel1>();
+        check_channel::<Channel2>();
+        check_channel::<Channel3>();
+        check_channel::<Channel4>();
+        check_channel::<Channel5>();
+    }
+}
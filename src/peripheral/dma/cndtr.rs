@@ -39,6 +39,11 @@ impl CNDTR {
     pub fn set_ndt(&mut self, num_data: u16) {
         self.0 = num_data as u32;
     }
+
+    /// Get the number of data left to be transferred.
+    pub fn get_ndt(&self) -> u16 {
+        self.0 as u16
+    }
 }
 
 #[cfg(test)]
@@ -57,5 +62,12 @@ mod tests {
         assert_eq!(cndtr.0, 5);
     }
 
+    #[test]
+    fn test_cndtr_get_ndt_ignores_reserved_bits() {
+        let cndtr = CNDTR(0xFFFF_0000 | 1234);
+
+        assert_eq!(cndtr.get_ndt(), 1234);
+    }
+
     // TODO: Tests for out of range values?
 }
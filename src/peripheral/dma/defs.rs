@@ -15,6 +15,8 @@
 * along with this program. If not, see <http://www.gnu.org/licenses/>.
 */
 
+use peripheral::addresses;
+
 // The STM32F04 only has one DMA peripheral from what I've read. The data sheet contains
 // lots of information about the STM32F09 which seems to have 2 DMA peripherals. Pretty
 // sure the base address listed here is the same regardless of which STM32F0xx device.
@@ -22,7 +24,7 @@
 // The DMA peripheral for the STM32F04 has 5 channels.
 
 // Base addresses for DMA 1 and 2
-pub const DMA_ADDR: *const u32 = 0x4002_0000 as *const _;
+pub const DMA_ADDR: *const u32 = addresses::DMA_ADDR;
 
 // ------------------------------------
 // DMAx - ISR Bit definitions
@@ -30,6 +32,13 @@ pub const DMA_ADDR: *const u32 = 0x4002_0000 as *const _;
 pub const ISR_OFFSET: u32 = 0x00;
 // These bits are set by hardware, and cleared in the IFCR Register by
 // writing a 1 to the correct bits.
+//
+// Only the channel 1 bits are listed, the bits for channel x are found by
+// shifting these left by 4 * (x - 1).
+pub const DMA_GIF_1:  u32 = 0b1;
+pub const DMA_TCIF_1: u32 = 0b1 << 1;
+pub const DMA_HTIF_1: u32 = 0b1 << 2;
+pub const DMA_TEIF_1: u32 = 0b1 << 3;
 
 // ------------------------------------
 // DMAx - IFCR Bit definitions
@@ -112,4 +121,13 @@ pub const CCR_MEM2MEM: u32 = 0b1  << 14;
 // CMAR_MA stores the base address of the memory area from/to which the
 // data will be read/written. MSIZE is related to this, read data sheet.
 
-
+// ------------------------------------
+// DMAx - Channel register offsets
+// ------------------------------------
+// Only the channel 1 offsets are listed, the registers for channel x are found by
+// adding CHANNEL_STRIDE * (x - 1).
+pub const CCR1_OFFSET: u32 = 0x08;
+pub const CNDTR1_OFFSET: u32 = 0x0C;
+pub const CPAR1_OFFSET: u32 = 0x10;
+pub const CMAR1_OFFSET: u32 = 0x14;
+pub const CHANNEL_STRIDE: u32 = 0x14;
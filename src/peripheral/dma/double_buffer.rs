@@ -0,0 +1,258 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! This module provides a ping-pong buffer built on top of a circular DMA transfer.
+//!
+//! The DMA channel runs in circular mode over the whole buffer. When the first half of the buffer
+//! has been transferred the HT flag is set, and when the second half has been transferred the TC
+//! flag is set and the transfer wraps back around to the start. Whichever half was just finished
+//! can be safely processed while the DMA works on the other half.
+
+use core::mem;
+use super::{DMA, DMAChannel, DataDirection, PeriphAndMemSize, ChannelPriorityLevel, Transfer};
+
+/// Defines the two halves of a `DoubleBuffer`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Half {
+    /// The lower half of the buffer, completed when the HT flag is set.
+    First,
+    /// The upper half of the buffer, completed when the TC flag is set.
+    Second,
+}
+
+/// Decides which half of the buffer is safe to touch based on the HT and TC flags.
+///
+/// The DMA always finishes the first half before the second, so if both flags are set at once
+/// (the flags weren't serviced in time) the half that completed most recently is the one after
+/// the last half that was handed out. The DMA has already moved on to the other half.
+#[derive(Copy, Clone, Debug)]
+struct HalfSelector {
+    last: Option<Half>,
+}
+
+impl HalfSelector {
+    fn new() -> Self {
+        HalfSelector {
+            last: None,
+        }
+    }
+
+    fn select(&mut self, half_transfer: bool, transfer_complete: bool) -> Option<Half> {
+        let half = match (half_transfer, transfer_complete) {
+            (false, false) => return None,
+            (true, false) => Half::First,
+            (false, true) => Half::Second,
+            (true, true) => match self.last {
+                Some(Half::First) => Half::First,
+                Some(Half::Second) | None => Half::Second,
+            },
+        };
+        self.last = Some(half);
+        Some(half)
+    }
+}
+
+/// A ping-pong buffer that is continuously filled (or drained) by a DMA channel in circular mode.
+///
+/// The channel's HT and TC interrupts are enabled, `ready_half` should be called either from the
+/// channel's interrupt handler or polled regularly. Don't use the channel that is driving the
+/// serial output (channel 4) since its interrupt handler clears the TC flag.
+///
+/// Example Usage:
+/// ```
+///   static mut SAMPLES: [u16; 64] = [0; 64];
+///   let mut buffer = DoubleBuffer::new(DMAChannel::One, ADC_DR_ADDR,
+///       unsafe { &mut SAMPLES }, DataDirection::FromPeriph);
+///   buffer.start();
+///   loop {
+///       if let Some(half) = buffer.ready_half() {
+///           process(half);
+///       }
+///   }
+/// ```
+pub struct DoubleBuffer<T: 'static> {
+    chan: DMAChannel,
+    peripheral_addr: *const u32,
+    buffer: &'static mut [T],
+    direction: DataDirection,
+    selector: HalfSelector,
+}
+
+impl<T: 'static> DoubleBuffer<T> {
+    /// Create a new double buffer on the specified channel.
+    ///
+    /// # Panics
+    ///
+    /// The buffer length must be even and no greater than 65535, and `T` must be 1, 2 or 4 bytes
+    /// wide, otherwise the kernel will panic.
+    pub fn new(chan: DMAChannel, peripheral_addr: *const u32, buffer: &'static mut [T],
+               direction: DataDirection) -> Self {
+        if buffer.len() % 2 != 0 || buffer.len() > 65535 {
+            panic!("DoubleBuffer::new - buffer length must be even and no greater than 65535!");
+        }
+        // Make sure T is a valid transfer size up front
+        transfer_size::<T>();
+
+        DoubleBuffer {
+            chan: chan,
+            peripheral_addr: peripheral_addr,
+            buffer: buffer,
+            direction: direction,
+            selector: HalfSelector::new(),
+        }
+    }
+
+    /// Configure the channel for a circular transfer over the whole buffer and start it.
+    pub fn start(&mut self) -> Transfer {
+        let mut dma = DMA::new();
+        let chan = self.chan;
+        let size = transfer_size::<T>();
+
+        dma[chan].disable_dma();
+        dma.channel_global_interrupt_clear(chan);
+        dma[chan].set_peripheral_address(self.peripheral_addr);
+        dma[chan].set_memory_address(self.buffer.as_ptr() as *const u32);
+
+        dma[chan].set_channel_priority(ChannelPriorityLevel::High);
+        dma[chan].set_memory_size(size);
+        dma[chan].set_peripheral_size(size);
+        dma[chan].set_data_transfer_direction(self.direction);
+        dma[chan].enable_memory_increment_mode();
+        dma[chan].disable_peripheral_increment_mode();
+        dma[chan].set_number_of_data(self.buffer.len() as u16);
+        dma[chan].disable_mem2mem_mode();
+        dma[chan].enable_circular_mode();
+        dma[chan].enable_half_transfer_interrupt();
+        dma[chan].enable_transmit_complete_interrupt();
+
+        self.selector = HalfSelector::new();
+        dma[chan].enable_dma();
+        Transfer::new(&dma[chan])
+    }
+
+    /// Stop the transfer.
+    pub fn stop(&mut self) {
+        let mut dma = DMA::new();
+        let chan = self.chan;
+
+        dma[chan].disable_dma();
+        dma[chan].disable_half_transfer_interrupt();
+        dma[chan].disable_transmit_complete_interrupt();
+        dma.channel_global_interrupt_clear(chan);
+    }
+
+    /// Return the half of the buffer that the DMA just finished with, if any.
+    ///
+    /// This clears the HT and TC flags for the channel. The returned half is safe to touch until
+    /// the DMA finishes the other half.
+    pub fn ready_half(&mut self) -> Option<&mut [T]> {
+        let mut dma = DMA::new();
+        let chan = self.chan;
+
+        let half_transfer = dma.channel_half_transfer_flag(chan);
+        let transfer_complete = dma.channel_transfer_complete_flag(chan);
+        if half_transfer {
+            dma.channel_half_transfer_clear(chan);
+        }
+        if transfer_complete {
+            dma.channel_transfer_complete_clear(chan);
+        }
+
+        let mid = self.buffer.len() / 2;
+        match self.selector.select(half_transfer, transfer_complete) {
+            Some(Half::First) => Some(&mut self.buffer[..mid]),
+            Some(Half::Second) => Some(&mut self.buffer[mid..]),
+            None => None,
+        }
+    }
+}
+
+fn transfer_size<T>() -> PeriphAndMemSize {
+    match mem::size_of::<T>() {
+        1 => PeriphAndMemSize::Eight,
+        2 => PeriphAndMemSize::Sixteen,
+        4 => PeriphAndMemSize::ThirtyTwo,
+        _ => panic!("DoubleBuffer - element size must be 1, 2 or 4 bytes!"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_half_selector_no_flags_returns_none() {
+        let mut selector = HalfSelector::new();
+        assert_eq!(selector.select(false, false), None);
+    }
+
+    #[test]
+    fn test_half_selector_half_transfer_returns_first() {
+        let mut selector = HalfSelector::new();
+        assert_eq!(selector.select(true, false), Some(Half::First));
+    }
+
+    #[test]
+    fn test_half_selector_transfer_complete_returns_second() {
+        let mut selector = HalfSelector::new();
+        assert_eq!(selector.select(false, true), Some(Half::Second));
+    }
+
+    #[test]
+    fn test_half_selector_alternates_halves() {
+        let mut selector = HalfSelector::new();
+        assert_eq!(selector.select(true, false), Some(Half::First));
+        assert_eq!(selector.select(false, true), Some(Half::Second));
+        assert_eq!(selector.select(true, false), Some(Half::First));
+        assert_eq!(selector.select(false, true), Some(Half::Second));
+    }
+
+    #[test]
+    fn test_half_selector_both_flags_at_start_returns_second() {
+        // HT then TC both fired before we got to them, DMA is now filling the first half
+        let mut selector = HalfSelector::new();
+        assert_eq!(selector.select(true, true), Some(Half::Second));
+    }
+
+    #[test]
+    fn test_half_selector_both_flags_after_first_returns_first() {
+        // TC then HT both fired after we handed out the first half, DMA is now filling the second
+        let mut selector = HalfSelector::new();
+        selector.select(true, false);
+        assert_eq!(selector.select(true, true), Some(Half::First));
+    }
+
+    #[test]
+    fn test_half_selector_both_flags_after_second_returns_second() {
+        let mut selector = HalfSelector::new();
+        selector.select(false, true);
+        assert_eq!(selector.select(true, true), Some(Half::Second));
+    }
+
+    #[test]
+    fn test_transfer_size_matches_element_width() {
+        match transfer_size::<u8>() { PeriphAndMemSize::Eight => {}, _ => panic!() }
+        match transfer_size::<u16>() { PeriphAndMemSize::Sixteen => {}, _ => panic!() }
+        match transfer_size::<u32>() { PeriphAndMemSize::ThirtyTwo => {}, _ => panic!() }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_transfer_size_invalid_element_width_panics() {
+        transfer_size::<u64>();
+    }
+}
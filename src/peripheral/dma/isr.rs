@@ -0,0 +1,127 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use super::DMAChannel;
+use super::defs::*;
+
+#[derive(Copy, Clone, Debug)]
+pub struct ISR(u32);
+
+impl ISR {
+    /* Bits 24, 20, 16, 12, 8, 4, 0
+        GIFx: Channel x global interrupt flag (x = 1..7 for DMA and x = 1..5 for DMA2)
+        This bit is set by hardware. It is cleared by software writing 1 to the corresponding bit
+        in the DMA_IFCR register.
+        0: No TE, HT or TC event on channel x
+        1: A TE, HT or TC event occurred on channel x
+    */
+    pub fn channel_global_interrupt_flag(&self, chan: DMAChannel) -> bool {
+        self.0 & (DMA_GIF_1 << (4 * (chan as u32))) != 0
+    }
+
+    /* Bits 25, 21, 17, 13, 9, 5, 1
+        TCIFx: Channel x transfer complete flag (x = 1..7 for DMA and x = 1..5 for DMA2)
+        This bit is set by hardware. It is cleared by software writing 1 to the corresponding bit
+        in the DMA_IFCR register.
+        0: No transfer complete (TC) event on channel x
+        1: A transfer complete (TC) event occurred on channel x
+    */
+    pub fn channel_transfer_complete_flag(&self, chan: DMAChannel) -> bool {
+        self.0 & (DMA_TCIF_1 << (4 * (chan as u32))) != 0
+    }
+
+    /* Bits 26, 22, 18, 14, 10, 6, 2
+        HTIFx: Channel x half transfer flag (x = 1..7 for DMA and x = 1..5 for DMA2)
+        This bit is set by hardware. It is cleared by software writing 1 to the corresponding bit
+        in the DMA_IFCR register.
+        0: No half transfer (HT) event on channel x
+        1: A half transfer (HT) event occurred on channel x
+    */
+    pub fn channel_half_transfer_flag(&self, chan: DMAChannel) -> bool {
+        self.0 & (DMA_HTIF_1 << (4 * (chan as u32))) != 0
+    }
+
+    /* Bits 27, 23, 19, 15, 11, 7, 3
+        TEIFx: Channel x transfer error flag (x = 1..7 for DMA and x = 1..5 for DMA2)
+        This bit is set by hardware. It is cleared by software writing 1 to the corresponding bit
+        in the DMA_IFCR register.
+        0: No transfer error (TE) on channel x
+        1: A transfer error (TE) occurred on channel x
+    */
+    pub fn channel_transfer_error_flag(&self, chan: DMAChannel) -> bool {
+        self.0 & (DMA_TEIF_1 << (4 * (chan as u32))) != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn channel_global_interrupt_flag_reads_correct_bit_for_each_chan() {
+        let chans = [DMAChannel::One, DMAChannel::Two, DMAChannel::Three, DMAChannel::Four,
+                     DMAChannel::Five];
+        for (i, &chan) in chans.iter().enumerate() {
+            let isr = ISR(0b1 << (4 * i));
+            for &other in &chans {
+                assert_eq!(isr.channel_global_interrupt_flag(other), other == chan);
+            }
+        }
+    }
+
+    #[test]
+    fn channel_global_interrupt_flag_ignores_specific_flags() {
+        // TC, HT and TE for channel 1 without its GIF
+        let isr = ISR(0b1110);
+        assert!(!isr.channel_global_interrupt_flag(DMAChannel::One));
+    }
+
+    #[test]
+    fn channel_transfer_complete_flag_reads_correct_bit_for_chan_one() {
+        let isr = ISR(0b1 << 1);
+        assert!(isr.channel_transfer_complete_flag(DMAChannel::One));
+        assert!(!isr.channel_transfer_complete_flag(DMAChannel::Two));
+    }
+
+    #[test]
+    fn channel_transfer_complete_flag_reads_correct_bit_for_chan_five() {
+        let isr = ISR(0b1 << 17);
+        assert!(isr.channel_transfer_complete_flag(DMAChannel::Five));
+        assert!(!isr.channel_transfer_complete_flag(DMAChannel::Four));
+    }
+
+    #[test]
+    fn channel_half_transfer_flag_reads_correct_bit_for_chan_two() {
+        let isr = ISR(0b1 << 6);
+        assert!(isr.channel_half_transfer_flag(DMAChannel::Two));
+        assert!(!isr.channel_transfer_complete_flag(DMAChannel::Two));
+    }
+
+    #[test]
+    fn channel_half_transfer_flag_reads_correct_bit_for_chan_four() {
+        let isr = ISR(0b1 << 14);
+        assert!(isr.channel_half_transfer_flag(DMAChannel::Four));
+        assert!(!isr.channel_half_transfer_flag(DMAChannel::Three));
+    }
+
+    #[test]
+    fn channel_transfer_error_flag_reads_correct_bit_for_chan_three() {
+        let isr = ISR(0b1 << 11);
+        assert!(isr.channel_transfer_error_flag(DMAChannel::Three));
+        assert!(!isr.channel_half_transfer_flag(DMAChannel::Three));
+    }
+}
@@ -0,0 +1,191 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Memory to memory copies on a DMA channel.
+//!
+//! CNDTR is only 16 bits, so a copy longer than 65535 items is issued as several transfers back to
+//! back, advancing CPAR and CMAR past what has already been copied. When both buffers and the
+//! length allow it the copy is done in half words or words, which cuts the number of items (and
+//! so the number of transfers) by 2 or 4.
+
+use super::{RawDMA, DMAChannel, DataDirection, PeriphAndMemSize};
+
+const MAX_ITEMS: usize = 0xFFFF;
+
+/// Pick the widest item size that both addresses and the length are aligned to.
+fn copy_size(src: usize, dst: usize, len: usize) -> (PeriphAndMemSize, usize) {
+    let align = src | dst | len;
+    if align & 0b11 == 0 {
+        (PeriphAndMemSize::ThirtyTwo, 4)
+    }
+    else if align & 0b1 == 0 {
+        (PeriphAndMemSize::Sixteen, 2)
+    }
+    else {
+        (PeriphAndMemSize::Eight, 1)
+    }
+}
+
+/// Splits a copy of `len` bytes into transfers of at most 65535 items of `item_bytes` each.
+///
+/// Each step yields the byte offset the transfer starts at and the number of items in it.
+#[derive(Copy, Clone, Debug)]
+struct CopyChunks {
+    offset: usize,
+    len: usize,
+    item_bytes: usize,
+}
+
+impl CopyChunks {
+    fn new(len: usize, item_bytes: usize) -> Self {
+        CopyChunks {
+            offset: 0,
+            len: len,
+            item_bytes: item_bytes,
+        }
+    }
+}
+
+impl Iterator for CopyChunks {
+    type Item = (usize, u16);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let remaining = (self.len - self.offset) / self.item_bytes;
+        if remaining == 0 {
+            return None;
+        }
+        let items = if remaining > MAX_ITEMS { MAX_ITEMS } else { remaining };
+        let offset = self.offset;
+        self.offset += items * self.item_bytes;
+        Some((offset, items as u16))
+    }
+}
+
+impl RawDMA {
+    /// Copy `src` into `dst` using the DMA, blocking until the copy is done. Returns false if the
+    /// channel raised a transfer error, in which case `dst` may be partially written.
+    ///
+    /// The copy is split into as many transfers as it takes to fit the 16 bit CNDTR, and uses
+    /// word or half word items when both buffers are aligned for them. The channel is left
+    /// disabled with its flags cleared.
+    ///
+    /// # Panics
+    ///
+    /// `src` and `dst` must be the same length or the kernel will panic.
+    pub fn mem_copy(&mut self, chan: DMAChannel, src: &[u8], dst: &mut [u8]) -> bool {
+        if src.len() != dst.len() {
+            panic!("DMA::mem_copy - source and destination must be the same length!");
+        }
+        let src_addr = src.as_ptr() as usize;
+        let dst_addr = dst.as_mut_ptr() as usize;
+        let (size, item_bytes) = copy_size(src_addr, dst_addr, src.len());
+
+        {
+            let regs = &mut self[chan];
+            regs.disable_dma();
+            regs.set_peripheral_size(size);
+            regs.set_memory_size(size);
+            regs.set_data_transfer_direction(DataDirection::FromPeriph);
+            regs.enable_peripheral_increment_mode();
+            regs.enable_memory_increment_mode();
+            regs.disable_circular_mode();
+            regs.enable_mem2mem_mode();
+        }
+
+        for (offset, items) in CopyChunks::new(src.len(), item_bytes) {
+            self.channel_global_interrupt_clear(chan);
+            {
+                let regs = &mut self[chan];
+                regs.set_peripheral_address((src_addr + offset) as *const u32);
+                regs.set_memory_address((dst_addr + offset) as *const u32);
+                regs.set_number_of_data(items);
+                regs.enable_dma();
+            }
+            while !self.channel_transfer_complete_flag(chan) {
+                if self.channel_transfer_error_flag(chan) {
+                    self.abort(chan);
+                    self[chan].disable_mem2mem_mode();
+                    return false;
+                }
+            }
+            self[chan].disable_dma();
+        }
+
+        self.channel_global_interrupt_clear(chan);
+        self[chan].disable_mem2mem_mode();
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    fn chunks(len: usize, item_bytes: usize) -> Vec<(usize, u16)> {
+        CopyChunks::new(len, item_bytes).collect()
+    }
+
+    #[test]
+    fn test_copy_size_picks_widest_alignment() {
+        assert_eq!(copy_size(0x100, 0x200, 64).1, 4);
+        assert_eq!(copy_size(0x102, 0x200, 64).1, 2);
+        assert_eq!(copy_size(0x100, 0x200, 62).1, 2);
+        assert_eq!(copy_size(0x100, 0x201, 64).1, 1);
+        assert_eq!(copy_size(0x100, 0x200, 63).1, 1);
+    }
+
+    #[test]
+    fn test_copy_chunks_empty_copy_has_no_transfers() {
+        assert_eq!(chunks(0, 1).len(), 0);
+    }
+
+    #[test]
+    fn test_copy_chunks_at_boundary_is_one_transfer() {
+        assert_eq!(chunks(65535, 1), [(0, 65535)]);
+    }
+
+    #[test]
+    fn test_copy_chunks_past_boundary_splits() {
+        assert_eq!(chunks(65536, 1), [(0, 65535), (65535, 1)]);
+        assert_eq!(chunks(3 * 65535 + 10, 1),
+                   [(0, 65535), (65535, 65535), (131070, 65535), (196605, 10)]);
+    }
+
+    #[test]
+    fn test_copy_chunks_words_reduce_transfers() {
+        // 128KiB needs 3 byte transfers but fits in a single word transfer
+        assert_eq!(chunks(0x2_0000, 1).len(), 3);
+        assert_eq!(chunks(0x2_0000, 4), [(0, 0x8000)]);
+    }
+
+    #[test]
+    fn test_copy_chunks_words_split_at_boundary() {
+        assert_eq!(chunks(4 * 65535, 4), [(0, 65535)]);
+        assert_eq!(chunks(4 * 65536, 4), [(0, 65535), (4 * 65535, 1)]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_mem_copy_length_mismatch_panics() {
+        let mut dma: RawDMA = unsafe { ::core::mem::zeroed() };
+        let src = [0u8; 4];
+        let mut dst = [0u8; 3];
+
+        dma.mem_copy(DMAChannel::One, &src, &mut dst);
+    }
+}
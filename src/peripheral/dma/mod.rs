@@ -35,9 +35,18 @@ mod cpar;
 mod cmar;
 mod defs;
 mod ifcr;
-
-use interrupt;
+mod isr;
+mod double_buffer;
+mod scatter;
+mod channel;
+mod word;
+mod callback;
+mod mem_copy;
+mod remap;
+
+use interrupt::{self, Interrupt};
 use peripheral::{rcc};
+use peripheral::usart::{RawUsart, DMAMode};
 use core::ops::{Deref, DerefMut, Index, IndexMut};
 use volatile::Volatile;
 use self::ccr::CCR;
@@ -45,8 +54,15 @@ use self::cndtr::CNDTR;
 use self::cpar::CPAR;
 use self::cmar::CMAR;
 use self::ifcr::IFCR;
+use self::isr::ISR;
 use self::defs::*;
-use self::ccr::{DataDirection, PeriphAndMemSize, ChannelPriorityLevel};
+
+pub use self::ccr::{DataDirection, PeriphAndMemSize, ChannelPriorityLevel};
+pub use self::double_buffer::{DoubleBuffer, Half};
+pub use self::scatter::{ScatterTransfer, Descriptor};
+pub use self::word::DmaWord;
+pub use self::channel::{DmaChannel, Channel1, Channel2, Channel3, Channel4, Channel5};
+pub use self::remap::{remap_request, DmaRequest, DmaError};
 
 /// Defines the wake/sleep channel for the USART TX on Channel 4.
 pub const DMA_TX_CHAN4PLUS: usize = 26;
@@ -81,7 +97,7 @@ impl IndexMut<DMAChannel> for RawDMA {
 /// Defines the availabe DMA Channels for STM32F04.
 ///
 /// Used as C-like enum in order to index into array of DMAChannelRegs.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum DMAChannel {
     /// DMA Channel 1 (Index 0)
     One,
@@ -95,6 +111,18 @@ pub enum DMAChannel {
     Five,
 }
 
+impl DMAChannel {
+    /// Get the interrupt raised by the channel. Channels 2 and 3 share an interrupt, as do
+    /// channels 4 and up.
+    pub fn interrupt(self) -> Interrupt {
+        match self {
+            DMAChannel::One => Interrupt::Dmach1,
+            DMAChannel::Two | DMAChannel::Three => Interrupt::Dmach23,
+            DMAChannel::Four | DMAChannel::Five => Interrupt::Dmach4Plus,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 #[doc(hidden)]
 pub struct DMAChannelRegs {
@@ -116,6 +144,12 @@ impl DMAChannelRegs {
         self.ccr.enable_dma(false);
     }
 
+    /// Check if the channel is enabled. CNDTR, CPAR and CMAR can only be written while the
+    /// channel is disabled.
+    pub fn is_enabled(&self) -> bool {
+        self.ccr.is_dma_enabled()
+    }
+
     /// Enable TC interrupt. This interrupt occurs at the end of the transfer.
     pub fn enable_transmit_complete_interrupt(&mut self) {
         self.ccr.enable_transmit_complete_interrupt(true);
@@ -166,6 +200,11 @@ impl DMAChannelRegs {
         self.ccr.set_data_transfer_direction(data_dir);
     }
 
+    /// Get the data transfer direction.
+    pub fn get_data_transfer_direction(&self) -> DataDirection {
+        self.ccr.get_data_transfer_direction()
+    }
+
     /// Enable circular mode.
     ///
     /// When enabled, the number of data to be transferred is automaticaly reloaded
@@ -266,16 +305,29 @@ impl DMAChannelRegs {
     }
 
     /// Set the number of data to be transferred. Up to 65535.
+    ///
+    /// The channel must be disabled, this is checked in debug builds.
     pub fn set_number_of_data(&mut self, num_data: u16) {
+        debug_assert!(!self.is_enabled(),
+                      "DMAChannelRegs::set_number_of_data - channel must be disabled!");
         self.cndtr.set_ndt(num_data);
     }
 
+    /// Get the number of data left to be transferred.
+    pub fn get_number_of_data(&self) -> u16 {
+        self.cndtr.get_ndt()
+    }
+
     /// Set the peripheral address.
     ///
     /// This is the base address of the peripheral that is using the DMA.
     /// The data will be moved from/to this address to/from the memory after
     /// the peripheral event.
+    ///
+    /// The channel must be disabled, this is checked in debug builds.
     pub fn set_peripheral_address(&mut self, periph_addr: *const u32) {
+        debug_assert!(!self.is_enabled(),
+                      "DMAChannelRegs::set_peripheral_address - channel must be disabled!");
         self.cpar.set_pa(periph_addr);
     }
 
@@ -283,7 +335,11 @@ impl DMAChannelRegs {
     ///
     /// This is the memory address where the data will be written to or read from
     /// after the peripheral event.
+    ///
+    /// The channel must be disabled, this is checked in debug builds.
     pub fn set_memory_address(&mut self, mem_addr: *const u32) {
+        debug_assert!(!self.is_enabled(),
+                      "DMAChannelRegs::set_memory_address - channel must be disabled!");
         self.cmar.set_ma(mem_addr);
     }
 }
@@ -292,7 +348,7 @@ impl DMAChannelRegs {
 #[repr(C)]
 #[doc(hidden)]
 pub struct RawDMA {
-    isr: u32,
+    isr: ISR,
     ifcr: IFCR,
     channel: [DMAChannelRegs; 5]
 }
@@ -354,6 +410,174 @@ impl RawDMA {
         self.ifcr.channel_transfer_error_clear(chan);
     }
 
+    /// Check if the GIF flag is set. The GIF flag is set when any of the TC, HT or TE flags are
+    /// set for the channel, so a shared interrupt handler can skip the channels with nothing
+    /// pending with a single check.
+    pub fn any_interrupt(&self, chan: DMAChannel) -> bool {
+        self.isr.channel_global_interrupt_flag(chan)
+    }
+
+    /// Check if the TC flag is set. The TC flag is set when the transfer of data has completed.
+    pub fn channel_transfer_complete_flag(&self, chan: DMAChannel) -> bool {
+        self.isr.channel_transfer_complete_flag(chan)
+    }
+
+    /// Check if the HTC flag is set. The HTC flag is set when half the data to be
+    /// transfered has completed.
+    pub fn channel_half_transfer_flag(&self, chan: DMAChannel) -> bool {
+        self.isr.channel_half_transfer_flag(chan)
+    }
+
+    /// Check if the TE flag is set. The TE flag is set when an error is generated through a
+    /// read or write access.
+    pub fn channel_transfer_error_flag(&self, chan: DMAChannel) -> bool {
+        self.isr.channel_transfer_error_flag(chan)
+    }
+
+    /// Check if the channel is enabled, see `DMAChannelRegs::is_enabled`.
+    pub fn is_channel_enabled(&self, chan: DMAChannel) -> bool {
+        self[chan].is_enabled()
+    }
+
+    /// Abort a transfer on the channel, whether or not it has finished.
+    ///
+    /// The channel is disabled first so it stops servicing requests (a single transfer already in
+    /// progress still completes), then its TC, HT and TE flags are cleared so an event raised by
+    /// that last transfer doesn't fire an interrupt after the abort.
+    pub fn abort(&mut self, chan: DMAChannel) {
+        self[chan].disable_dma();
+        self.ifcr.channel_global_interrupt_clear(chan);
+    }
+
+    /// Abort a transfer on a channel serving `usart`.
+    ///
+    /// After the channel is aborted as in `abort`, the Usart's DMAR or DMAT bit for the
+    /// direction of the transfer is cleared. Otherwise the Usart keeps its request pending, and
+    /// the channel would service it as soon as it's enabled again. The bit for the other
+    /// direction is left alone, so a transfer the other way on another channel is unaffected.
+    pub fn abort_usart(&mut self, chan: DMAChannel, usart: &mut RawUsart) {
+        self.abort(chan);
+        match self[chan].get_data_transfer_direction() {
+            DataDirection::FromPeriph => usart.disable_dma_mode(DMAMode::Receive),
+            DataDirection::FromMem => usart.disable_dma_mode(DMAMode::Transmit),
+        }
+    }
+
+    /// Get the registers for a channel selected at compile time.
+    pub fn channel_regs<C: DmaChannel>(&mut self) -> &mut DMAChannelRegs {
+        &mut self.channel[C::INDEX]
+    }
+
+    /// Configure a channel selected at compile time for a transfer between a peripheral and
+    /// memory. The channel is disabled while it's configured and left disabled, call
+    /// `enable_dma` on the channel's registers to start the transfer.
+    pub fn configure<C: DmaChannel>(&mut self, peripheral_addr: *const u32,
+                                    memory_addr: *const u32, num_data: u16,
+                                    direction: DataDirection) -> Transfer {
+        let regs = self.channel_regs::<C>();
+
+        regs.disable_dma();
+        regs.set_peripheral_address(peripheral_addr);
+        regs.set_memory_address(memory_addr);
+        regs.set_number_of_data(num_data);
+        regs.set_data_transfer_direction(direction);
+        regs.enable_memory_increment_mode();
+        regs.disable_peripheral_increment_mode();
+        Transfer::new(regs)
+    }
+
+    /// Start transferring the elements of `src` to the peripheral register at `dst`.
+    ///
+    /// The peripheral and memory data sizes are both set from the element type. The DMA reads
+    /// from `src` after this returns, so it must stay alive and unchanged until the transfer
+    /// completes.
+    ///
+    /// # Panics
+    ///
+    /// The DMA can't transfer more than 65535 elements at once, a longer `src` will cause the
+    /// kernel to panic.
+    pub fn start_transfer<T: DmaWord>(&mut self, chan: DMAChannel, src: &[T],
+                                      dst: *mut T) -> Transfer {
+        self.start(chan, dst as *const u32, src.as_ptr() as *const u32, src.len(),
+                   T::SIZE, DataDirection::FromMem);
+    }
+
+    /// Start receiving elements from the peripheral register at `src` into `dst`.
+    ///
+    /// The peripheral and memory data sizes are both set from the element type. The DMA writes to
+    /// `dst` after this returns, so it must stay alive and untouched until the transfer completes.
+    ///
+    /// # Panics
+    ///
+    /// The DMA can't transfer more than 65535 elements at once, a longer `dst` will cause the
+    /// kernel to panic.
+    pub fn start_receive<T: DmaWord>(&mut self, chan: DMAChannel, src: *const T,
+                                     dst: &mut [T]) -> Transfer {
+        self.start(chan, src as *const u32, dst.as_ptr() as *const u32, dst.len(),
+                   T::SIZE, DataDirection::FromPeriph);
+    }
+
+    fn start(&mut self, chan: DMAChannel, peripheral_addr: *const u32, memory_addr: *const u32,
+             len: usize, size: PeriphAndMemSize, direction: DataDirection) -> Transfer {
+        if len > 0xFFFF {
+            panic!("DMA::start_transfer - cannot transfer more than 65535 elements at once!");
+        }
+        let regs = &mut self[chan];
+
+        regs.disable_dma();
+        regs.set_peripheral_address(peripheral_addr);
+        regs.set_memory_address(memory_addr);
+        regs.set_number_of_data(len as u16);
+        regs.set_peripheral_size(size);
+        regs.set_memory_size(size);
+        regs.set_data_transfer_direction(direction);
+        regs.enable_memory_increment_mode();
+        regs.disable_peripheral_increment_mode();
+        regs.enable_dma();
+        Transfer::new(regs)
+    }
+}
+
+/// A transfer programmed on a channel, kept to check how far along it is while the DMA runs.
+#[derive(Copy, Clone, Debug)]
+pub struct Transfer {
+    regs: Volatile<DMAChannelRegs>,
+    total: u16,
+}
+
+impl Transfer {
+    // CNDTR counts down as the transfer runs, so take the total while it still holds it.
+    fn new(regs: &DMAChannelRegs) -> Self {
+        unsafe {
+            Transfer {
+                regs: Volatile::new(regs as *const _),
+                total: regs.get_number_of_data(),
+            }
+        }
+    }
+
+    /// Get the number of data the transfer was programmed with.
+    pub fn total(&self) -> u16 {
+        self.total
+    }
+
+    /// Get how far along the transfer is as a percentage.
+    ///
+    /// In circular mode the count is reloaded at the end of each cycle, so this is the progress
+    /// through the current cycle. A zero length transfer is reported as 100% complete.
+    pub fn progress(&self) -> u8 {
+        transfer_progress(self.total, self.regs.get_number_of_data())
+    }
+}
+
+// The remaining count should never be above the total, but if the channel was reprogrammed
+// after the transfer was started we report no progress rather than underflowing.
+fn transfer_progress(total: u16, remaining: u16) -> u8 {
+    if total == 0 {
+        return 100;
+    }
+    let done = total.saturating_sub(remaining) as u32;
+    (done * 100 / total as u32) as u8
 }
 
 /// Initialize the DMA peripheral
@@ -365,11 +589,12 @@ pub fn init() {
     rcc.enable_peripheral(rcc::Peripheral::DMA);
 
     let mut nvic = interrupt::nvic();
-    nvic.enable_interrupt(interrupt::Hardware::Dmach4Plus);
+    nvic.enable_interrupt(DMAChannel::Four.interrupt());
 }
 
 /// Configure the DMA for Usart TX.
-pub fn set_dma_usart_tx(chan: DMAChannel, peripheral_addr: *const u32, memory_addr: &[u8]) {
+pub fn set_dma_usart_tx(chan: DMAChannel, peripheral_addr: *const u32,
+                        memory_addr: &[u8]) -> Transfer {
     let mut dma = DMA::new();
 
     dma[chan].disable_dma();
@@ -387,4 +612,175 @@ pub fn set_dma_usart_tx(chan: DMAChannel, peripheral_addr: *const u32, memory_ad
     dma[chan].disable_mem2mem_mode();
     dma[chan].enable_transmit_complete_interrupt();
     dma[chan].enable_dma();
+    Transfer::new(&dma[chan])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use peripheral::raw_word;
+    use peripheral::usart::defs as usart_defs;
+
+    #[test]
+    fn test_dma_channel_interrupt() {
+        assert_eq!(DMAChannel::One.interrupt(), Interrupt::Dmach1);
+        assert_eq!(DMAChannel::Two.interrupt(), Interrupt::Dmach23);
+        assert_eq!(DMAChannel::Three.interrupt(), Interrupt::Dmach23);
+        assert_eq!(DMAChannel::Four.interrupt(), Interrupt::Dmach4Plus);
+        assert_eq!(DMAChannel::Five.interrupt(), Interrupt::Dmach4Plus);
+    }
+
+    fn usart_cr3(usart: &RawUsart) -> u32 {
+        let base = usart as *const RawUsart as *const u8;
+        unsafe { *(base.offset(usart_defs::CR3_OFFSET as isize) as *const u32) }
+    }
+
+    #[test]
+    fn test_transfer_progress() {
+        assert_eq!(transfer_progress(200, 200), 0);
+        assert_eq!(transfer_progress(200, 150), 25);
+        assert_eq!(transfer_progress(3, 1), 66);
+        assert_eq!(transfer_progress(200, 0), 100);
+    }
+
+    #[test]
+    fn test_transfer_progress_zero_length_is_complete() {
+        assert_eq!(transfer_progress(0, 0), 100);
+    }
+
+    #[test]
+    fn test_transfer_progress_remaining_above_total_is_zero() {
+        assert_eq!(transfer_progress(10, 20), 0);
+    }
+
+    #[test]
+    fn test_transfer_progress_large_count_does_not_overflow() {
+        assert_eq!(transfer_progress(0xFFFF, 1), 99);
+    }
+
+    #[test]
+    fn test_progress_uses_total_from_start() {
+        let mut dma: RawDMA = unsafe { ::core::mem::zeroed() };
+        let data = [0u8; 160];
+        let mut reg = 0u8;
+
+        let transfer = dma.start_transfer(DMAChannel::Five, &data, &mut reg as *mut u8);
+        assert_eq!(transfer.total(), 160);
+        assert_eq!(transfer.progress(), 0);
+
+        // The mock doesn't count down, so stand in for the DMA
+        dma[DMAChannel::Five].disable_dma();
+        dma[DMAChannel::Five].set_number_of_data(40);
+        assert_eq!(transfer.progress(), 75);
+    }
+
+    #[test]
+    fn test_progress_uses_total_from_configure() {
+        let mut dma: RawDMA = unsafe { ::core::mem::zeroed() };
+
+        let transfer = dma.configure::<Channel2>(0x4000_4428 as *const u32,
+                                                 0x2000_0000 as *const u32, 8,
+                                                 DataDirection::FromPeriph);
+        assert_eq!(transfer.total(), 8);
+
+        dma[DMAChannel::Two].set_number_of_data(2);
+        assert_eq!(transfer.progress(), 75);
+    }
+
+    #[test]
+    fn test_transfers_on_separate_controllers_keep_their_own_total() {
+        let mut first: RawDMA = unsafe { ::core::mem::zeroed() };
+        let mut second: RawDMA = unsafe { ::core::mem::zeroed() };
+        let data = [0u8; 10];
+        let mut reg = 0u8;
+
+        let short = first.start_transfer(DMAChannel::One, &data[..4], &mut reg as *mut u8);
+        let long = second.start_transfer(DMAChannel::One, &data, &mut reg as *mut u8);
+
+        first[DMAChannel::One].disable_dma();
+        first[DMAChannel::One].set_number_of_data(2);
+        assert_eq!(short.progress(), 50);
+        assert_eq!(long.progress(), 0);
+    }
+
+    #[test]
+    fn test_is_channel_enabled_reads_en_bit() {
+        let mut dma: RawDMA = unsafe { ::core::mem::zeroed() };
+        dma[DMAChannel::Three].enable_dma();
+
+        assert!(dma.is_channel_enabled(DMAChannel::Three));
+        assert!(!dma.is_channel_enabled(DMAChannel::Two));
+        assert!(!dma.is_channel_enabled(DMAChannel::Four));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_set_number_of_data_while_enabled_panics() {
+        let mut dma: RawDMA = unsafe { ::core::mem::zeroed() };
+        dma[DMAChannel::One].enable_dma();
+
+        dma[DMAChannel::One].set_number_of_data(4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_set_memory_address_while_enabled_panics() {
+        let mut dma: RawDMA = unsafe { ::core::mem::zeroed() };
+        dma[DMAChannel::One].enable_dma();
+
+        dma[DMAChannel::One].set_memory_address(0x2000_0000 as *const u32);
+    }
+
+    #[test]
+    fn test_configure_after_disable_does_not_panic() {
+        let mut dma: RawDMA = unsafe { ::core::mem::zeroed() };
+        dma[DMAChannel::One].enable_dma();
+
+        dma.configure::<Channel1>(0x4000_4428 as *const u32, 0x2000_0000 as *const u32, 8,
+                                  DataDirection::FromMem);
+        assert_eq!(dma[DMAChannel::One].get_number_of_data(), 8);
+        assert!(!dma.is_channel_enabled(DMAChannel::One));
+    }
+
+    #[test]
+    fn test_abort_disables_channel_and_clears_flags() {
+        let mut dma: RawDMA = unsafe { ::core::mem::zeroed() };
+        dma[DMAChannel::Two].enable_dma();
+        dma[DMAChannel::Three].enable_dma();
+
+        dma.abort(DMAChannel::Three);
+        assert_eq!(raw_word(&dma[DMAChannel::Three].ccr) & CCR_EN, 0);
+        assert_eq!(raw_word(&dma.ifcr), DMA_CGIF_1 << 8);
+        assert_eq!(raw_word(&dma[DMAChannel::Two].ccr) & CCR_EN, CCR_EN);
+    }
+
+    #[test]
+    fn test_abort_usart_clears_receive_request() {
+        let mut dma: RawDMA = unsafe { ::core::mem::zeroed() };
+        let mut usart: RawUsart = unsafe { ::core::mem::zeroed() };
+        usart.set_dma_mode(DMAMode::All);
+        dma[DMAChannel::Five].set_data_transfer_direction(DataDirection::FromPeriph);
+        dma[DMAChannel::Five].enable_dma();
+
+        dma.abort_usart(DMAChannel::Five, &mut usart);
+        assert_eq!(raw_word(&dma[DMAChannel::Five].ccr) & CCR_EN, 0);
+        assert_eq!(raw_word(&dma.ifcr), DMA_CGIF_1 << 16);
+        // Only the transmit request is left
+        assert_eq!(usart_cr3(&usart), usart_defs::CR3_DMAT);
+    }
+
+    #[test]
+    fn test_abort_usart_clears_transmit_request() {
+        let mut dma: RawDMA = unsafe { ::core::mem::zeroed() };
+        let mut usart: RawUsart = unsafe { ::core::mem::zeroed() };
+        usart.set_dma_mode(DMAMode::All);
+        dma[DMAChannel::Four].set_data_transfer_direction(DataDirection::FromMem);
+        dma[DMAChannel::Four].enable_dma();
+
+        dma.abort_usart(DMAChannel::Four, &mut usart);
+        assert_eq!(raw_word(&dma[DMAChannel::Four].ccr) & CCR_EN, 0);
+        assert_eq!(raw_word(&dma.ifcr), DMA_CGIF_1 << 12);
+        // Only the receive request is left
+        assert_eq!(usart_cr3(&usart), usart_defs::CR3_DMAR);
+    }
 }
@@ -0,0 +1,126 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! This module moves peripheral DMA requests between channels through the SYSCFG.
+
+use peripheral::{rcc, syscfg};
+use peripheral::syscfg::DmaRemap;
+use super::DMAChannel;
+
+/// Defines the peripheral DMA requests whose channel can be looked up or changed.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DmaRequest {
+    /// ADC, channel 1 or 2.
+    Adc,
+    /// USART1 TX, channel 2 or 4.
+    Usart1Tx,
+    /// USART1 RX, channel 3 or 5.
+    Usart1Rx,
+    /// USART2 TX, always channel 4.
+    Usart2Tx,
+    /// USART2 RX, always channel 5.
+    Usart2Rx,
+    /// TIM16, channel 3 or 4.
+    Tim16,
+    /// TIM17, channel 1 or 2.
+    Tim17,
+}
+
+/// Errors that can be reported when configuring the DMA.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DmaError {
+    /// The request can't be served by the channel.
+    UnsupportedRemap,
+}
+
+/// Serve a peripheral's DMA request on the specified channel, so requests that would otherwise
+/// share a channel can be spread out. Each request can only be served by its default channel or
+/// one alternate channel, any other channel returns `DmaError::UnsupportedRemap` and leaves the
+/// mapping as it was.
+///
+/// The channel should be remapped while no transfer is running for the request.
+pub fn remap_request(req: DmaRequest, channel: DMAChannel) -> Result<(), DmaError> {
+    let setting = match remap_setting(req, channel) {
+        Ok(setting) => setting,
+        Err(e) => return Err(e),
+    };
+
+    if let Some((remap, enable)) = setting {
+        let mut rcc = rcc::rcc();
+        rcc.enable_peripheral(rcc::Peripheral::SysCfgComp);
+        syscfg::syscfg().set_dma_remap(remap, enable);
+    }
+    Ok(())
+}
+
+// The default channel for the request, and the SYSCFG bit with the channel it moves the request
+// to if the request can be remapped.
+fn channels(req: DmaRequest) -> (DMAChannel, Option<(DmaRemap, DMAChannel)>) {
+    match req {
+        DmaRequest::Adc => (DMAChannel::One, Some((DmaRemap::Adc, DMAChannel::Two))),
+        DmaRequest::Usart1Tx => (DMAChannel::Two, Some((DmaRemap::Usart1Tx, DMAChannel::Four))),
+        DmaRequest::Usart1Rx => (DMAChannel::Three, Some((DmaRemap::Usart1Rx, DMAChannel::Five))),
+        DmaRequest::Usart2Tx => (DMAChannel::Four, None),
+        DmaRequest::Usart2Rx => (DMAChannel::Five, None),
+        DmaRequest::Tim16 => (DMAChannel::Three, Some((DmaRemap::Tim16, DMAChannel::Four))),
+        DmaRequest::Tim17 => (DMAChannel::One, Some((DmaRemap::Tim17, DMAChannel::Two))),
+    }
+}
+
+// The SYSCFG bit to write and its value to serve the request on the channel, `None` if the
+// request can't be remapped and the channel is already its default.
+fn remap_setting(req: DmaRequest, channel: DMAChannel)
+    -> Result<Option<(DmaRemap, bool)>, DmaError> {
+    match channels(req) {
+        (default, Some((remap, _))) if default == channel => Ok(Some((remap, false))),
+        (_, Some((remap, alternate))) if alternate == channel => Ok(Some((remap, true))),
+        (default, None) if default == channel => Ok(None),
+        _ => Err(DmaError::UnsupportedRemap),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remap_setting_alternate_channel() {
+        assert_eq!(remap_setting(DmaRequest::Adc, DMAChannel::Two),
+                   Ok(Some((DmaRemap::Adc, true))));
+        assert_eq!(remap_setting(DmaRequest::Usart1Rx, DMAChannel::Five),
+                   Ok(Some((DmaRemap::Usart1Rx, true))));
+    }
+
+    #[test]
+    fn test_remap_setting_default_channel_clears_remap() {
+        assert_eq!(remap_setting(DmaRequest::Usart1Tx, DMAChannel::Two),
+                   Ok(Some((DmaRemap::Usart1Tx, false))));
+    }
+
+    #[test]
+    fn test_remap_setting_fixed_request_on_default_channel() {
+        assert_eq!(remap_setting(DmaRequest::Usart2Tx, DMAChannel::Four), Ok(None));
+    }
+
+    #[test]
+    fn test_remap_setting_rejects_unsupported_channel() {
+        assert_eq!(remap_setting(DmaRequest::Adc, DMAChannel::Three),
+                   Err(DmaError::UnsupportedRemap));
+        assert_eq!(remap_setting(DmaRequest::Usart2Rx, DMAChannel::One),
+                   Err(DmaError::UnsupportedRemap));
+    }
+}
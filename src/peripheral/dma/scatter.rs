@@ -0,0 +1,235 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! This module provides a scatter list transfer for sending several non-contiguous buffers to a
+//! peripheral as one logical transfer on a single DMA channel.
+//!
+//! Each descriptor is transferred as a normal (non-circular) transfer. When the channel's TC flag
+//! is set the next descriptor is loaded into CMAR/CNDTR and the channel is re-enabled, until the
+//! list is exhausted.
+
+use super::{DMA, DMAChannel, DataDirection, PeriphAndMemSize, ChannelPriorityLevel};
+
+/// A single region of memory to be transferred, given as a start address and a length in bytes.
+pub type Descriptor = (*const u8, usize);
+
+/// Walks through a list of descriptors, skipping empty ones.
+///
+/// A descriptor with a length of 0 can't be loaded, the channel would never raise the TC flag to
+/// tell us to move on, so those are passed over.
+#[derive(Copy, Clone, Debug)]
+struct DescriptorCursor {
+    next: usize,
+}
+
+impl DescriptorCursor {
+    fn new() -> Self {
+        DescriptorCursor {
+            next: 0,
+        }
+    }
+
+    fn advance(&mut self, list: &[Descriptor]) -> Option<Descriptor> {
+        while self.next < list.len() {
+            let descriptor = list[self.next];
+            self.next += 1;
+            if descriptor.1 != 0 {
+                return Some(descriptor);
+            }
+        }
+        None
+    }
+}
+
+/// A transfer of several non-contiguous regions of memory to a peripheral on one DMA channel.
+///
+/// The descriptor list and the memory it points to must stay valid until the transfer is
+/// complete.
+///
+/// `transfer_complete` must be called from the channel's interrupt handler whenever the TC flag
+/// is set, it loads the next descriptor. Don't use the channel that is driving the serial output
+/// (channel 4) since its interrupt handler clears the TC flag.
+///
+/// Example Usage:
+/// ```
+///   static HEADER: [u8; 4] = [0xAA, 0x55, 0x00, 0x10];
+///   static PAYLOAD: [u8; 16] = [0; 16];
+///   let list = [(HEADER.as_ptr(), HEADER.len()), (PAYLOAD.as_ptr(), PAYLOAD.len())];
+///   let mut transfer = ScatterTransfer::new(DMAChannel::Two, USART1_TDR_ADDR, &list);
+///   transfer.start();
+///   // In the DMA channel 2/3 interrupt handler:
+///   transfer.transfer_complete();
+/// ```
+pub struct ScatterTransfer<'a> {
+    chan: DMAChannel,
+    peripheral_addr: *const u32,
+    list: &'a [Descriptor],
+    cursor: DescriptorCursor,
+    complete: bool,
+}
+
+impl<'a> ScatterTransfer<'a> {
+    /// Create a new scatter transfer on the specified channel.
+    ///
+    /// # Panics
+    ///
+    /// Each descriptor's length must be no greater than 65535 or the kernel will panic.
+    pub fn new(chan: DMAChannel, peripheral_addr: *const u32, list: &'a [Descriptor]) -> Self {
+        for descriptor in list {
+            if descriptor.1 > 65535 {
+                panic!("ScatterTransfer::new - descriptor length must be no greater than 65535!");
+            }
+        }
+
+        ScatterTransfer {
+            chan: chan,
+            peripheral_addr: peripheral_addr,
+            list: list,
+            cursor: DescriptorCursor::new(),
+            complete: true,
+        }
+    }
+
+    /// Configure the channel and start transferring the first descriptor.
+    ///
+    /// If there is nothing to transfer the transfer is immediately complete.
+    pub fn start(&mut self) {
+        let mut dma = DMA::new();
+        let chan = self.chan;
+
+        dma[chan].disable_dma();
+        dma.channel_global_interrupt_clear(chan);
+        dma[chan].set_peripheral_address(self.peripheral_addr);
+
+        dma[chan].set_channel_priority(ChannelPriorityLevel::Medium);
+        dma[chan].set_memory_size(PeriphAndMemSize::Eight);
+        dma[chan].set_peripheral_size(PeriphAndMemSize::Eight);
+        dma[chan].set_data_transfer_direction(DataDirection::FromMem);
+        dma[chan].enable_memory_increment_mode();
+        dma[chan].disable_peripheral_increment_mode();
+        dma[chan].disable_mem2mem_mode();
+        dma[chan].disable_circular_mode();
+        dma[chan].enable_transmit_complete_interrupt();
+
+        self.cursor = DescriptorCursor::new();
+        self.complete = false;
+        self.load_next(&mut dma);
+    }
+
+    /// Move on to the next descriptor, this should be called from the channel's interrupt handler
+    /// when the TC flag is set. Returns true if there is more data to transfer, false once the
+    /// list has been exhausted.
+    ///
+    /// The channel has to be disabled to reload CNDTR, so only CMAR and CNDTR are rewritten
+    /// between descriptors to keep the gap as short as possible. The peripheral keeps its DMA
+    /// request asserted while the channel is disabled, so no data is lost.
+    pub fn transfer_complete(&mut self) -> bool {
+        let mut dma = DMA::new();
+        let chan = self.chan;
+
+        if !dma.channel_transfer_complete_flag(chan) {
+            return !self.complete;
+        }
+        dma.channel_transfer_complete_clear(chan);
+        self.load_next(&mut dma);
+        !self.complete
+    }
+
+    /// Check if every descriptor has been transferred.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    fn load_next(&mut self, dma: &mut DMA) {
+        let chan = self.chan;
+
+        dma[chan].disable_dma();
+        match self.cursor.advance(self.list) {
+            Some((addr, len)) => {
+                dma[chan].set_memory_address(addr as *const u32);
+                dma[chan].set_number_of_data(len as u16);
+                dma[chan].enable_dma();
+            },
+            None => {
+                dma[chan].disable_transmit_complete_interrupt();
+                self.complete = true;
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::ptr;
+
+    fn addr(n: usize) -> *const u8 {
+        n as *const u8
+    }
+
+    #[test]
+    fn test_descriptor_cursor_empty_list_returns_none() {
+        let mut cursor = DescriptorCursor::new();
+        assert_eq!(cursor.advance(&[]), None);
+    }
+
+    #[test]
+    fn test_descriptor_cursor_advances_in_order() {
+        let list = [(addr(0x100), 4), (addr(0x200), 16), (addr(0x300), 1)];
+        let mut cursor = DescriptorCursor::new();
+
+        assert_eq!(cursor.advance(&list), Some((addr(0x100), 4)));
+        assert_eq!(cursor.advance(&list), Some((addr(0x200), 16)));
+        assert_eq!(cursor.advance(&list), Some((addr(0x300), 1)));
+        assert_eq!(cursor.advance(&list), None);
+    }
+
+    #[test]
+    fn test_descriptor_cursor_stays_exhausted() {
+        let list = [(addr(0x100), 4)];
+        let mut cursor = DescriptorCursor::new();
+
+        cursor.advance(&list);
+        assert_eq!(cursor.advance(&list), None);
+        assert_eq!(cursor.advance(&list), None);
+    }
+
+    #[test]
+    fn test_descriptor_cursor_skips_empty_descriptors() {
+        let list = [(addr(0x100), 0), (addr(0x200), 8), (ptr::null(), 0), (addr(0x300), 2)];
+        let mut cursor = DescriptorCursor::new();
+
+        assert_eq!(cursor.advance(&list), Some((addr(0x200), 8)));
+        assert_eq!(cursor.advance(&list), Some((addr(0x300), 2)));
+        assert_eq!(cursor.advance(&list), None);
+    }
+
+    #[test]
+    fn test_descriptor_cursor_all_empty_returns_none() {
+        let list = [(addr(0x100), 0), (addr(0x200), 0)];
+        let mut cursor = DescriptorCursor::new();
+
+        assert_eq!(cursor.advance(&list), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_scatter_transfer_descriptor_too_long_panics() {
+        let list = [(addr(0x100), 4), (addr(0x200), 65536)];
+        ScatterTransfer::new(DMAChannel::Two, ptr::null(), &list);
+    }
+}
@@ -0,0 +1,98 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! Element types that the DMA can move, and the transfer size that matches each of them.
+
+use super::PeriphAndMemSize;
+
+/// An element type the DMA can transfer, tying the Rust type to the matching peripheral and
+/// memory data size so that the two can't disagree.
+pub trait DmaWord: Copy {
+    /// The data size to configure for this element type.
+    const SIZE: PeriphAndMemSize;
+}
+
+impl DmaWord for u8 {
+    const SIZE: PeriphAndMemSize = PeriphAndMemSize::Eight;
+}
+
+impl DmaWord for u16 {
+    const SIZE: PeriphAndMemSize = PeriphAndMemSize::Sixteen;
+}
+
+impl DmaWord for u32 {
+    const SIZE: PeriphAndMemSize = PeriphAndMemSize::ThirtyTwo;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use peripheral::raw_word;
+    use super::super::{RawDMA, DMAChannel};
+    use super::super::defs::*;
+    use core::mem;
+
+    #[test]
+    fn test_start_transfer_u16_uses_half_words() {
+        let mut dma: RawDMA = unsafe { mem::zeroed() };
+        let data: [u16; 4] = [1, 2, 3, 4];
+        let mut reg: u16 = 0;
+
+        dma.start_transfer(DMAChannel::Two, &data, &mut reg as *mut u16);
+        let ccr = raw_word(&dma[DMAChannel::Two].ccr);
+        assert_eq!(ccr & (CCR_PSIZE0 | CCR_PSIZE1), CCR_PSIZE0);
+        assert_eq!(ccr & (CCR_MSIZE0 | CCR_MSIZE1), CCR_MSIZE0);
+        assert_eq!(ccr & CCR_DIR, CCR_DIR);
+        assert_eq!(ccr & CCR_EN, CCR_EN);
+        assert_eq!(raw_word(&dma[DMAChannel::Two].cndtr), 4);
+    }
+
+    #[test]
+    fn test_start_receive_u32_uses_words() {
+        let mut dma: RawDMA = unsafe { mem::zeroed() };
+        let mut data: [u32; 2] = [0; 2];
+        let reg: u32 = 0;
+
+        dma.start_receive(DMAChannel::Three, &reg as *const u32, &mut data);
+        let ccr = raw_word(&dma[DMAChannel::Three].ccr);
+        assert_eq!(ccr & (CCR_PSIZE0 | CCR_PSIZE1), CCR_PSIZE1);
+        assert_eq!(ccr & (CCR_MSIZE0 | CCR_MSIZE1), CCR_MSIZE1);
+        assert_eq!(ccr & CCR_DIR, 0);
+        assert_eq!(raw_word(&dma[DMAChannel::Three].cndtr), 2);
+    }
+
+    #[test]
+    fn test_start_transfer_u8_uses_bytes() {
+        let mut dma: RawDMA = unsafe { mem::zeroed() };
+        let data: [u8; 3] = [0; 3];
+        let mut reg: u8 = 0;
+
+        dma.start_transfer(DMAChannel::One, &data, &mut reg as *mut u8);
+        let ccr = raw_word(&dma[DMAChannel::One].ccr);
+        assert_eq!(ccr & (CCR_PSIZE0 | CCR_PSIZE1 | CCR_MSIZE0 | CCR_MSIZE1), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_start_transfer_too_long_panics() {
+        let mut dma: RawDMA = unsafe { mem::zeroed() };
+        let data: [u8; 0x1_0000] = [0; 0x1_0000];
+        let mut reg: u8 = 0;
+
+        dma.start_transfer(DMAChannel::One, &data, &mut reg as *mut u8);
+    }
+}
@@ -0,0 +1,33 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use peripheral::addresses;
+
+pub const EXTI_ADDR: *const u32 = addresses::EXTI_ADDR;
+
+// The highest numbered EXTI line.
+pub const MAX_LINE: u8 = 31;
+
+// Interrupt Mask Register
+pub const IMR_OFFSET: u32 = 0x00;
+
+// Rising and Falling Trigger Selection Registers
+pub const RTSR_OFFSET: u32 = 0x08;
+pub const FTSR_OFFSET: u32 = 0x0C;
+
+// Pending Register
+pub const PR_OFFSET: u32 = 0x14;
@@ -0,0 +1,79 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use super::defs::*;
+
+#[derive(Copy, Clone, Debug)]
+pub struct IMR(u32);
+
+impl IMR {
+    /* Bits 31:0 IMx: Interrupt Mask on line x
+     *   0: Interrupt request from Line x is masked
+     *   1: Interrupt request from Line x is not masked
+     */
+    pub fn set_line(&mut self, enable: bool, line: u8) {
+        if line > MAX_LINE {
+            panic!("IMR::set_line - specified line must be between [0..31]!");
+        }
+        let mask = 0b1 << line;
+
+        self.0 &= !mask;
+        if enable {
+            self.0 |= mask;
+        }
+    }
+
+    pub fn get_line(&self, line: u8) -> bool {
+        if line > MAX_LINE {
+            panic!("IMR::get_line - specified line must be between [0..31]!");
+        }
+        self.0 & (0b1 << line) != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_imr_set_line() {
+        let mut imr = IMR(0);
+
+        imr.set_line(true, 0);
+        imr.set_line(true, 13);
+        assert_eq!(imr.0, 0b1 | 0b1 << 13);
+
+        imr.set_line(false, 0);
+        assert_eq!(imr.0, 0b1 << 13);
+    }
+
+    #[test]
+    fn test_imr_get_line() {
+        let imr = IMR(0b1 << 31);
+
+        assert!(imr.get_line(31));
+        assert!(!imr.get_line(30));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_imr_set_line_greater_than_31_panics() {
+        let mut imr = IMR(0);
+
+        imr.set_line(true, 32);
+    }
+}
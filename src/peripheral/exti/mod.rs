@@ -0,0 +1,204 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! This module controls the extended interrupts and events controller (EXTI), which routes edge
+//! events on the external lines to the NVIC.
+//!
+//! An interrupt handler for an EXTI line must clear the line's pending bit with `clear_pending`,
+//! otherwise the interrupt fires again as soon as the handler returns.
+
+mod imr;
+mod pr;
+mod tr;
+mod defs;
+
+use core::ops::{Deref, DerefMut};
+use volatile::Volatile;
+use self::defs::*;
+use self::imr::IMR;
+use self::pr::PR;
+use self::tr::TR;
+
+/// Returns an instance of the EXTI struct so it can be used to configure the external interrupts.
+pub fn exti() -> EXTI {
+    EXTI::exti()
+}
+
+#[derive(Copy, Clone, Debug)]
+#[repr(C)]
+#[doc(hidden)]
+pub struct RawEXTI {
+    imr: IMR,
+    emr: u32,
+    rtsr: TR,
+    ftsr: TR,
+    swier: u32,
+    pr: PR,
+}
+
+/// Extended Interrupts and Events Controller
+#[derive(Copy, Clone, Debug)]
+pub struct EXTI(Volatile<RawEXTI>);
+
+impl EXTI {
+    fn exti() -> Self {
+        unsafe {
+            EXTI(Volatile::new(EXTI_ADDR as *const _))
+        }
+    }
+}
+
+impl Deref for EXTI {
+    type Target = RawEXTI;
+
+    fn deref(&self) -> &Self::Target {
+        &*(self.0)
+    }
+}
+
+impl DerefMut for EXTI {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut *(self.0)
+    }
+}
+
+impl RawEXTI {
+    /// Unmask the interrupt request from the line.
+    ///
+    /// # Panics
+    ///
+    /// Line must be a value between [0..31] or the kernel will panic.
+    pub fn enable_line(&mut self, line: u8) {
+        self.imr.set_line(true, line);
+    }
+
+    /// Mask the interrupt request from the line.
+    ///
+    /// # Panics
+    ///
+    /// Line must be a value between [0..31] or the kernel will panic.
+    pub fn disable_line(&mut self, line: u8) {
+        self.imr.set_line(false, line);
+    }
+
+    /// Check if the interrupt request from the line is unmasked.
+    ///
+    /// # Panics
+    ///
+    /// Line must be a value between [0..31] or the kernel will panic.
+    pub fn is_line_enabled(&self, line: u8) -> bool {
+        self.imr.get_line(line)
+    }
+
+    /// Trigger an event on the line on a rising edge. This can be combined with a falling edge
+    /// trigger to catch both edges.
+    ///
+    /// # Panics
+    ///
+    /// Line must be a value between [0..31] or the kernel will panic.
+    pub fn set_rising_trigger(&mut self, line: u8, enable: bool) {
+        self.rtsr.set_line(enable, line);
+    }
+
+    /// Trigger an event on the line on a falling edge.
+    ///
+    /// # Panics
+    ///
+    /// Line must be a value between [0..31] or the kernel will panic.
+    pub fn set_falling_trigger(&mut self, line: u8, enable: bool) {
+        self.ftsr.set_line(enable, line);
+    }
+
+    /// Check if an edge event is pending on the line.
+    ///
+    /// # Panics
+    ///
+    /// Line must be a value between [0..31] or the kernel will panic.
+    pub fn is_pending(&self, line: u8) -> bool {
+        self.pr.is_pending(line)
+    }
+
+    /// Get the pending edge events on every line at once, line `n` is bit `n`.
+    pub fn pending_lines(&self) -> u32 {
+        self.pr.get_pending()
+    }
+
+    /// Clear the pending edge event on the line. Pending events on other lines are left as they
+    /// are.
+    ///
+    /// # Panics
+    ///
+    /// Line must be a value between [0..31] or the kernel will panic.
+    pub fn clear_pending(&mut self, line: u8) {
+        self.pr.clear_pending(line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use peripheral::raw_word;
+    use core::{mem, ptr};
+
+    #[test]
+    fn test_enable_disable_line() {
+        let mut exti: RawEXTI = unsafe { mem::zeroed() };
+
+        exti.enable_line(0);
+        exti.enable_line(5);
+        assert_eq!(raw_word(&exti.imr), 0b1 | 0b1 << 5);
+        assert!(exti.is_line_enabled(5));
+
+        exti.disable_line(0);
+        assert_eq!(raw_word(&exti.imr), 0b1 << 5);
+        assert!(!exti.is_line_enabled(0));
+    }
+
+    #[test]
+    fn test_set_triggers() {
+        let mut exti: RawEXTI = unsafe { mem::zeroed() };
+
+        exti.set_rising_trigger(4, true);
+        exti.set_falling_trigger(4, true);
+        exti.set_falling_trigger(4, false);
+        assert_eq!(raw_word(&exti.rtsr), 0b1 << 4);
+        assert_eq!(raw_word(&exti.ftsr), 0);
+    }
+
+    #[test]
+    fn test_clear_pending_writes_one_to_the_line() {
+        let mut exti: RawEXTI = unsafe { mem::zeroed() };
+        unsafe { ptr::write(&mut exti.pr as *mut _ as *mut u32, 0b1 << 3 | 0b1 << 9); }
+        assert!(exti.is_pending(3));
+        assert!(exti.is_pending(9));
+
+        exti.clear_pending(3);
+        // Line 9 must not be written, or its event would be lost
+        assert_eq!(raw_word(&exti.pr), 0b1 << 3);
+    }
+
+    #[test]
+    fn test_register_offsets() {
+        let exti: RawEXTI = unsafe { mem::zeroed() };
+        let base = &exti as *const _ as usize;
+
+        assert_eq!(&exti.imr as *const _ as usize - base, IMR_OFFSET as usize);
+        assert_eq!(&exti.rtsr as *const _ as usize - base, RTSR_OFFSET as usize);
+        assert_eq!(&exti.ftsr as *const _ as usize - base, FTSR_OFFSET as usize);
+        assert_eq!(&exti.pr as *const _ as usize - base, PR_OFFSET as usize);
+    }
+}
@@ -0,0 +1,80 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use super::defs::*;
+
+#[derive(Copy, Clone, Debug)]
+pub struct PR(u32);
+
+impl PR {
+    /* Bits 31:0 PIFx: Pending bit on line x
+     *   0: No trigger request occurred
+     *   1: Selected trigger request occurred
+     *   This bit is set when the selected edge event arrives on the external
+     *   interrupt line. This bit is cleared by writing it to 1.
+     */
+    pub fn is_pending(&self, line: u8) -> bool {
+        if line > MAX_LINE {
+            panic!("PR::is_pending - specified line must be between [0..31]!");
+        }
+        self.0 & (0b1 << line) != 0
+    }
+
+    pub fn get_pending(&self) -> u32 {
+        self.0
+    }
+
+    // Writing back the pending bits we read would clear every line that was pending, not just the
+    // one we want, so only the bit for the line is written.
+    pub fn clear_pending(&mut self, line: u8) {
+        if line > MAX_LINE {
+            panic!("PR::clear_pending - specified line must be between [0..31]!");
+        }
+        self.0 = 0b1 << line;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pr_is_pending() {
+        let pr = PR(0b1 << 4 | 0b1);
+
+        assert!(pr.is_pending(0));
+        assert!(pr.is_pending(4));
+        assert!(!pr.is_pending(1));
+    }
+
+    #[test]
+    fn test_pr_clear_pending_writes_only_the_line() {
+        // Lines 2 and 7 are pending
+        let mut pr = PR(0b1 << 2 | 0b1 << 7);
+
+        pr.clear_pending(7);
+        assert_eq!(pr.0, 0b1 << 7);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_pr_clear_pending_line_greater_than_31_panics() {
+        let mut pr = PR(0);
+
+        pr.clear_pending(32);
+    }
+}
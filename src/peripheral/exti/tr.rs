@@ -0,0 +1,70 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use super::defs::*;
+
+// Used for both the rising and falling trigger selection registers, which share a layout.
+#[derive(Copy, Clone, Debug)]
+pub struct TR(u32);
+
+impl TR {
+    /* Bits 31:0 TRx: Rising/Falling trigger event configuration bit of line x
+     *   0: Rising/Falling trigger disabled (for Event and Interrupt) for input line
+     *   1: Rising/Falling trigger enabled (for Event and Interrupt) for input line
+     */
+    pub fn set_line(&mut self, enable: bool, line: u8) {
+        if line > MAX_LINE {
+            panic!("TR::set_line - specified line must be between [0..31]!");
+        }
+        let mask = 0b1 << line;
+
+        self.0 &= !mask;
+        if enable {
+            self.0 |= mask;
+        }
+    }
+
+    pub fn get_line(&self, line: u8) -> bool {
+        if line > MAX_LINE {
+            panic!("TR::get_line - specified line must be between [0..31]!");
+        }
+        self.0 & (0b1 << line) != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tr_set_line() {
+        let mut tr = TR(0b1 << 2);
+
+        tr.set_line(true, 7);
+        assert_eq!(tr.0, 0b1 << 2 | 0b1 << 7);
+        assert!(tr.get_line(7));
+
+        tr.set_line(false, 2);
+        assert_eq!(tr.0, 0b1 << 7);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_tr_get_line_greater_than_31_panics() {
+        TR(0).get_line(32);
+    }
+}
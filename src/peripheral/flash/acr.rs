@@ -0,0 +1,110 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use super::defs::*;
+
+#[derive(Copy, Clone, Debug)]
+pub struct ACR(u32);
+
+impl ACR {
+    /* Bits 2:0 LATENCY[2:0]: Latency
+     *   These bits represent the ratio of the SYSCLK period to the flash
+     *   access time.
+     *   000: Zero wait state, if SYSCLK <= 24 MHz
+     *   001: One wait state, if 24 MHz < SYSCLK <= 48 MHz
+     */
+    pub fn set_latency(&mut self, wait_states: u8) {
+        if wait_states > 1 {
+            panic!("ACR::set_latency - wait states must be 0 or 1!");
+        }
+        self.0 &= !ACR_LATENCY_MASK;
+        self.0 |= wait_states as u32;
+    }
+
+    pub fn get_latency(&self) -> u8 {
+        (self.0 & ACR_LATENCY_MASK) as u8
+    }
+
+    /* Bit 4 PRFTBE: Prefetch buffer enable
+     *   0: Prefetch is disabled
+     *   1: Prefetch is enabled
+     */
+    pub fn set_prefetch(&mut self, enable: bool) {
+        self.0 &= !ACR_PRFTBE;
+        if enable {
+            self.0 |= ACR_PRFTBE;
+        }
+    }
+
+    /* Bit 5 PRFTBS: Prefetch buffer status
+     *   This bit provides the status of the prefetch buffer.
+     *   0: Prefetch buffer is disabled
+     *   1: Prefetch buffer is enabled
+     */
+    pub fn prefetch_enabled(&self) -> bool {
+        self.0 & ACR_PRFTBS != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_acr_set_prefetch() {
+        let mut acr = ACR(0);
+
+        acr.set_prefetch(true);
+        assert_eq!(acr.0, 0b1 << 4);
+
+        acr.set_prefetch(false);
+        assert_eq!(acr.0, 0);
+    }
+
+    #[test]
+    fn test_acr_set_prefetch_keeps_latency() {
+        let mut acr = ACR(0b001);
+
+        acr.set_prefetch(true);
+        assert_eq!(acr.0, 0b1 << 4 | 0b001);
+    }
+
+    #[test]
+    fn test_acr_set_latency_keeps_prefetch() {
+        let mut acr = ACR(0b1 << 4);
+
+        acr.set_latency(1);
+        assert_eq!(acr.0, 0b1 << 4 | 0b001);
+        assert_eq!(acr.get_latency(), 1);
+
+        acr.set_latency(0);
+        assert_eq!(acr.0, 0b1 << 4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_acr_set_latency_out_of_range_panics() {
+        ACR(0).set_latency(2);
+    }
+
+    #[test]
+    fn test_acr_prefetch_enabled_reads_status() {
+        // The enable bit alone doesn't mean the buffer is on yet
+        assert!(!ACR(0b1 << 4).prefetch_enabled());
+        assert!(ACR(0b11 << 4).prefetch_enabled());
+    }
+}
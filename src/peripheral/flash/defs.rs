@@ -0,0 +1,26 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use peripheral::addresses;
+
+pub const FLASH_ADDR: *const u32 = addresses::FLASH_ADDR;
+
+// Flash Access Control Register
+pub const ACR_OFFSET: u32 = 0x00;
+pub const ACR_LATENCY_MASK: u32 = 0b111;
+pub const ACR_PRFTBE: u32 = 0b1 << 4;
+pub const ACR_PRFTBS: u32 = 0b1 << 5;
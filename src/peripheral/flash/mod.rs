@@ -0,0 +1,109 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! This module controls the flash memory interface.
+
+mod acr;
+mod defs;
+
+use core::ops::{Deref, DerefMut};
+use volatile::Volatile;
+use arm::asm::{data_barrier, instruction_barrier};
+use self::defs::*;
+use self::acr::ACR;
+
+/// Returns an instance of the FLASH struct so it can be used to configure the flash interface.
+pub fn flash() -> FLASH {
+    FLASH::flash()
+}
+
+#[derive(Copy, Clone, Debug)]
+#[repr(C)]
+#[doc(hidden)]
+pub struct RawFLASH {
+    acr: ACR,
+    keyr: u32,
+    optkeyr: u32,
+    sr: u32,
+    cr: u32,
+    ar: u32,
+    _res: u32,
+    obr: u32,
+    wrpr: u32,
+}
+
+/// Flash Memory Interface
+#[derive(Copy, Clone, Debug)]
+pub struct FLASH(Volatile<RawFLASH>);
+
+impl FLASH {
+    fn flash() -> Self {
+        unsafe {
+            FLASH(Volatile::new(FLASH_ADDR as *const _))
+        }
+    }
+}
+
+impl Deref for FLASH {
+    type Target = RawFLASH;
+
+    fn deref(&self) -> &Self::Target {
+        &*(self.0)
+    }
+}
+
+impl DerefMut for FLASH {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut *(self.0)
+    }
+}
+
+impl RawFLASH {
+    /// Set the number of flash wait states. 0 is enough for SYSCLK up to 24 MHz, above that 1 is
+    /// needed. The wait states must be raised before speeding SYSCLK up, and only lowered after
+    /// slowing it down.
+    ///
+    /// # Panics
+    ///
+    /// Wait states must be 0 or 1 or the kernel will panic.
+    pub fn set_latency(&mut self, wait_states: u8) {
+        self.acr.set_latency(wait_states);
+        // Instructions already fetched with the old wait states need to be fetched again
+        data_barrier();
+        instruction_barrier();
+    }
+
+    /// Get the number of flash wait states.
+    pub fn get_latency(&self) -> u8 {
+        self.acr.get_latency()
+    }
+
+    /// Turn the prefetch buffer on or off.
+    ///
+    /// The prefetch buffer hides the flash wait states when running code sequentially, so it
+    /// should be on whenever a wait state is used. It can only be turned on or off while SYSCLK is
+    /// running at 24 MHz or less.
+    pub fn set_prefetch(&mut self, enable: bool) {
+        self.acr.set_prefetch(enable);
+    }
+
+    /// Check if the prefetch buffer is on. This reads the buffer's status rather than the enable
+    /// bit, so it only returns true once the buffer is actually running.
+    pub fn prefetch_enabled(&self) -> bool {
+        self.acr.prefetch_enabled()
+    }
+}
@@ -22,7 +22,7 @@ use super::defs::*;
 ///
 /// See data sheet for port mappings.
 #[allow(missing_docs)]
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum AlternateFunction {
     Zero,
     One,
@@ -50,6 +50,27 @@ impl Field for AlternateFunction {
 }
 
 impl AlternateFunction {
+    /// Get the raw 4-bit register encoding of this value.
+    pub fn as_bits(self) -> u8 {
+        self.mask() as u8
+    }
+
+    /// Convert a raw 4-bit register encoding back into a value, returns `None` if the bits are
+    /// not a valid encoding.
+    pub fn from_bits(bits: u8) -> Option<Self> {
+        match bits as u32 {
+            AF0 => Some(AlternateFunction::Zero),
+            AF1 => Some(AlternateFunction::One),
+            AF2 => Some(AlternateFunction::Two),
+            AF3 => Some(AlternateFunction::Three),
+            AF4 => Some(AlternateFunction::Four),
+            AF5 => Some(AlternateFunction::Five),
+            AF6 => Some(AlternateFunction::Six),
+            AF7 => Some(AlternateFunction::Seven),
+            _ => None,
+        }
+    }
+
     fn from_mask(mask: u32) -> Self {
         match mask {
             AF0 => AlternateFunction::Zero,
@@ -67,9 +88,12 @@ impl AlternateFunction {
 
 #[derive(Copy, Clone, Debug)]
 pub struct AFRL(u32);
+
+impl_register!(AFRL);
+
 impl AFRL {
     pub fn set_function(&mut self, function: AlternateFunction, port: u8) {
-        if port > 8 {
+        if port > 7 {
             panic!("AFRL::set_function - specified port must be between [0..7]!");
         }
         let mask = function.mask();
@@ -79,10 +103,10 @@ impl AFRL {
     }
 
     pub fn get_function(&self, port: u8) -> AlternateFunction {
-        if port > 8 {
-            panic!("AFRL::set_function - specified port must be between [0..7]!");
+        if port > 7 {
+            panic!("AFRL::get_function - specified port must be between [0..7]!");
         }
-        let mask = self.0 & (AFR_MASK << (port * 4));
+        let mask = (self.0 >> (port * 4)) & AFR_MASK;
 
         AlternateFunction::from_mask(mask)
     }
@@ -90,6 +114,9 @@ impl AFRL {
 
 #[derive(Copy, Clone, Debug)]
 pub struct AFRH(u32);
+
+impl_register!(AFRH);
+
 impl AFRH {
     pub fn set_function(&mut self, function: AlternateFunction, port: u8) {
         if port > 15 || port < 8 {
@@ -111,7 +138,7 @@ impl AFRH {
         }
         // #9: See comment in `set_function`
         let port = port - 8;
-        let mask = self.0 & (AFR_MASK << (port * 4));
+        let mask = (self.0 >> (port * 4)) & AFR_MASK;
 
         AlternateFunction::from_mask(mask)
     }
@@ -150,4 +177,43 @@ mod tests {
         let mut afrh = AFRH(0);
         afrh.set_function(AlternateFunction::Seven, 2);
     }
+
+    #[test]
+    fn test_afrl_get_function() {
+        let afrl = AFRL(0x2000);
+
+        assert_eq!(afrl.get_function(3), AlternateFunction::Two);
+        assert_eq!(afrl.get_function(2), AlternateFunction::Zero);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_afrl_get_port_out_of_bounds_panics() {
+        AFRL(0).get_function(8);
+    }
+
+    #[test]
+    fn test_afrh_get_function() {
+        let afrh = AFRH(0x7 << 12 | 0x5);
+
+        assert_eq!(afrh.get_function(8), AlternateFunction::Five);
+        assert_eq!(afrh.get_function(11), AlternateFunction::Seven);
+    }
+
+    #[test]
+    fn test_alternate_function_bits_round_trip() {
+        let functions = [AlternateFunction::Zero, AlternateFunction::One, AlternateFunction::Two,
+            AlternateFunction::Three, AlternateFunction::Four, AlternateFunction::Five,
+            AlternateFunction::Six, AlternateFunction::Seven];
+        for (i, function) in functions.iter().enumerate() {
+            assert_eq!(function.as_bits(), i as u8);
+            assert_eq!(AlternateFunction::from_bits(function.as_bits()), Some(*function));
+        }
+    }
+
+    #[test]
+    fn test_alternate_function_from_bits_reserved_is_none() {
+        assert_eq!(AlternateFunction::from_bits(0b1000), None);
+        assert_eq!(AlternateFunction::from_bits(0b1111), None);
+    }
 }
@@ -20,6 +20,8 @@ use super::defs::*;
 #[derive(Copy, Clone, Debug)]
 pub struct BSRR(u32);
 
+impl_register!(BSRR);
+
 impl BSRR {
     /// Set the bit high for the specified port, port must be a value between [0..15] or the kernel
     /// will panic.
@@ -36,6 +38,19 @@ impl BSRR {
         }
         self.0 |= 0b1 << (port + BSRR_RESET_OFFSET);
     }
+
+    /// Drive all 16 outputs at once, port `n` is set high if bit `n` of `value` is set and low
+    /// otherwise. Every port is either set or reset by the one write, so the outputs change
+    /// together.
+    pub fn write_port(&mut self, value: u16) {
+        self.0 = (value as u32) | ((!value as u32) << BSRR_RESET_OFFSET);
+    }
+
+    /// Drive only the outputs selected by `mask`, port `n` is set high if bit `n` of `value` is
+    /// set and low otherwise. Ports outside of the mask are neither set nor reset.
+    pub fn write_masked(&mut self, value: u16, mask: u16) {
+        self.0 = ((value & mask) as u32) | (((!value & mask) as u32) << BSRR_RESET_OFFSET);
+    }
 }
 
 #[cfg(test)]
@@ -83,4 +98,26 @@ mod tests {
         let mut bsrr = BSRR(0);
         bsrr.set(16);
     }
+
+    #[test]
+    fn test_bsrr_write_port_sets_and_resets_every_port() {
+        let mut bsrr = BSRR(0);
+        bsrr.write_port(0x00FF);
+        assert_eq!(bsrr.0, 0xFF00_00FF);
+    }
+
+    #[test]
+    fn test_bsrr_write_port_replaces_previous_value() {
+        let mut bsrr = BSRR(0);
+        bsrr.set(15);
+        bsrr.write_port(0x0001);
+        assert_eq!(bsrr.0, 0xFFFE_0001);
+    }
+
+    #[test]
+    fn test_bsrr_write_masked_leaves_other_ports_alone() {
+        let mut bsrr = BSRR(0);
+        bsrr.write_masked(0xA50, 0xFF0);
+        assert_eq!(bsrr.0, 0x05A0_0A50);
+    }
 }
@@ -0,0 +1,212 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! This module drives a group of pins together as a parallel bus.
+
+use super::{GPIO, Port, Group};
+
+/// A set of pins driven together as one value, e.g. the 8 data lines of a parallel LCD. Bit `n`
+/// of the value is driven on `pins[n]`.
+///
+/// The pins can be spread over several groups. If they are all in one group and in order with
+/// no gaps, the whole bus is written with a single BSRR write and read with a single IDR read,
+/// otherwise each pin is written and read on its own.
+///
+/// The pins must already be configured, the bus doesn't change their mode.
+#[derive(Copy, Clone, Debug)]
+pub struct PinBus<'a> {
+    pins: &'a [Port],
+    contiguous: Option<(Group, u8)>,
+}
+
+impl<'a> PinBus<'a> {
+    /// Create a bus from the pins, `pins[0]` is the least significant bit.
+    ///
+    /// # Panics
+    ///
+    /// A bus can have at most 32 pins, more will cause the kernel to panic.
+    pub fn new(pins: &'a [Port]) -> Self {
+        if pins.len() > 32 {
+            panic!("PinBus::new - a bus can have at most 32 pins!");
+        }
+        PinBus {
+            pins: pins,
+            contiguous: contiguous(pins),
+        }
+    }
+
+    /// Get the number of pins on the bus.
+    pub fn width(&self) -> usize {
+        self.pins.len()
+    }
+
+    /// Drive the pins from `value`, bits above the width of the bus are ignored.
+    pub fn write(&mut self, value: u32) {
+        match self.contiguous {
+            Some((group, first)) => {
+                let (bits, mask) = port_bits(first, self.pins.len(), value);
+                GPIO::group(group).write_port_masked(bits, mask);
+            },
+            None => scatter(self.pins, value, |mut pin, high| {
+                if high {
+                    pin.set();
+                } else {
+                    pin.reset();
+                }
+            }),
+        }
+    }
+
+    /// Read the pins into a value, bits above the width of the bus are 0.
+    pub fn read(&self) -> u32 {
+        match self.contiguous {
+            Some((group, first)) => {
+                let mask = bus_mask(self.pins.len());
+                (GPIO::group(group).read_port() as u32 >> first) & mask
+            },
+            None => gather(self.pins, |pin| pin.read()),
+        }
+    }
+}
+
+// The group and first port of the pins if they're all in one group and in order with no gaps.
+fn contiguous(pins: &[Port]) -> Option<(Group, u8)> {
+    let first = match pins.first() {
+        Some(pin) => *pin,
+        None => return None,
+    };
+    for (i, pin) in pins.iter().enumerate() {
+        if pin.get_group() != first.get_group() ||
+            pin.get_index() as usize != first.get_index() as usize + i {
+            return None;
+        }
+    }
+    Some((first.get_group(), first.get_index()))
+}
+
+fn bus_mask(width: usize) -> u32 {
+    if width >= 32 {
+        !0
+    } else {
+        (0b1 << width) - 1
+    }
+}
+
+// The port bits and the mask of the ports to write for a contiguous bus starting at `first`.
+fn port_bits(first: u8, width: usize, value: u32) -> (u16, u16) {
+    let mask = bus_mask(width);
+    (((value & mask) << first) as u16, (mask << first) as u16)
+}
+
+// Calls `f` with each pin and whether its bit is set in `value`.
+fn scatter<F: FnMut(Port, bool)>(pins: &[Port], value: u32, mut f: F) {
+    for (i, pin) in pins.iter().enumerate() {
+        f(*pin, value & (0b1 << i) != 0);
+    }
+}
+
+// Builds a value from each pin's level as read by `read`.
+fn gather<F: Fn(Port) -> bool>(pins: &[Port], read: F) -> u32 {
+    let mut value = 0;
+    for (i, pin) in pins.iter().enumerate() {
+        if read(*pin) {
+            value |= 0b1 << i;
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    fn ports(group: Group, indexes: &[u8]) -> Vec<Port> {
+        indexes.iter().map(|&i| Port::new(i, group)).collect()
+    }
+
+    #[test]
+    fn test_contiguous_pins_use_fast_path() {
+        let pins = ports(Group::B, &[4, 5, 6, 7, 8, 9, 10, 11]);
+
+        assert_eq!(contiguous(&pins), Some((Group::B, 4)));
+    }
+
+    #[test]
+    fn test_pins_with_gap_are_scattered() {
+        let pins = ports(Group::A, &[0, 1, 3, 4]);
+
+        assert_eq!(contiguous(&pins), None);
+    }
+
+    #[test]
+    fn test_pins_out_of_order_are_scattered() {
+        let pins = ports(Group::A, &[1, 0]);
+
+        assert_eq!(contiguous(&pins), None);
+    }
+
+    #[test]
+    fn test_pins_across_groups_are_scattered() {
+        let mut pins = ports(Group::A, &[0, 1]);
+        pins.push(Port::new(2, Group::B));
+
+        assert_eq!(contiguous(&pins), None);
+    }
+
+    #[test]
+    fn test_port_bits_shifts_value_to_first_port() {
+        assert_eq!(port_bits(4, 8, 0xA5), (0xA50, 0xFF0));
+    }
+
+    #[test]
+    fn test_port_bits_ignores_bits_above_width() {
+        assert_eq!(port_bits(0, 4, 0xFFF3), (0x3, 0xF));
+    }
+
+    #[test]
+    fn test_port_bits_full_width() {
+        assert_eq!(port_bits(0, 16, 0x1234), (0x1234, 0xFFFF));
+    }
+
+    #[test]
+    fn test_scatter_maps_bits_to_pins() {
+        let mut pins = ports(Group::A, &[7, 2]);
+        pins.push(Port::new(9, Group::C));
+        let mut levels = Vec::new();
+
+        scatter(&pins, 0b101, |pin, high| levels.push((pin.get_group(), pin.get_index(), high)));
+        assert_eq!(levels, [(Group::A, 7, true), (Group::A, 2, false), (Group::C, 9, true)]);
+    }
+
+    #[test]
+    fn test_gather_maps_pins_to_bits() {
+        let mut pins = ports(Group::A, &[7, 2]);
+        pins.push(Port::new(9, Group::C));
+
+        let value = gather(&pins, |pin| pin.get_index() != 2);
+        assert_eq!(value, 0b101);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bus_wider_than_32_pins_panics() {
+        let pins = [Port::new(0, Group::A); 33];
+
+        PinBus::new(&pins);
+    }
+}
@@ -0,0 +1,159 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! This module keeps track of the callbacks to run when a GPIO pin's EXTI line fires.
+
+use interrupt::{self, Interrupt};
+use peripheral::{rcc, exti, syscfg};
+use peripheral::callback::{CallbackSlot, CALLBACK_SLOT_INIT};
+use peripheral::exti::RawEXTI;
+use super::Group;
+
+// One slot per EXTI line 0 - 15.
+static CALLBACKS: [CallbackSlot; 16] = [
+    CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT,
+    CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT,
+    CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT,
+    CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT,
+];
+
+/// Run `f` from the EXTI interrupt whenever a rising edge arrives on the pin. Registering a new
+/// callback for a pin number replaces the old one.
+///
+/// Pin `n` of every group shares EXTI line `n`, so registering PB3 takes line 3 away from PA3.
+/// The edge can be changed with `exti().set_falling_trigger` and `exti().set_rising_trigger`.
+/// The pending bit is cleared before `f` runs.
+///
+/// # Panics
+///
+/// Port must be a value between [0..15] or the kernel will panic.
+pub fn on_interrupt(group: Group, port: u8, f: fn()) {
+    if port > 15 {
+        panic!("gpio::on_interrupt - port must be a value between 0..15");
+    }
+    CALLBACKS[port as usize].store(f);
+
+    let mut rcc = rcc::rcc();
+    rcc.enable_peripheral(rcc::Peripheral::SysCfgComp);
+    syscfg::syscfg().set_exti_source(port, group);
+
+    let mut exti = exti::exti();
+    exti.set_rising_trigger(port, true);
+    exti.clear_pending(port);
+    exti.enable_line(port);
+
+    interrupt::nvic().enable_interrupt(line_interrupt(port));
+}
+
+// Check each of the lines in [first..last] for a pending event, clearing it and running the
+// line's callback. This should only be called from the EXTI interrupt handlers.
+#[doc(hidden)]
+pub fn dispatch_interrupt(first: u8, last: u8) {
+    let mut exti = exti::exti();
+    dispatch(&mut exti, &CALLBACKS, first, last);
+}
+
+// The NVIC interrupt that EXTI line 0 - 15 is routed to.
+fn line_interrupt(line: u8) -> Interrupt {
+    match line {
+        0...1 => Interrupt::Exti01,
+        2...3 => Interrupt::Exti23,
+        _ => Interrupt::Exti415,
+    }
+}
+
+// PR is read once up front, so an event that arrives while the callbacks run is left pending and
+// fires the interrupt again.
+fn dispatch(exti: &mut RawEXTI, callbacks: &[CallbackSlot], first: u8, last: u8) {
+    let pending = exti.pending_lines();
+    for line in first..last + 1 {
+        if pending & (0b1 << line) != 0 {
+            exti.clear_pending(line);
+            callbacks[line as usize].run();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use altos_core::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
+    use core::{mem, ptr};
+
+    static CALLED_FOUR: AtomicUsize = ATOMIC_USIZE_INIT;
+    static CALLED_NINE: AtomicUsize = ATOMIC_USIZE_INIT;
+    static CALLED_FIFTEEN: AtomicUsize = ATOMIC_USIZE_INIT;
+
+    fn on_four() { CALLED_FOUR.fetch_add(1, Ordering::SeqCst); }
+    fn on_nine() { CALLED_NINE.fetch_add(1, Ordering::SeqCst); }
+    fn on_fifteen() { CALLED_FIFTEEN.fetch_add(1, Ordering::SeqCst); }
+
+    // The PR offset within the EXTI block, the test can't see the private field.
+    const PR_OFFSET: isize = 0x14;
+
+    fn mock_exti(pending: u32) -> RawEXTI {
+        let mut exti: RawEXTI = unsafe { mem::zeroed() };
+        let pr = unsafe { (&mut exti as *mut RawEXTI as *mut u8).offset(PR_OFFSET) as *mut u32 };
+        unsafe { ptr::write(pr, pending); }
+        exti
+    }
+
+    fn slots() -> [CallbackSlot; 16] {
+        let slots = [
+            CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT,
+            CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT,
+            CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT,
+            CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT, CALLBACK_SLOT_INIT,
+        ];
+        slots[4].store(on_four);
+        slots[9].store(on_nine);
+        slots[15].store(on_fifteen);
+        slots
+    }
+
+    #[test]
+    fn test_line_interrupt() {
+        assert_eq!(line_interrupt(0), Interrupt::Exti01);
+        assert_eq!(line_interrupt(1), Interrupt::Exti01);
+        assert_eq!(line_interrupt(3), Interrupt::Exti23);
+        assert_eq!(line_interrupt(4), Interrupt::Exti415);
+        assert_eq!(line_interrupt(15), Interrupt::Exti415);
+    }
+
+    #[test]
+    fn test_dispatch_runs_every_pending_line_in_range() {
+        // Lines 9 and 15 share the EXTI4_15 interrupt, line 2 is outside of it
+        let mut exti = mock_exti(0b1 << 2 | 0b1 << 9 | 0b1 << 15);
+        let callbacks = slots();
+
+        dispatch(&mut exti, &callbacks, 4, 15);
+        assert_eq!(CALLED_FOUR.load(Ordering::SeqCst), 0);
+        assert_eq!(CALLED_NINE.load(Ordering::SeqCst), 1);
+        assert_eq!(CALLED_FIFTEEN.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_dispatch_clears_pending_without_callback() {
+        let mut exti = mock_exti(0b1 << 5);
+        let callbacks = slots();
+
+        dispatch(&mut exti, &callbacks, 4, 15);
+        // PR is cleared by writing 1, so the mock holds the bit that was written
+        assert!(exti.is_pending(5));
+        assert_eq!(CALLED_FOUR.load(Ordering::SeqCst), 0);
+    }
+}
@@ -0,0 +1,103 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! This module defines a description of a single pin's full configuration, which can be
+//! checked against the hardware with `RawGPIO::verify_pin`.
+
+use super::{Mode, Type, Speed, Pull, AlternateFunction};
+
+/// The complete configuration of a single GPIO pin.
+///
+/// Any setting that isn't specified keeps its reset value.
+///
+/// Example Usage:
+/// ```
+///   let config = PinConfig::new(Mode::Alternate)
+///       .with_type(Type::OpenDrain)
+///       .with_pull(Pull::Up)
+///       .with_function(AlternateFunction::One);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PinConfig {
+    mode: Mode,
+    p_type: Type,
+    speed: Speed,
+    pull: Pull,
+    function: AlternateFunction,
+}
+
+impl PinConfig {
+    /// Create a configuration with the specified mode and every other setting at its reset value.
+    pub fn new(mode: Mode) -> PinConfig {
+        PinConfig {
+            mode: mode,
+            p_type: Type::PushPull,
+            speed: Speed::Low,
+            pull: Pull::Neither,
+            function: AlternateFunction::Zero,
+        }
+    }
+
+    /// Set the output type.
+    pub fn with_type(mut self, p_type: Type) -> PinConfig {
+        self.p_type = p_type;
+        self
+    }
+
+    /// Set the pin speed.
+    pub fn with_speed(mut self, speed: Speed) -> PinConfig {
+        self.speed = speed;
+        self
+    }
+
+    /// Set the behavior of the pin when it is not asserted.
+    pub fn with_pull(mut self, pull: Pull) -> PinConfig {
+        self.pull = pull;
+        self
+    }
+
+    /// Set the alternate function.
+    pub fn with_function(mut self, function: AlternateFunction) -> PinConfig {
+        self.function = function;
+        self
+    }
+
+    /// Get the pin mode.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Get the output type.
+    pub fn p_type(&self) -> Type {
+        self.p_type
+    }
+
+    /// Get the pin speed.
+    pub fn speed(&self) -> Speed {
+        self.speed
+    }
+
+    /// Get the behavior of the pin when it is not asserted.
+    pub fn pull(&self) -> Pull {
+        self.pull
+    }
+
+    /// Get the alternate function.
+    pub fn function(&self) -> AlternateFunction {
+        self.function
+    }
+}
@@ -0,0 +1,204 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! This module provides a software debounce for reading mechanical switches.
+
+use super::{Port, Mode};
+
+/// Tracks the debounced state of a noisy input from raw samples.
+///
+/// The state only changes once the input has read the same level for the required number of
+/// samples in a row.
+#[derive(Copy, Clone, Debug)]
+struct Debouncer {
+    samples: u8,
+    count: u8,
+    pressed: bool,
+    just_pressed: bool,
+}
+
+impl Debouncer {
+    fn new(samples: u8) -> Self {
+        Debouncer {
+            samples: samples,
+            count: 0,
+            pressed: false,
+            just_pressed: false,
+        }
+    }
+
+    fn sample(&mut self, pressed: bool) {
+        self.just_pressed = false;
+        if pressed == self.pressed {
+            self.count = 0;
+            return;
+        }
+        self.count += 1;
+        if self.count >= self.samples {
+            self.count = 0;
+            self.pressed = pressed;
+            self.just_pressed = pressed;
+        }
+    }
+}
+
+/// A button or switch on an input pin, debounced in software.
+///
+/// `update` samples the pin, and should be called at a fixed rate, usually from the SysTick
+/// handler or a task woken by it. The sampling window is then the number of samples times the
+/// update period, e.g. 5 samples taken every 1 ms makes a 5 ms window.
+///
+/// Example Usage:
+/// ```
+///   // A button to ground on PA0, using the internal pull-up
+///   let mut port = Port::new(0, Group::A);
+///   port.set_pull(Pull::Up);
+///   let mut button = DebouncedInput::new_active_low(port, 5);
+///   // Every 1 ms:
+///   button.update();
+///   if button.just_pressed() {
+///       // ...
+///   }
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct DebouncedInput {
+    port: Port,
+    active_low: bool,
+    debouncer: Debouncer,
+}
+
+impl DebouncedInput {
+    /// Debounce an input that reads high while pressed. The port is put into input mode, its pull
+    /// setting is left alone.
+    ///
+    /// # Panics
+    ///
+    /// Samples must be greater than 0 or the kernel will panic.
+    pub fn new(port: Port, samples: u8) -> Self {
+        DebouncedInput::with_polarity(port, samples, false)
+    }
+
+    /// Debounce an input that reads low while pressed, like a button to ground with a pull-up.
+    ///
+    /// # Panics
+    ///
+    /// Samples must be greater than 0 or the kernel will panic.
+    pub fn new_active_low(port: Port, samples: u8) -> Self {
+        DebouncedInput::with_polarity(port, samples, true)
+    }
+
+    fn with_polarity(mut port: Port, samples: u8, active_low: bool) -> Self {
+        if samples == 0 {
+            panic!("DebouncedInput::new - samples must be greater than 0!");
+        }
+        port.set_mode(Mode::Input);
+        DebouncedInput {
+            port: port,
+            active_low: active_low,
+            debouncer: Debouncer::new(samples),
+        }
+    }
+
+    /// Take a sample of the pin.
+    pub fn update(&mut self) {
+        let pressed = self.port.read() != self.active_low;
+        self.debouncer.sample(pressed);
+    }
+
+    /// Check if the input is pressed, once debounced.
+    pub fn is_pressed(&self) -> bool {
+        self.debouncer.pressed
+    }
+
+    /// Check if the last call to `update` saw the input become pressed.
+    pub fn just_pressed(&self) -> bool {
+        self.debouncer.just_pressed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debouncer_starts_released() {
+        let debouncer = Debouncer::new(3);
+        assert!(!debouncer.pressed);
+        assert!(!debouncer.just_pressed);
+    }
+
+    #[test]
+    fn test_debouncer_needs_consecutive_samples() {
+        let mut debouncer = Debouncer::new(3);
+
+        debouncer.sample(true);
+        debouncer.sample(true);
+        assert!(!debouncer.pressed);
+
+        debouncer.sample(true);
+        assert!(debouncer.pressed);
+    }
+
+    #[test]
+    fn test_debouncer_bounce_restarts_count() {
+        let mut debouncer = Debouncer::new(3);
+
+        debouncer.sample(true);
+        debouncer.sample(true);
+        debouncer.sample(false);
+        debouncer.sample(true);
+        debouncer.sample(true);
+        assert!(!debouncer.pressed);
+
+        debouncer.sample(true);
+        assert!(debouncer.pressed);
+    }
+
+    #[test]
+    fn test_debouncer_just_pressed_lasts_one_sample() {
+        let mut debouncer = Debouncer::new(2);
+
+        debouncer.sample(true);
+        assert!(!debouncer.just_pressed);
+        debouncer.sample(true);
+        assert!(debouncer.just_pressed);
+        debouncer.sample(true);
+        assert!(!debouncer.just_pressed);
+        assert!(debouncer.pressed);
+    }
+
+    #[test]
+    fn test_debouncer_release_is_not_a_press() {
+        let mut debouncer = Debouncer::new(2);
+        debouncer.sample(true);
+        debouncer.sample(true);
+
+        debouncer.sample(false);
+        debouncer.sample(false);
+        assert!(!debouncer.pressed);
+        assert!(!debouncer.just_pressed);
+    }
+
+    #[test]
+    fn test_debouncer_single_sample() {
+        let mut debouncer = Debouncer::new(1);
+
+        debouncer.sample(true);
+        assert!(debouncer.pressed);
+        assert!(debouncer.just_pressed);
+    }
+}
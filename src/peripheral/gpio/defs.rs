@@ -15,10 +15,12 @@
 * along with this program. If not, see <http://www.gnu.org/licenses/>.
 */
 
-pub const GROUPA_ADDR: *const u32 = 0x4800_0000 as *const _;
-pub const GROUPB_ADDR: *const u32 = 0x4800_0400 as *const _;
-pub const GROUPC_ADDR: *const u32 = 0x4800_0800 as *const _;
-pub const GROUPF_ADDR: *const u32 = 0x4800_1400 as *const _;
+use peripheral::addresses;
+
+pub const GROUPA_ADDR: *const u32 = addresses::GPIOA_ADDR;
+pub const GROUPB_ADDR: *const u32 = addresses::GPIOB_ADDR;
+pub const GROUPC_ADDR: *const u32 = addresses::GPIOC_ADDR;
+pub const GROUPF_ADDR: *const u32 = addresses::GPIOF_ADDR;
 
 pub const OTYPER_OFFSET: u32 = 0x04;
 pub const TYPE_PUSHPULL: u32 = 0b0;
@@ -37,6 +39,9 @@ pub const PUPD_NEITHER: u32 = 0b00;
 pub const PUPD_UP: u32 = 0b01;
 pub const PUPD_DOWN: u32 = 0b10;
 
+pub const IDR_OFFSET: u32 = 0x10;
+
+pub const ODR_OFFSET: u32 = 0x14;
 
 pub const BSRR_OFFSET: u32 = 0x18;
 pub const BSRR_RESET_OFFSET: u8 = 16;
@@ -0,0 +1,103 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! `embedded-hal` digital pin trait implementations for `Port`.
+
+use hal::digital::v2::{OutputPin, InputPin};
+use super::{GPIO, RawGPIO, Port, GpioError};
+
+impl OutputPin for Port {
+    type Error = GpioError;
+
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        write_pin(&mut GPIO::group(self.get_group()), *self, false)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        write_pin(&mut GPIO::group(self.get_group()), *self, true)
+    }
+}
+
+impl InputPin for Port {
+    type Error = GpioError;
+
+    fn is_high(&self) -> Result<bool, Self::Error> {
+        read_pin(&GPIO::group(self.get_group()), *self)
+    }
+
+    fn is_low(&self) -> Result<bool, Self::Error> {
+        read_pin(&GPIO::group(self.get_group()), *self).map(|high| !high)
+    }
+}
+
+// Drive the port through the BSRR of `gpio`, the registers for the port's group.
+fn write_pin(gpio: &mut RawGPIO, port: Port, high: bool) -> Result<(), GpioError> {
+    if high {
+        gpio.set_bit_port(port);
+    }
+    else {
+        gpio.reset_bit_port(port);
+    }
+    Ok(())
+}
+
+// Read the port from the IDR of `gpio`, the registers for the port's group.
+fn read_pin(gpio: &RawGPIO, port: Port) -> Result<bool, GpioError> {
+    Ok(gpio.get_bit_port(port))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use peripheral::raw_word;
+    use super::super::Group;
+    use core::{mem, ptr};
+
+    #[test]
+    fn test_port_implements_digital_traits() {
+        fn assert_output<T: OutputPin<Error=GpioError>>() {}
+        fn assert_input<T: InputPin<Error=GpioError>>() {}
+
+        assert_output::<Port>();
+        assert_input::<Port>();
+    }
+
+    #[test]
+    fn test_set_high_writes_bsrr_set_bit() {
+        let mut gpio: RawGPIO = unsafe { mem::zeroed() };
+
+        assert_eq!(write_pin(&mut gpio, Port::new(5, Group::B), true), Ok(()));
+        assert_eq!(raw_word(&gpio.bsrr), 0b1 << 5);
+    }
+
+    #[test]
+    fn test_set_low_writes_bsrr_reset_bit() {
+        let mut gpio: RawGPIO = unsafe { mem::zeroed() };
+
+        assert_eq!(write_pin(&mut gpio, Port::new(5, Group::B), false), Ok(()));
+        assert_eq!(raw_word(&gpio.bsrr), 0b1 << 21);
+    }
+
+    #[test]
+    fn test_is_high_reads_idr() {
+        let mut gpio: RawGPIO = unsafe { mem::zeroed() };
+        unsafe { ptr::write(&mut gpio.idr as *mut _ as *mut u32, 0b1 << 7); }
+
+        assert_eq!(read_pin(&gpio, Port::new(7, Group::A)), Ok(true));
+        assert_eq!(read_pin(&gpio, Port::new(6, Group::A)), Ok(false));
+    }
+}
@@ -0,0 +1,67 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+#[derive(Copy, Clone, Debug)]
+pub struct IDR(u32);
+
+impl_register!(IDR);
+
+impl IDR {
+    /// Return true if the input for the specified port is high, port must be a value between
+    /// [0..15] or the kernel will panic.
+    pub fn get_bit(&self, port: u8) -> bool {
+        if port > 15 {
+            panic!("IDR::get_bit - specified port must be between [0..15]!");
+        }
+        self.0 & (0b1 << port) != 0
+    }
+
+    /// Return the inputs of all 16 ports, port `n` is bit `n`.
+    pub fn get_port(&self) -> u16 {
+        (self.0 & 0xFFFF) as u16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idr_get_bit_high() {
+        let idr = IDR(0b1 << 12);
+        assert!(idr.get_bit(12));
+    }
+
+    #[test]
+    fn test_idr_get_bit_low() {
+        let idr = IDR(!(0b1 << 12));
+        assert!(!idr.get_bit(12));
+    }
+
+    #[test]
+    fn test_idr_get_port_masks_reserved_bits() {
+        let idr = IDR(0xABCD_1234);
+        assert_eq!(idr.get_port(), 0x1234);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_idr_get_bit_panics_when_port_is_out_of_bounds() {
+        let idr = IDR(0);
+        idr.get_bit(16);
+    }
+}
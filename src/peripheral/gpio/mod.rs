@@ -21,10 +21,19 @@ mod port;
 mod moder;
 mod otyper;
 mod bsrr;
+mod idr;
+mod odr;
 mod ospeedr;
 mod pupdr;
 mod afr;
+mod preset;
+mod config;
+mod callback;
+mod debounce;
+mod bus;
 mod defs;
+#[cfg(feature="embedded-hal")]
+mod hal;
 
 use core::ops::{Deref, DerefMut};
 use volatile::Volatile;
@@ -37,17 +46,24 @@ pub use self::otyper::Type;
 pub use self::ospeedr::Speed;
 pub use self::pupdr::Pull;
 pub use self::afr::AlternateFunction;
+pub use self::preset::PinPreset;
+pub use self::config::PinConfig;
+pub use self::debounce::DebouncedInput;
+pub use self::bus::PinBus;
+pub use self::callback::{on_interrupt, dispatch_interrupt};
 
 use self::moder::MODER;
 use self::otyper::OTYPER;
 use self::ospeedr::OSPEEDR;
 use self::pupdr::PUPDR;
 use self::bsrr::BSRR;
+use self::idr::IDR;
+use self::odr::ODR;
 use self::afr::{AFRL, AFRH};
 
 /// An IO group containing up to 16 pins. For some reason, the datasheet shows the memory
 /// for groups D and E as reserved, so for now they are left out.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Group {
     /// GPIO Group A
     A,
@@ -59,6 +75,71 @@ pub enum Group {
     F,
 }
 
+static GROUPS: [Group; 4] = [Group::A, Group::B, Group::C, Group::F];
+
+impl Group {
+    /// Get all of the available GPIO groups.
+    pub fn all() -> &'static [Group] {
+        &GROUPS
+    }
+
+    /// Get the base address of the memory mapped registers for this group.
+    pub fn base_address(self) -> u32 {
+        let addr = match self {
+            Group::A => GROUPA_ADDR,
+            Group::B => GROUPB_ADDR,
+            Group::C => GROUPC_ADDR,
+            Group::F => GROUPF_ADDR,
+        };
+        addr as u32
+    }
+
+    /// Get the number of pins the group has on the largest STM32F04x package. Groups A and B have
+    /// all 16, group C only has PC13-PC15 and group F only has PF0, PF1 and PF11.
+    pub fn pin_count(self) -> u8 {
+        match self {
+            Group::A | Group::B => 16,
+            Group::C | Group::F => 3,
+        }
+    }
+
+    /// Check if the pin exists in the group, see `pin_count`. Use this to skip pins that aren't
+    /// there when configuring a whole group.
+    pub fn has_pin(self, port: u8) -> bool {
+        match self {
+            Group::A | Group::B => port <= 15,
+            Group::C => port >= 13 && port <= 15,
+            Group::F => port == 0 || port == 1 || port == 11,
+        }
+    }
+
+    /// Check if the pins in the group can be driven at the speed. PC13-PC15 are supplied through
+    /// the backup domain power switch, so they are limited to 2 MHz and only support
+    /// `Speed::Low`. The other groups support every speed.
+    pub fn supports_speed(self, speed: Speed) -> bool {
+        match self {
+            Group::C => speed == Speed::Low,
+            Group::A | Group::B | Group::F => true,
+        }
+    }
+}
+
+/// Errors that can be reported by GPIO operations. Reading and writing a `Port` never fails.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GpioError {
+    /// A debug pin was going to be reconfigured without acknowledging that debug access will be
+    /// lost.
+    DebugNotAcknowledged,
+    /// The pin isn't one of the debug pins.
+    NotDebugPin,
+}
+
+/// An output whose state can be flipped without the caller having to track it.
+pub trait Toggle {
+    /// Toggle the output, setting it low if it was high and high if it was low.
+    fn toggle(&mut self);
+}
+
 /// A GPIO contains the base address for a memory mapped GPIO group associated with it.
 #[derive(Copy, Clone, Debug)]
 #[repr(C)]
@@ -68,8 +149,8 @@ pub struct RawGPIO {
     otyper: OTYPER,
     ospeedr: OSPEEDR,
     pupdr: PUPDR,
-    idr: u32,
-    odr: u32,
+    idr: IDR,
+    odr: ODR,
     bsrr: BSRR,
     lckr: u32,
     afrl: AFRL,
@@ -77,6 +158,19 @@ pub struct RawGPIO {
     brr: u32,
 }
 
+/// A copy of the configuration and output registers for a GPIO group, taken with
+/// `RawGPIO::snapshot` and written back with `RawGPIO::restore`.
+#[derive(Copy, Clone, Debug)]
+pub struct GpioSnapshot {
+    moder: MODER,
+    otyper: OTYPER,
+    ospeedr: OSPEEDR,
+    pupdr: PUPDR,
+    odr: ODR,
+    afrl: AFRL,
+    afrh: AFRH,
+}
+
 /// Creates struct for accessing the GPIO groups.
 ///
 /// Has a RawGPIO data member in order to access each register for the
@@ -86,12 +180,7 @@ pub struct GPIO(Volatile<RawGPIO>);
 
 impl GPIO {
     fn group(group: Group) -> GPIO {
-        match group {
-            Group::A => GPIO::new(GROUPA_ADDR),
-            Group::B => GPIO::new(GROUPB_ADDR),
-            Group::C => GPIO::new(GROUPC_ADDR),
-            Group::F => GPIO::new(GROUPF_ADDR),
-        }
+        GPIO::new(group.base_address() as *const u32)
     }
 
     fn new(mem_addr: *const u32) -> GPIO {
@@ -194,6 +283,24 @@ impl RawGPIO {
         self.bsrr.reset(port);
     }
 
+    /// Reads the input of the GPIO pin at specified port, returning true if it is high.
+    ///
+    /// # Panics
+    ///
+    /// Port must be a value between [0..15] or the kernel will panic.
+    fn get_bit(&self, port: u8) -> bool {
+        self.idr.get_bit(port)
+    }
+
+    /// Toggles the output of the GPIO pin at specified port.
+    ///
+    /// # Panics
+    ///
+    /// Port must be a value between [0..15] or the kernel will panic.
+    fn toggle_bit(&mut self, port: u8) {
+        self.odr.toggle(port);
+    }
+
     /// Sets the port speed for the GPIO pin.
     ///
     /// # Panics
@@ -252,7 +359,634 @@ impl RawGPIO {
         match port {
             0...7 => self.afrl.get_function(port),
             8...15 => self.afrh.get_function(port),
-            _ => panic!("AFRL/AFRH::set_function - specified port must be between [0..15]!"),
+            _ => panic!("AFRL/AFRH::get_function - specified port must be between [0..15]!"),
+        }
+    }
+
+    /// Set the pull of all 16 ports in the group at once, port `n` takes its pull from
+    /// `pulls[n]`. The PUPDR is written once, rather than read and written for every port.
+    pub fn set_port_pulls(&mut self, pulls: [Pull; 16]) {
+        self.pupdr.set_all(&pulls);
+    }
+
+    /// Read the inputs of all 16 ports in the group at once, port `n` is bit `n`.
+    pub fn read_port(&self) -> u16 {
+        self.idr.get_port()
+    }
+
+    /// Drive the outputs of all 16 ports in the group at once, port `n` is set high if bit `n` of
+    /// `value` is set and low otherwise. This is a single BSRR write, so all of the outputs
+    /// change together.
+    pub fn write_port(&mut self, value: u16) {
+        self.bsrr.write_port(value);
+    }
+
+    /// Drive only the ports whose bit is set in `mask`, port `n` is set high if bit `n` of
+    /// `value` is set and low otherwise. The other ports are left as they are, and the selected
+    /// ports change together with a single BSRR write.
+    pub fn write_port_masked(&mut self, value: u16, mask: u16) {
+        self.bsrr.write_masked(value, mask);
+    }
+
+    /// Set the mode of every port in the group with a single write, `modes[n]` is the mode for
+    /// port `n`.
+    pub fn set_port_modes(&mut self, modes: [Mode; 16]) {
+        let mut moder = self.moder;
+        moder.set_all_modes(modes);
+        self.moder = moder;
+    }
+
+    /// Put every port in the group into analog mode, except the ports whose bit is set in
+    /// `except`. Analog mode has the lowest leakage and disables the input Schmitt trigger, so
+    /// this is the recommended state for unused pins before entering Stop or Standby mode.
+    ///
+    /// PA13 and PA14 are the SWD pins, debug access is lost if they're put into analog mode
+    /// without being in the except mask.
+    pub fn set_all_analog(&mut self, except: u16) {
+        let mut moder = self.moder;
+        moder.set_analog_except(except);
+        self.moder = moder;
+    }
+
+    /// Configure a pin for use as an I2C SCL or SDA line, with the specified alternate function.
+    ///
+    /// The pin is set to open-drain with the internal pull-up enabled, and the alternate function
+    /// is selected before the pin is switched into alternate function mode, so the pin never
+    /// drives the bus while it's being configured.
+    ///
+    /// # Panics
+    ///
+    /// Port must be a value between [0..15] or the kernel will panic.
+    pub fn configure_i2c_pin(&mut self, port: u8, af: AlternateFunction) {
+        self.set_type(Type::OpenDrain, port);
+        self.set_pull(Pull::Up, port);
+        self.set_function(af, port);
+        self.set_mode(Mode::Alternate, port);
+    }
+
+    /// Configure a pin with the usual settings for a peripheral signal, using the specified
+    /// alternate function.
+    ///
+    /// As with `configure_i2c_pin`, the pin is only switched into alternate function mode once
+    /// everything else is set up.
+    ///
+    /// # Panics
+    ///
+    /// Port must be a value between [0..15] or the kernel will panic.
+    pub fn apply_preset(&mut self, port: u8, preset: PinPreset, af: AlternateFunction) {
+        let (p_type, speed, pull) = preset.settings();
+        self.set_type(p_type, port);
+        self.set_speed(speed, port);
+        self.set_pull(pull, port);
+        self.set_function(af, port);
+        self.set_mode(Mode::Alternate, port);
+    }
+
+    /// Check that the hardware configuration of a pin matches `expected`, returning true if the
+    /// mode, type, speed, pull and alternate function all read back as expected.
+    ///
+    /// This is a cheap self-test for bring-up, e.g. a mismatch usually means the group's clock
+    /// was never enabled so the configuration writes were dropped.
+    ///
+    /// # Panics
+    ///
+    /// Port must be a value between [0..15] or the kernel will panic.
+    pub fn verify_pin(&self, port: u8, expected: &PinConfig) -> bool {
+        self.get_mode(port) == expected.mode() &&
+            self.get_type(port) == expected.p_type() &&
+            self.get_speed(port) == expected.speed() &&
+            self.get_pull(port) == expected.pull() &&
+            self.get_function(port) == expected.function()
+    }
+
+    /// Take a snapshot of the configuration and output registers for the group.
+    pub fn snapshot(&self) -> GpioSnapshot {
+        GpioSnapshot {
+            moder: self.moder,
+            otyper: self.otyper,
+            ospeedr: self.ospeedr,
+            pupdr: self.pupdr,
+            odr: self.odr,
+            afrl: self.afrl,
+            afrh: self.afrh,
+        }
+    }
+
+    /// Write a snapshot back to the group's registers.
+    ///
+    /// The output levels and pin settings are restored before the modes, so a pin never drives
+    /// its old output with the wrong level or type while it's being restored.
+    pub fn restore(&mut self, snapshot: &GpioSnapshot) {
+        self.odr = snapshot.odr;
+        self.otyper = snapshot.otyper;
+        self.ospeedr = snapshot.ospeedr;
+        self.pupdr = snapshot.pupdr;
+        self.afrl = snapshot.afrl;
+        self.afrh = snapshot.afrh;
+        self.moder = snapshot.moder;
+    }
+
+    // The methods below take a `Port` rather than a raw index. A `Port` can only be constructed
+    // with an index in the range [0..15], so these never panic on a bad index.
+
+    /// Set the mode for the specified port.
+    pub fn set_mode_port(&mut self, mode: Mode, port: Port) {
+        self.set_mode(mode, port.get_index());
+    }
+
+    /// Gets the mode for the specified port.
+    pub fn get_mode_port(&self, port: Port) -> Mode {
+        self.get_mode(port.get_index())
+    }
+
+    /// Sets the type for the specified port.
+    pub fn set_type_port(&mut self, p_type: Type, port: Port) {
+        self.set_type(p_type, port.get_index());
+    }
+
+    /// Gets the type for the specified port.
+    pub fn get_type_port(&self, port: Port) -> Type {
+        self.get_type(port.get_index())
+    }
+
+    /// Turns on GPIO pin at specified port.
+    pub fn set_bit_port(&mut self, port: Port) {
+        self.set_bit(port.get_index());
+    }
+
+    /// Resets bit at specified port.
+    pub fn reset_bit_port(&mut self, port: Port) {
+        self.reset_bit(port.get_index());
+    }
+
+    /// Reads the input of the GPIO pin at specified port, returning true if it is high.
+    pub fn get_bit_port(&self, port: Port) -> bool {
+        self.get_bit(port.get_index())
+    }
+
+    /// Toggles the output of the GPIO pin at specified port.
+    pub fn toggle_bit_port(&mut self, port: Port) {
+        self.toggle_bit(port.get_index());
+    }
+
+    /// Sets the port speed for the GPIO pin.
+    pub fn set_speed_port(&mut self, speed: Speed, port: Port) {
+        self.set_speed(speed, port.get_index());
+    }
+
+    /// Get the current port speed.
+    pub fn get_speed_port(&self, port: Port) -> Speed {
+        self.get_speed(port.get_index())
+    }
+
+    /// Set behavior of GPIO pin when it is not asserted.
+    pub fn set_pull_port(&mut self, pull: Pull, port: Port) {
+        self.set_pull(pull, port.get_index());
+    }
+
+    /// Get currently defined behavior of GPIO pin when not asserted.
+    pub fn get_pull_port(&self, port: Port) -> Pull {
+        self.get_pull(port.get_index())
+    }
+
+    /// Set the GPIO function type.
+    pub fn set_function_port(&mut self, function: AlternateFunction, port: Port) {
+        self.set_function(function, port.get_index());
+    }
+
+    /// Get the GPIO function type.
+    pub fn get_function_port(&self, port: Port) -> AlternateFunction {
+        self.get_function(port.get_index())
+    }
+}
+
+/// Configure a pair of pins as Usart TX and RX, e.g. `((Group::A, 2), (Group::A, 3))` for
+/// Usart2, using the specified alternate function.
+///
+/// The clocks for the pins' groups are enabled, then both pins are set up as push-pull at high
+/// speed with a pull-up so the lines idle high, see `PinPreset::UsartTx` and `PinPreset::UsartRx`.
+///
+/// # Panics
+///
+/// The kernel will panic if either pin doesn't exist in its group.
+pub fn configure_uart_pins(tx: (Group, u8), rx: (Group, u8), af: AlternateFunction) {
+    for &(group, port) in &[tx, rx] {
+        if !group.has_pin(port) {
+            panic!("gpio::configure_uart_pins - pin does not exist in the group!");
+        }
+    }
+
+    GPIO::enable(tx.0);
+    configure_uart_pins_in(&mut GPIO::group(tx.0), tx.0, tx, rx, af);
+    if rx.0 != tx.0 {
+        GPIO::enable(rx.0);
+        configure_uart_pins_in(&mut GPIO::group(rx.0), rx.0, tx, rx, af);
+    }
+}
+
+// Configure whichever of the TX and RX pins are in `group`, `gpio` being that group's registers.
+fn configure_uart_pins_in(gpio: &mut RawGPIO, group: Group, tx: (Group, u8), rx: (Group, u8),
+                          af: AlternateFunction) {
+    if tx.0 == group {
+        gpio.apply_preset(tx.1, PinPreset::UsartTx, af);
+    }
+    if rx.0 == group {
+        gpio.apply_preset(rx.1, PinPreset::UsartRx, af);
+    }
+}
+
+// Reconfigure a debug pin, `gpio` being the registers for the port's group. See
+// `Port::reclaim_debug_pin`.
+fn reclaim_debug_pin_in(gpio: &mut RawGPIO, port: Port, mode: Mode, acknowledge_debug_loss: bool)
+    -> Result<(), GpioError> {
+    if !is_debug_pin(port) {
+        return Err(GpioError::NotDebugPin);
+    }
+    if !acknowledge_debug_loss {
+        return Err(GpioError::DebugNotAcknowledged);
+    }
+    gpio.set_mode_port(mode, port);
+    Ok(())
+}
+
+fn is_debug_pin(port: Port) -> bool {
+    match (port.get_group(), port.get_index()) {
+        (Group::A, 13) | (Group::A, 14) | (Group::A, 15) => true,
+        (Group::B, 3) | (Group::B, 4) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use peripheral::raw_word;
+
+    #[test]
+    fn test_group_pin_count() {
+        assert_eq!(Group::A.pin_count(), 16);
+        assert_eq!(Group::B.pin_count(), 16);
+        assert_eq!(Group::C.pin_count(), 3);
+        assert_eq!(Group::F.pin_count(), 3);
+    }
+
+    #[test]
+    fn test_group_has_pin_matches_pin_count() {
+        for &group in Group::all() {
+            let count = (0..16).filter(|&port| group.has_pin(port)).count();
+            assert_eq!(count, group.pin_count() as usize);
+        }
+    }
+
+    #[test]
+    fn test_group_has_pin() {
+        assert!(Group::A.has_pin(15));
+        assert!(!Group::A.has_pin(16));
+        assert!(Group::C.has_pin(13));
+        assert!(!Group::C.has_pin(12));
+        assert!(Group::F.has_pin(11));
+        assert!(!Group::F.has_pin(2));
+    }
+
+    #[test]
+    fn test_group_supports_speed() {
+        for &speed in &[Speed::Low, Speed::Medium, Speed::High] {
+            assert!(Group::A.supports_speed(speed));
+            assert!(Group::B.supports_speed(speed));
+            assert!(Group::F.supports_speed(speed));
+        }
+        assert!(Group::C.supports_speed(Speed::Low));
+        assert!(!Group::C.supports_speed(Speed::Medium));
+        assert!(!Group::C.supports_speed(Speed::High));
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let mut gpio: RawGPIO = unsafe { ::core::mem::zeroed() };
+        gpio.set_mode(Mode::Output, 3);
+        gpio.set_type(Type::OpenDrain, 3);
+        gpio.set_speed(Speed::High, 3);
+        gpio.set_pull(Pull::Down, 9);
+        gpio.set_function(AlternateFunction::Two, 1);
+        gpio.set_function(AlternateFunction::Five, 12);
+        gpio.toggle_bit(3);
+        let snapshot = gpio.snapshot();
+
+        let mut other: RawGPIO = unsafe { ::core::mem::zeroed() };
+        other.set_mode(Mode::Analog, 0);
+        other.set_pull(Pull::Up, 4);
+        other.restore(&snapshot);
+
+        assert_eq!(raw_word(&other.moder), raw_word(&gpio.moder));
+        assert_eq!(raw_word(&other.otyper), raw_word(&gpio.otyper));
+        assert_eq!(raw_word(&other.ospeedr), raw_word(&gpio.ospeedr));
+        assert_eq!(raw_word(&other.pupdr), raw_word(&gpio.pupdr));
+        assert_eq!(raw_word(&other.odr), raw_word(&gpio.odr));
+        assert_eq!(raw_word(&other.afrl), raw_word(&gpio.afrl));
+        assert_eq!(raw_word(&other.afrh), raw_word(&gpio.afrh));
+        assert_eq!(other.get_mode(3), Mode::Output);
+        assert_eq!(other.get_mode(0), Mode::Input);
+        assert_eq!(other.get_pull(4), Pull::Neither);
+    }
+
+    #[test]
+    fn test_snapshot_is_unaffected_by_later_changes() {
+        let mut gpio: RawGPIO = unsafe { ::core::mem::zeroed() };
+        gpio.set_mode(Mode::Output, 5);
+        let snapshot = gpio.snapshot();
+
+        gpio.set_mode(Mode::Analog, 5);
+        gpio.restore(&snapshot);
+        assert_eq!(gpio.get_mode(5), Mode::Output);
+    }
+
+    fn check_preset(preset: PinPreset, p_type: Type, speed: Speed, pull: Pull) {
+        let mut gpio: RawGPIO = unsafe { ::core::mem::zeroed() };
+        gpio.apply_preset(10, preset, AlternateFunction::Two);
+
+        assert_eq!(gpio.get_mode(10), Mode::Alternate);
+        assert_eq!(gpio.get_type(10), p_type);
+        assert_eq!(gpio.get_speed(10), speed);
+        assert_eq!(gpio.get_pull(10), pull);
+        assert_eq!(raw_word(&gpio.afrh), 0b0010 << 8);
+        assert_eq!(raw_word(&gpio.afrl), 0);
+    }
+
+    #[test]
+    fn test_apply_preset_usart() {
+        check_preset(PinPreset::UsartTx, Type::PushPull, Speed::High, Pull::Up);
+        check_preset(PinPreset::UsartRx, Type::PushPull, Speed::High, Pull::Up);
+    }
+
+    #[test]
+    fn test_apply_preset_spi() {
+        check_preset(PinPreset::SpiSck, Type::PushPull, Speed::High, Pull::Neither);
+        check_preset(PinPreset::SpiMosi, Type::PushPull, Speed::High, Pull::Neither);
+        check_preset(PinPreset::SpiMiso, Type::PushPull, Speed::High, Pull::Neither);
+    }
+
+    #[test]
+    fn test_apply_preset_i2c() {
+        check_preset(PinPreset::I2cScl, Type::OpenDrain, Speed::Medium, Pull::Up);
+        check_preset(PinPreset::I2cSda, Type::OpenDrain, Speed::Medium, Pull::Up);
+    }
+
+    #[test]
+    fn test_apply_preset_pwm() {
+        check_preset(PinPreset::PwmOut, Type::PushPull, Speed::Medium, Pull::Neither);
+    }
+
+    #[test]
+    fn test_apply_preset_leaves_other_ports() {
+        let mut gpio: RawGPIO = unsafe { ::core::mem::zeroed() };
+        gpio.apply_preset(2, PinPreset::UsartTx, AlternateFunction::One);
+
+        assert_eq!(raw_word(&gpio.moder), 0b10 << 4);
+        assert_eq!(raw_word(&gpio.pupdr), 0b01 << 4);
+        assert_eq!(raw_word(&gpio.afrl), 0b0001 << 8);
+    }
+
+    #[test]
+    fn test_read_port() {
+        let mut gpio: RawGPIO = unsafe { ::core::mem::zeroed() };
+        unsafe { *(&mut gpio.idr as *mut IDR as *mut u32) = 0x0001_8421; }
+
+        assert_eq!(gpio.read_port(), 0x8421);
+    }
+
+    #[test]
+    fn test_write_port() {
+        let mut gpio: RawGPIO = unsafe { ::core::mem::zeroed() };
+
+        gpio.write_port(0xA5A5);
+        assert_eq!(raw_word(&gpio.bsrr), 0x5A5A_A5A5);
+    }
+
+    #[test]
+    fn test_set_port_modes() {
+        let mut gpio: RawGPIO = unsafe { ::core::mem::zeroed() };
+        gpio.set_mode(Mode::Analog, 4);
+        let mut modes = [Mode::Output; 16];
+        modes[3] = Mode::Alternate;
+
+        gpio.set_port_modes(modes);
+        assert_eq!(gpio.get_mode(3), Mode::Alternate);
+        assert_eq!(gpio.get_mode(4), Mode::Output);
+        assert_eq!(raw_word(&gpio.moder), 0x5555_5555 & !(0b11 << 6) | 0b10 << 6);
+    }
+
+    #[test]
+    fn test_set_all_analog_keeps_excepted_ports() {
+        let mut gpio: RawGPIO = unsafe { ::core::mem::zeroed() };
+        gpio.set_mode(Mode::Output, 2);
+        gpio.set_mode(Mode::Alternate, 13);
+        gpio.set_mode(Mode::Alternate, 14);
+
+        gpio.set_all_analog(0b1 << 2 | 0b1 << 13 | 0b1 << 14);
+        assert_eq!(raw_word(&gpio.moder), 0xC3FF_FFCF | 0b01 << 4 | 0b10 << 26 | 0b10 << 28);
+        assert_eq!(gpio.get_mode(0), Mode::Analog);
+        assert_eq!(gpio.get_mode(2), Mode::Output);
+        assert_eq!(gpio.get_mode(14), Mode::Alternate);
+    }
+
+    #[test]
+    fn test_configure_i2c_pin() {
+        let mut gpio: RawGPIO = unsafe { ::core::mem::zeroed() };
+
+        gpio.configure_i2c_pin(6, AlternateFunction::One);
+        assert_eq!(gpio.get_mode(6), Mode::Alternate);
+        assert_eq!(gpio.get_type(6), Type::OpenDrain);
+        assert_eq!(gpio.get_pull(6), Pull::Up);
+        assert_eq!(raw_word(&gpio.afrl), 0b0001 << 24);
+    }
+
+    #[test]
+    fn test_configure_i2c_pin_leaves_other_pins_alone() {
+        let mut gpio: RawGPIO = unsafe { ::core::mem::zeroed() };
+
+        gpio.configure_i2c_pin(7, AlternateFunction::One);
+        assert_eq!(gpio.get_mode(6), Mode::Input);
+        assert_eq!(gpio.get_type(6), Type::PushPull);
+        assert_eq!(gpio.get_pull(6), Pull::Neither);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_configure_i2c_pin_port_greater_than_15_panics() {
+        let mut gpio: RawGPIO = unsafe { ::core::mem::zeroed() };
+
+        gpio.configure_i2c_pin(16, AlternateFunction::One);
+    }
+
+    #[test]
+    fn test_verify_pin_matching_config() {
+        let mut gpio: RawGPIO = unsafe { ::core::mem::zeroed() };
+        gpio.apply_preset(9, PinPreset::I2cScl, AlternateFunction::Four);
+
+        let expected = PinConfig::new(Mode::Alternate)
+            .with_type(Type::OpenDrain)
+            .with_speed(Speed::Medium)
+            .with_pull(Pull::Up)
+            .with_function(AlternateFunction::Four);
+        assert!(gpio.verify_pin(9, &expected));
+    }
+
+    #[test]
+    fn test_verify_pin_reset_config() {
+        let gpio: RawGPIO = unsafe { ::core::mem::zeroed() };
+
+        assert!(gpio.verify_pin(0, &PinConfig::new(Mode::Input)));
+        assert!(gpio.verify_pin(15, &PinConfig::new(Mode::Input)));
+    }
+
+    #[test]
+    fn test_verify_pin_mismatching_config() {
+        let mut gpio: RawGPIO = unsafe { ::core::mem::zeroed() };
+        gpio.configure_i2c_pin(3, AlternateFunction::One);
+
+        let expected = PinConfig::new(Mode::Alternate)
+            .with_type(Type::OpenDrain)
+            .with_pull(Pull::Up)
+            .with_function(AlternateFunction::One);
+        assert!(gpio.verify_pin(3, &expected));
+        assert!(!gpio.verify_pin(3, &expected.with_function(AlternateFunction::Two)));
+        assert!(!gpio.verify_pin(3, &expected.with_pull(Pull::Down)));
+        assert!(!gpio.verify_pin(3, &expected.with_speed(Speed::High)));
+        assert!(!gpio.verify_pin(3, &expected.with_type(Type::PushPull)));
+        assert!(!gpio.verify_pin(3, &PinConfig::new(Mode::Output)));
+        assert!(!gpio.verify_pin(2, &expected));
+    }
+
+    #[test]
+    fn test_reclaim_debug_pin_without_acknowledgment_is_rejected() {
+        let mut gpio: RawGPIO = unsafe { ::core::mem::zeroed() };
+        gpio.set_mode(Mode::Alternate, 13);
+
+        let result = reclaim_debug_pin_in(&mut gpio, Port::new(13, Group::A), Mode::Output, false);
+        assert_eq!(result, Err(GpioError::DebugNotAcknowledged));
+        assert_eq!(gpio.get_mode(13), Mode::Alternate);
+    }
+
+    #[test]
+    fn test_reclaim_debug_pin_with_acknowledgment() {
+        let mut gpio: RawGPIO = unsafe { ::core::mem::zeroed() };
+        gpio.set_mode(Mode::Alternate, 14);
+
+        let result = reclaim_debug_pin_in(&mut gpio, Port::new(14, Group::A), Mode::Output, true);
+        assert_eq!(result, Ok(()));
+        assert_eq!(gpio.get_mode(14), Mode::Output);
+    }
+
+    #[test]
+    fn test_reclaim_debug_pin_rejects_other_pins() {
+        let mut gpio: RawGPIO = unsafe { ::core::mem::zeroed() };
+
+        // PB13 isn't a debug pin even though PA13 is
+        let result = reclaim_debug_pin_in(&mut gpio, Port::new(13, Group::B), Mode::Output, true);
+        assert_eq!(result, Err(GpioError::NotDebugPin));
+        assert_eq!(gpio.get_mode(13), Mode::Input);
+    }
+
+    #[test]
+    fn test_is_debug_pin() {
+        assert!(is_debug_pin(Port::new(15, Group::A)));
+        assert!(is_debug_pin(Port::new(3, Group::B)));
+        assert!(is_debug_pin(Port::new(4, Group::B)));
+        assert!(!is_debug_pin(Port::new(3, Group::A)));
+        assert!(!is_debug_pin(Port::new(14, Group::C)));
+    }
+
+    #[test]
+    fn test_group_all_returns_available_groups() {
+        assert_eq!(Group::all(), &[Group::A, Group::B, Group::C, Group::F]);
+    }
+
+    fn check_uart_pin(gpio: &RawGPIO, port: u8) {
+        assert_eq!(gpio.get_mode(port), Mode::Alternate);
+        assert_eq!(gpio.get_type(port), Type::PushPull);
+        assert_eq!(gpio.get_speed(port), Speed::High);
+        assert_eq!(gpio.get_pull(port), Pull::Up);
+        assert_eq!(gpio.get_function(port), AlternateFunction::One);
+    }
+
+    #[test]
+    fn test_configure_uart_pins_same_group() {
+        let mut gpio: RawGPIO = unsafe { ::core::mem::zeroed() };
+
+        configure_uart_pins_in(&mut gpio, Group::A, (Group::A, 2), (Group::A, 15),
+                               AlternateFunction::One);
+        check_uart_pin(&gpio, 2);
+        check_uart_pin(&gpio, 15);
+        assert_eq!(raw_word(&gpio.moder), 0b10 << 4 | 0b10 << 30);
+    }
+
+    #[test]
+    fn test_configure_uart_pins_split_groups() {
+        let mut gpio_a: RawGPIO = unsafe { ::core::mem::zeroed() };
+        let mut gpio_b: RawGPIO = unsafe { ::core::mem::zeroed() };
+        let (tx, rx) = ((Group::A, 9), (Group::B, 7));
+
+        configure_uart_pins_in(&mut gpio_a, Group::A, tx, rx, AlternateFunction::One);
+        configure_uart_pins_in(&mut gpio_b, Group::B, tx, rx, AlternateFunction::One);
+        check_uart_pin(&gpio_a, 9);
+        check_uart_pin(&gpio_b, 7);
+        assert_eq!(raw_word(&gpio_a.moder), 0b10 << 18);
+        assert_eq!(raw_word(&gpio_b.moder), 0b10 << 14);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_configure_uart_pins_missing_pin_panics() {
+        configure_uart_pins((Group::A, 2), (Group::F, 5), AlternateFunction::One);
+    }
+
+    #[test]
+    fn test_set_port_pulls_matches_single_port_pulls() {
+        let mut pulls = [Pull::Neither; 16];
+        pulls[2] = Pull::Up;
+        pulls[9] = Pull::Down;
+        pulls[14] = Pull::Up;
+
+        let mut bulk: RawGPIO = unsafe { ::core::mem::zeroed() };
+        bulk.set_port_pulls(pulls);
+
+        let mut single: RawGPIO = unsafe { ::core::mem::zeroed() };
+        for (port, &pull) in pulls.iter().enumerate() {
+            single.set_pull(pull, port as u8);
+        }
+        assert_eq!(raw_word(&bulk.pupdr), raw_word(&single.pupdr));
+        assert_eq!(raw_word(&bulk.pupdr), 0b01 << 4 | 0b10 << 18 | 0b01 << 28);
+    }
+
+    #[test]
+    fn test_function_split_at_port_8() {
+        let mut gpio: RawGPIO = unsafe { ::core::mem::zeroed() };
+
+        gpio.set_function(AlternateFunction::Five, 7);
+        gpio.set_function(AlternateFunction::Two, 8);
+
+        // Port 7 is the top nibble of AFRL and port 8 the bottom nibble of AFRH.
+        assert_eq!(raw_word(&gpio.afrl), 0x5 << 28);
+        assert_eq!(raw_word(&gpio.afrh), 0x2);
+        assert_eq!(gpio.get_function(7), AlternateFunction::Five);
+        assert_eq!(gpio.get_function(8), AlternateFunction::Two);
+    }
+
+    #[test]
+    fn test_group_base_address() {
+        assert_eq!(Group::A.base_address(), 0x4800_0000);
+        assert_eq!(Group::B.base_address(), 0x4800_0400);
+        assert_eq!(Group::C.base_address(), 0x4800_0800);
+        assert_eq!(Group::F.base_address(), 0x4800_1400);
+    }
+
+    #[test]
+    fn test_group_base_addresses_are_unique() {
+        let groups = Group::all();
+        for (i, a) in groups.iter().enumerate() {
+            for b in &groups[i + 1..] {
+                assert!(a.base_address() != b.base_address());
+            }
         }
     }
 }
@@ -43,6 +43,23 @@ impl Field for Mode {
 }
 
 impl Mode {
+    /// Get the raw 2-bit register encoding of this value.
+    pub fn as_bits(self) -> u8 {
+        self.mask() as u8
+    }
+
+    /// Convert a raw 2-bit register encoding back into a value, returns `None` if the bits are
+    /// not a valid encoding.
+    pub fn from_bits(bits: u8) -> Option<Self> {
+        match bits as u32 {
+            MODE_INPUT => Some(Mode::Input),
+            MODE_OUTPUT => Some(Mode::Output),
+            MODE_ALTERNATE => Some(Mode::Alternate),
+            MODE_ANALOG => Some(Mode::Analog),
+            _ => None,
+        }
+    }
+
     fn from_mask(mask: u32) -> Self {
         match mask {
             MODE_INPUT => Mode::Input,
@@ -57,6 +74,8 @@ impl Mode {
 #[derive(Copy, Clone, Debug)]
 pub struct MODER(u32);
 
+impl_register!(MODER);
+
 impl MODER {
     pub fn set_mode(&mut self, mode: Mode, port: u8) {
         if port > 15 {
@@ -68,6 +87,28 @@ impl MODER {
         self.0 |= mask << (port * 2);
     }
 
+    /// Set the mode for every port in the group at once, `modes[n]` is the mode for port `n`.
+    pub fn set_all_modes(&mut self, modes: [Mode; 16]) {
+        let mut value = 0;
+        for (port, mode) in modes.iter().enumerate() {
+            value |= mode.mask() << (port * 2);
+        }
+        self.0 = value;
+    }
+
+    /// Set every port whose bit isn't set in `except` to analog mode, the other ports keep their
+    /// current mode.
+    pub fn set_analog_except(&mut self, except: u16) {
+        let mut mask = 0;
+        for port in 0..16 {
+            if except & (0b1 << port) == 0 {
+                mask |= MODE_MASK << (port * 2);
+            }
+        }
+        // Analog is 0b11, so the selected ports just need all of their bits set
+        self.0 |= mask;
+    }
+
     /// Get the current mode for the specified port, port must be a value between [0..15] or
     /// the kernel will panic.
     pub fn get_mode(&self, port: u8) -> Mode {
@@ -85,6 +126,22 @@ impl MODER {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_moder_set_analog_except() {
+        let mut moder = MODER(0b01 << 2 | 0b10 << 30);
+
+        moder.set_analog_except(0b1 << 1 | 0b1 << 15);
+        assert_eq!(moder.0, 0x3FFF_FFF3 | 0b01 << 2 | 0b10 << 30);
+    }
+
+    #[test]
+    fn test_moder_set_analog_except_nothing() {
+        let mut moder = MODER(0x1234_5678);
+
+        moder.set_analog_except(0);
+        assert_eq!(moder.0, 0xFFFF_FFFF);
+    }
+
     #[test]
     fn test_moder_set_mode() {
         let mut moder = MODER(0);
@@ -112,6 +169,35 @@ mod tests {
         moder.set_mode(Mode::Analog, 16);
     }
 
+    #[test]
+    fn test_moder_set_all_modes() {
+        let mut moder = MODER(0xFFFF_FFFF);
+        let mut modes = [Mode::Input; 16];
+        modes[0] = Mode::Output;
+        modes[1] = Mode::Alternate;
+        modes[7] = Mode::Analog;
+        modes[15] = Mode::Output;
+
+        moder.set_all_modes(modes);
+        assert_eq!(moder.0, 0b01 | 0b10 << 2 | 0b11 << 14 | 0b01 << 30);
+    }
+
+    #[test]
+    fn test_moder_set_all_modes_matches_set_mode() {
+        let modes = [Mode::Analog, Mode::Output, Mode::Input, Mode::Alternate,
+                     Mode::Output, Mode::Output, Mode::Analog, Mode::Input,
+                     Mode::Alternate, Mode::Input, Mode::Output, Mode::Analog,
+                     Mode::Input, Mode::Alternate, Mode::Analog, Mode::Output];
+        let mut all = MODER(0);
+        let mut each = MODER(0);
+
+        all.set_all_modes(modes);
+        for (port, mode) in modes.iter().enumerate() {
+            each.set_mode(*mode, port as u8);
+        }
+        assert_eq!(all.0, each.0);
+    }
+
     #[test]
     fn test_moder_get_mode() {
         let moder = MODER(0b11 << 14);
@@ -126,4 +212,23 @@ mod tests {
 
         moder.get_mode(16);
     }
+
+    #[test]
+    fn test_mode_bits_round_trip() {
+        for mode in &[Mode::Input, Mode::Output, Mode::Alternate, Mode::Analog] {
+            assert_eq!(Mode::from_bits(mode.as_bits()), Some(*mode));
+        }
+    }
+
+    #[test]
+    fn test_mode_as_bits() {
+        assert_eq!(Mode::Input.as_bits(), 0b00);
+        assert_eq!(Mode::Analog.as_bits(), 0b11);
+    }
+
+    #[test]
+    fn test_mode_from_bits_invalid_is_none() {
+        assert_eq!(Mode::from_bits(0b100), None);
+        assert_eq!(Mode::from_bits(0xFF), None);
+    }
 }
@@ -0,0 +1,76 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+#[derive(Copy, Clone, Debug)]
+pub struct ODR(u32);
+
+impl_register!(ODR);
+
+impl ODR {
+    /// Return true if the output for the specified port is set high, port must be a value between
+    /// [0..15] or the kernel will panic.
+    pub fn get_bit(&self, port: u8) -> bool {
+        if port > 15 {
+            panic!("ODR::get_bit - specified port must be between [0..15]!");
+        }
+        self.0 & (0b1 << port) != 0
+    }
+
+    /// Flip the output for the specified port, port must be a value between [0..15] or the kernel
+    /// will panic.
+    pub fn toggle(&mut self, port: u8) {
+        if port > 15 {
+            panic!("ODR::toggle - specified port must be between [0..15]!");
+        }
+        self.0 ^= 0b1 << port;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_odr_get_bit() {
+        let odr = ODR(0b1 << 3);
+        assert!(odr.get_bit(3));
+        assert!(!odr.get_bit(4));
+    }
+
+    #[test]
+    fn test_odr_toggle_sets_low_bit_high() {
+        let mut odr = ODR(0);
+        odr.toggle(5);
+        assert_eq!(odr.0, 0b1 << 5);
+    }
+
+    #[test]
+    fn test_odr_toggle_twice_returns_to_initial_state() {
+        let mut odr = ODR(0b1 << 2 | 0b1 << 9);
+        odr.toggle(9);
+        assert_eq!(odr.0, 0b1 << 2);
+        odr.toggle(9);
+        assert_eq!(odr.0, 0b1 << 2 | 0b1 << 9);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_odr_toggle_panics_when_port_is_out_of_bounds() {
+        let mut odr = ODR(0);
+        odr.toggle(16);
+    }
+}
@@ -41,6 +41,22 @@ impl Field for Speed {
 }
 
 impl Speed {
+    /// Get the raw 2-bit register encoding of this value.
+    pub fn as_bits(self) -> u8 {
+        self.mask() as u8
+    }
+
+    /// Convert a raw 2-bit register encoding back into a value, returns `None` if the bits are
+    /// not a valid encoding.
+    pub fn from_bits(bits: u8) -> Option<Self> {
+        match bits as u32 {
+            SPEED_LOW | SPEED_LOW_ALT => Some(Speed::Low),
+            SPEED_MEDIUM => Some(Speed::Medium),
+            SPEED_HIGH => Some(Speed::High),
+            _ => None,
+        }
+    }
+
     fn from_mask(mask: u32) -> Self {
         match mask {
             SPEED_LOW | SPEED_LOW_ALT => Speed::Low,
@@ -54,6 +70,8 @@ impl Speed {
 #[derive(Copy, Clone, Debug)]
 pub struct OSPEEDR(u32);
 
+impl_register!(OSPEEDR);
+
 impl OSPEEDR {
     pub fn set_speed(&mut self, speed: Speed, port: u8) {
         if port > 15 {
@@ -121,4 +139,21 @@ mod tests {
 
         ospeedr.get_speed(16);
     }
+
+    #[test]
+    fn test_speed_bits_round_trip() {
+        for speed in &[Speed::Low, Speed::Medium, Speed::High] {
+            assert_eq!(Speed::from_bits(speed.as_bits()), Some(*speed));
+        }
+    }
+
+    #[test]
+    fn test_speed_from_bits_alternate_low_encoding() {
+        assert_eq!(Speed::from_bits(0b10), Some(Speed::Low));
+    }
+
+    #[test]
+    fn test_speed_from_bits_invalid_is_none() {
+        assert_eq!(Speed::from_bits(0b100), None);
+    }
 }
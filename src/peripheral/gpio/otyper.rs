@@ -37,6 +37,21 @@ impl Field for Type {
 }
 
 impl Type {
+    /// Get the raw 1-bit register encoding of this value.
+    pub fn as_bits(self) -> u8 {
+        self.mask() as u8
+    }
+
+    /// Convert a raw 1-bit register encoding back into a value, returns `None` if the bits are
+    /// not a valid encoding.
+    pub fn from_bits(bits: u8) -> Option<Self> {
+        match bits as u32 {
+            TYPE_PUSHPULL => Some(Type::PushPull),
+            TYPE_OPENDRAIN => Some(Type::OpenDrain),
+            _ => None,
+        }
+    }
+
     fn from_mask(mask: u32) -> Self {
         match mask {
             TYPE_PUSHPULL => Type::PushPull,
@@ -49,6 +64,8 @@ impl Type {
 #[derive(Copy, Clone, Debug)]
 pub struct OTYPER(u32);
 
+impl_register!(OTYPER);
+
 impl OTYPER {
     pub fn set_type(&mut self, new_type: Type, port: u8) {
         if port > 15 {
@@ -117,4 +134,16 @@ mod tests {
 
         otyper.get_type(16);
     }
+
+    #[test]
+    fn test_type_bits_round_trip() {
+        for p_type in &[Type::PushPull, Type::OpenDrain] {
+            assert_eq!(Type::from_bits(p_type.as_bits()), Some(*p_type));
+        }
+    }
+
+    #[test]
+    fn test_type_from_bits_invalid_is_none() {
+        assert_eq!(Type::from_bits(0b10), None);
+    }
 }
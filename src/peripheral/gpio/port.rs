@@ -15,7 +15,7 @@
 * along with this program. If not, see <http://www.gnu.org/licenses/>.
 */
 
-use super::{GPIO, Mode, Group, Type, Speed, Pull, AlternateFunction};
+use super::{GPIO, Mode, Group, Type, Speed, Pull, AlternateFunction, Toggle, GpioError};
 
 /// A specific GPIO port. You can modify the mode it is set to
 /// and set the pin high or low with the .set() and .reset() methods
@@ -28,6 +28,7 @@ use super::{GPIO, Mode, Group, Type, Speed, Pull, AlternateFunction};
 ///   port.set_type(Type::PushPull);
 ///   port.set(); // Light's green!
 /// ```
+#[derive(Copy, Clone, Debug)]
 pub struct Port {
     group: Group,
     port: u8,
@@ -36,85 +37,161 @@ pub struct Port {
 impl Port {
     /// Create a new port for the associated group. Ports are NOT thread safe, if you must ensure
     /// an atomic set of operations on a port, use some kind of synchronization tool.
+    ///
+    /// # Panics
+    ///
+    /// Port must be a value between [0..15] or the kernel will panic.
     pub fn new(port: u8, group: Group) -> Port {
+        match Port::try_new(port, group) {
+            Some(port) => port,
+            None => panic!("Port::new - port must be a value between 0..15"),
+        }
+    }
+
+    /// Create a new port for the associated group, returning `None` if the port is not a value
+    /// between [0..15].
+    pub fn try_new(port: u8, group: Group) -> Option<Port> {
         if port > 15 {
-            panic!("Port::new - port must be a value between 0..15");
+            return None;
         }
-        Port {
+        Some(Port {
             group: group,
             port: port,
-        }
+        })
+    }
+
+    /// Get the index of the port within its group. This is always a value between [0..15].
+    pub fn get_index(&self) -> u8 {
+        self.port
+    }
+
+    /// Get the group the port belongs to.
+    pub fn get_group(&self) -> Group {
+        self.group
     }
 
     /// Set the port mode.
     pub fn set_mode(&mut self, mode: Mode) {
         let mut gpio = GPIO::group(self.group);
-        gpio.set_mode(mode, self.port);
+        gpio.set_mode_port(mode, *self);
     }
 
     /// Get the current port mode.
     pub fn get_mode(&self) -> Mode {
         let gpio = GPIO::group(self.group);
-        gpio.get_mode(self.port)
+        gpio.get_mode_port(*self)
     }
 
     /// Set the port type.
     pub fn set_type(&mut self, p_type: Type) {
         let mut gpio = GPIO::group(self.group);
-        gpio.set_type(p_type, self.port);
+        gpio.set_type_port(p_type, *self);
     }
 
     /// Get the current port type.
     pub fn get_type(&self) -> Type {
         let gpio = GPIO::group(self.group);
-        gpio.get_type(self.port)
+        gpio.get_type_port(*self)
+    }
+
+    /// Reconfigure one of the debug pins (PA13, PA14, PA15, PB3 or PB4) with the specified mode.
+    ///
+    /// The STM32F0 debugger is connected through SWDIO on PA13 and SWCLK on PA14. Once they're
+    /// reconfigured the debugger can't attach anymore, and the only way to get it back is to
+    /// connect under reset. PA15, PB3 and PB4 carry JTAG on other parts, so they are guarded too.
+    /// To make sure this never happens by accident, the pin is only reconfigured if
+    /// `acknowledge_debug_loss` is true, otherwise `GpioError::DebugNotAcknowledged` is returned.
+    /// If the port isn't a debug pin `GpioError::NotDebugPin` is returned, use `set_mode` for
+    /// those.
+    pub fn reclaim_debug_pin(&mut self, mode: Mode, acknowledge_debug_loss: bool)
+        -> Result<(), GpioError> {
+        let mut gpio = GPIO::group(self.group);
+        super::reclaim_debug_pin_in(&mut gpio, *self, mode, acknowledge_debug_loss)
     }
 
     /// Set the port pin speed.
     pub fn set_speed(&mut self, speed: Speed) {
         let mut gpio = GPIO::group(self.group);
-        gpio.set_speed(speed, self.port);
+        gpio.set_speed_port(speed, *self);
     }
 
     /// Get the current port pin speed.
     pub fn get_speed(&self) -> Speed {
         let gpio = GPIO::group(self.group);
-        gpio.get_speed(self.port)
+        gpio.get_speed_port(*self)
     }
 
     /// Set behavior of GPIO pin when it is not asserted.
     pub fn set_pull(&mut self, pull: Pull) {
         let mut gpio = GPIO::group(self.group);
-        gpio.set_pull(pull, self.port);
+        gpio.set_pull_port(pull, *self);
     }
 
     /// Get currently defined behavior of GPIO pin when not asserted.
     pub fn get_pull(&self) -> Pull {
         let gpio = GPIO::group(self.group);
-        gpio.get_pull(self.port)
+        gpio.get_pull_port(*self)
     }
 
     /// Set the function mode for the port.
     pub fn set_function(&mut self, function: AlternateFunction) {
         let mut gpio = GPIO::group(self.group);
-        gpio.set_function(function, self.port);
+        gpio.set_function_port(function, *self);
     }
 
     /// Get the current function mode for the port.
     pub fn get_function(&self) -> AlternateFunction {
         let gpio = GPIO::group(self.group);
-        gpio.get_function(self.port)
+        gpio.get_function_port(*self)
     }
 
     /// Set the pin high.
     pub fn set(&mut self) {
         let mut gpio = GPIO::group(self.group);
-        gpio.set_bit(self.port);
+        gpio.set_bit_port(*self);
     }
 
     /// Set the pin low.
     pub fn reset(&mut self) {
         let mut gpio = GPIO::group(self.group);
-        gpio.reset_bit(self.port);
+        gpio.reset_bit_port(*self);
+    }
+
+    /// Read the pin, returning true if it is high.
+    pub fn read(&self) -> bool {
+        let gpio = GPIO::group(self.group);
+        gpio.get_bit_port(*self)
+    }
+}
+
+impl Toggle for Port {
+    fn toggle(&mut self) {
+        let mut gpio = GPIO::group(self.group);
+        gpio.toggle_bit_port(*self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_port_try_new_valid_index() {
+        let port = Port::try_new(15, Group::A).unwrap();
+
+        assert_eq!(port.get_index(), 15);
+        assert_eq!(port.get_group(), Group::A);
+    }
+
+    #[test]
+    fn test_port_try_new_index_greater_than_15_is_none() {
+        assert!(Port::try_new(16, Group::B).is_none());
+        assert!(Port::try_new(255, Group::C).is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_port_new_index_greater_than_15_panics() {
+        Port::new(16, Group::F);
     }
 }
@@ -0,0 +1,57 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! This module defines the usual pin settings for common peripherals, so bring-up code doesn't
+//! have to look them up in the datasheet each time.
+
+use super::{Type, Speed, Pull};
+
+/// Pin configurations for common peripheral signals. Every preset puts the pin into alternate
+/// function mode.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PinPreset {
+    /// Usart transmit line, push-pull with a pull-up so the line idles high.
+    UsartTx,
+    /// Usart receive line, with a pull-up so the line idles high.
+    UsartRx,
+    /// SPI clock, push-pull at high speed.
+    SpiSck,
+    /// SPI master out slave in, push-pull at high speed.
+    SpiMosi,
+    /// SPI master in slave out, floating input.
+    SpiMiso,
+    /// I2C clock, open-drain with a pull-up.
+    I2cScl,
+    /// I2C data, open-drain with a pull-up.
+    I2cSda,
+    /// Timer PWM output, push-pull.
+    PwmOut,
+}
+
+impl PinPreset {
+    /// Get the output type, speed and pull settings for the preset.
+    pub fn settings(self) -> (Type, Speed, Pull) {
+        match self {
+            PinPreset::UsartTx | PinPreset::UsartRx => (Type::PushPull, Speed::High, Pull::Up),
+            PinPreset::SpiSck | PinPreset::SpiMosi | PinPreset::SpiMiso => {
+                (Type::PushPull, Speed::High, Pull::Neither)
+            },
+            PinPreset::I2cScl | PinPreset::I2cSda => (Type::OpenDrain, Speed::Medium, Pull::Up),
+            PinPreset::PwmOut => (Type::PushPull, Speed::Medium, Pull::Neither),
+        }
+    }
+}
@@ -40,6 +40,22 @@ impl Field for Pull {
 }
 
 impl Pull {
+    /// Get the raw 2-bit register encoding of this value.
+    pub fn as_bits(self) -> u8 {
+        self.mask() as u8
+    }
+
+    /// Convert a raw 2-bit register encoding back into a value, returns `None` if the bits are
+    /// not a valid encoding.
+    pub fn from_bits(bits: u8) -> Option<Self> {
+        match bits as u32 {
+            PUPD_NEITHER => Some(Pull::Neither),
+            PUPD_UP => Some(Pull::Up),
+            PUPD_DOWN => Some(Pull::Down),
+            _ => None,
+        }
+    }
+
     fn from_mask(mask: u32) -> Self {
         match mask {
             PUPD_NEITHER => Pull::Neither,
@@ -53,6 +69,8 @@ impl Pull {
 #[derive(Copy, Clone, Debug)]
 pub struct PUPDR(u32);
 
+impl_register!(PUPDR);
+
 impl PUPDR {
     pub fn set_pull(&mut self, pull: Pull, port: u8) {
         if port > 15 {
@@ -73,6 +91,19 @@ impl PUPDR {
 
         Pull::from_mask(mask)
     }
+
+    /* Sets the pull of all 16 ports with a single write, port `n` takes its
+     * pull from `pulls[n]`.
+     */
+    pub fn set_all(&mut self, pulls: &[Pull; 16]) {
+        self.0 = compose_pulls(pulls);
+    }
+}
+
+fn compose_pulls(pulls: &[Pull; 16]) -> u32 {
+    pulls.iter()
+         .enumerate()
+         .fold(0, |value, (port, pull)| value | pull.mask() << (port * 2))
 }
 
 #[cfg(test)]
@@ -106,6 +137,28 @@ mod tests {
         pupdr.set_pull(Pull::Up, 16);
     }
 
+    #[test]
+    fn test_compose_pulls_mixed() {
+        let mut pulls = [Pull::Neither; 16];
+        pulls[0] = Pull::Up;
+        pulls[1] = Pull::Down;
+        pulls[7] = Pull::Up;
+        pulls[15] = Pull::Down;
+
+        assert_eq!(compose_pulls(&pulls), 0b01 | 0b10 << 2 | 0b01 << 14 | 0b10 << 30);
+    }
+
+    #[test]
+    fn test_pupdr_set_all_replaces_every_port() {
+        let mut pupdr = PUPDR(0xFFFF_FFFF);
+
+        pupdr.set_all(&[Pull::Up; 16]);
+        assert_eq!(pupdr.0, 0x5555_5555);
+        for port in 0..16 {
+            assert_eq!(pupdr.get_pull(port), Pull::Up);
+        }
+    }
+
     #[test]
     fn test_pupdr_get_pull() {
         let pupdr = PUPDR(0b10 << 6);
@@ -120,4 +173,17 @@ mod tests {
 
         pupdr.get_pull(16);
     }
+
+    #[test]
+    fn test_pull_bits_round_trip() {
+        for pull in &[Pull::Neither, Pull::Up, Pull::Down] {
+            assert_eq!(Pull::from_bits(pull.as_bits()), Some(*pull));
+        }
+    }
+
+    #[test]
+    fn test_pull_from_bits_reserved_is_none() {
+        assert_eq!(Pull::from_bits(0b11), None);
+        assert_eq!(Pull::from_bits(0b100), None);
+    }
 }
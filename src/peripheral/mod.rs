@@ -17,14 +17,44 @@
 
 //! This module handles the memory mapped peripherals that are a part of the Cortex-M0. Submodules
 //! will handle the more specific details of each peripheral.
+
+// Implement `Register` for register types wrapping a single `u32`. This has to be defined ahead
+// of the submodules so they can use it.
+#[macro_export]
+macro_rules! impl_register {
+    ($($name:ident),+) => {
+        $(
+            impl $crate::peripheral::Register for $name {
+                fn word(&self) -> u32 {
+                    self.0
+                }
+
+                fn word_mut(&mut self) -> &mut u32 {
+                    &mut self.0
+                }
+            }
+        )+
+    }
+}
+
+pub mod addresses;
 pub mod rcc;
+pub mod flash;
 pub mod gpio;
 pub mod systick;
+pub mod pwr;
+pub mod exti;
+pub mod syscfg;
+pub mod adc;
 #[cfg(feature="dma")]
 pub mod dma;
 #[cfg(feature="serial")]
 pub mod usart;
 
+mod callback;
+
+use core::ptr;
+
 #[macro_export]
 macro_rules! pad_field {
     ($name:ident[$N:expr]) => {
@@ -48,3 +78,186 @@ pub trait Field {
     /// Return the bit mask for the register bit field.
     fn mask(&self) -> u32;
 }
+
+/// A register that wraps its raw `u32` value, giving the common read-modify-write idioms so each
+/// register type doesn't have to repeat them.
+pub trait Register {
+    /// Return the raw value of the register.
+    fn word(&self) -> u32;
+
+    /// Return a mutable reference to the raw value of the register.
+    fn word_mut(&mut self) -> &mut u32;
+
+    /// Read the value of the register.
+    fn read(&self) -> u32 {
+        self.word()
+    }
+
+    /// Set the bits selected by `mask`, all other bits are left unchanged.
+    fn set_bits(&mut self, mask: u32) {
+        *self.word_mut() |= mask;
+    }
+
+    /// Clear the bits selected by `mask`, all other bits are left unchanged.
+    fn clear_bits(&mut self, mask: u32) {
+        *self.word_mut() &= !mask;
+    }
+
+    /// Replace the bits selected by `mask` with the matching bits from `value`. All other bits
+    /// are left unchanged.
+    fn modify(&mut self, mask: u32, value: u32) {
+        let word = self.word_mut();
+        *word = (*word & !mask) | (value & mask);
+    }
+}
+
+/// Gives register level access to a memory mapped peripheral, relative to the base address of its
+/// register block.
+pub trait Control {
+    /// Return the base address of the peripheral's register block.
+    fn mem_addr(&self) -> *const u32;
+
+    /// Read the register at the byte offset from the base address.
+    ///
+    /// # Panics
+    ///
+    /// The offset must be word aligned or the kernel will panic.
+    fn read_reg(&self, offset: u32) -> u32 {
+        unsafe { ptr::read_volatile(reg_addr(self.mem_addr(), offset)) }
+    }
+
+    /// Replace the bits selected by `mask` in the register at the byte offset from the base
+    /// address with the matching bits from `value`. All other bits are left unchanged.
+    ///
+    /// # Panics
+    ///
+    /// The offset must be word aligned or the kernel will panic.
+    fn modify_bits(&mut self, offset: u32, mask: u32, value: u32) {
+        let addr = reg_addr(self.mem_addr(), offset) as *mut u32;
+        unsafe {
+            let old = ptr::read_volatile(addr);
+            ptr::write_volatile(addr, (old & !mask) | (value & mask));
+        }
+    }
+}
+
+fn reg_addr(base: *const u32, offset: u32) -> *const u32 {
+    if offset % 4 != 0 {
+        panic!("Control::reg_addr - register offset must be word aligned!");
+    }
+    unsafe { base.offset((offset / 4) as isize) }
+}
+
+// Poll `ready` up to `attempts` times, returning true as soon as it does. The peripherals use this
+// to wait on a hardware flag without hanging if the flag never comes up.
+fn wait_until<F: FnMut() -> bool>(attempts: u32, mut ready: F) -> bool {
+    for _ in 0..attempts {
+        if ready() {
+            return true;
+        }
+    }
+    false
+}
+
+// Read the raw value of a register in a register block living in regular memory, for the
+// peripheral tests.
+#[cfg(test)]
+fn raw_word<T>(reg: &T) -> u32 {
+    unsafe { ptr::read(reg as *const T as *const u32) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Block([u32; 4]);
+
+    impl Control for Block {
+        fn mem_addr(&self) -> *const u32 {
+            self.0.as_ptr()
+        }
+    }
+
+    struct Reg(u32);
+
+    impl Register for Reg {
+        fn word(&self) -> u32 {
+            self.0
+        }
+
+        fn word_mut(&mut self) -> &mut u32 {
+            &mut self.0
+        }
+    }
+
+    #[test]
+    fn test_register_set_and_clear_bits() {
+        let mut reg = Reg(0b1000);
+
+        reg.set_bits(0b0011);
+        assert_eq!(reg.read(), 0b1011);
+        reg.clear_bits(0b1001);
+        assert_eq!(reg.read(), 0b0010);
+    }
+
+    #[test]
+    fn test_register_modify_only_touches_mask() {
+        let mut reg = Reg(0b1010_1010);
+
+        reg.modify(0b1111, 0xFFFF_0101);
+        assert_eq!(reg.read(), 0b1010_0101);
+    }
+
+    #[test]
+    fn test_control_read_reg_uses_byte_offset() {
+        let block = Block([0xA, 0xB, 0xC, 0xD]);
+
+        assert_eq!(block.read_reg(0x00), 0xA);
+        assert_eq!(block.read_reg(0x04), 0xB);
+        assert_eq!(block.read_reg(0x0C), 0xD);
+    }
+
+    #[test]
+    fn test_control_modify_bits_only_touches_mask() {
+        let mut block = Block([0; 4]);
+        block.0[2] = 0b1010_1010;
+
+        block.modify_bits(0x08, 0b1111, 0b0101);
+        assert_eq!(block.0[2], 0b1010_0101);
+        assert_eq!(block.0[1], 0);
+        assert_eq!(block.0[3], 0);
+    }
+
+    #[test]
+    fn test_control_modify_bits_ignores_value_outside_mask() {
+        let mut block = Block([0; 4]);
+
+        block.modify_bits(0x04, 0b1 << 3, 0xFFFF_FFFF);
+        assert_eq!(block.0[1], 0b1 << 3);
+    }
+
+    #[test]
+    fn test_wait_until_gives_up() {
+        let mut polls = 0;
+
+        assert!(!wait_until(10, || { polls += 1; false }));
+        assert_eq!(polls, 10);
+        assert!(wait_until(10, || true));
+    }
+
+    #[test]
+    fn test_wait_until_stops_polling_once_ready() {
+        let mut polls = 0;
+
+        assert!(wait_until(10, || { polls += 1; polls == 4 }));
+        assert_eq!(polls, 4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_control_unaligned_offset_panics() {
+        let block = Block([0; 4]);
+
+        block.read_reg(0x02);
+    }
+}
@@ -0,0 +1,58 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use super::defs::*;
+
+#[derive(Copy, Clone, Debug)]
+pub struct CR(u32);
+
+impl CR {
+    /* Bit 8 DBP: Disable RTC domain write protection.
+     *   In reset state, the RTC and backup registers are protected against parasitic write
+     *   access. This bit must be set to enable write access to these registers.
+     *   0: Access to RTC and Backup registers disabled
+     *   1: Access to RTC and Backup registers enabled
+     */
+    pub fn set_backup_access(&mut self, enable: bool) {
+        self.0 &= !CR_DBP;
+        if enable {
+            self.0 |= CR_DBP;
+        }
+    }
+
+    pub fn get_backup_access(&self) -> bool {
+        self.0 & CR_DBP != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cr_set_backup_access() {
+        let mut cr = CR(0);
+
+        cr.set_backup_access(true);
+        assert_eq!(cr.0, 0b1 << 8);
+        assert!(cr.get_backup_access());
+
+        cr.set_backup_access(false);
+        assert_eq!(cr.0, 0);
+        assert!(!cr.get_backup_access());
+    }
+}
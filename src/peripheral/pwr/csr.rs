@@ -0,0 +1,119 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use super::super::Field;
+use super::defs::*;
+
+/// Defines the wakeup pins that can bring the device out of Standby mode.
+///
+/// Not every device has all eight pins, check the datasheet for which are available.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum WakeupPin {
+    /// WKUP1, on PA0.
+    One,
+    /// WKUP2, on PC13.
+    Two,
+    /// WKUP3, on PE6.
+    Three,
+    /// WKUP4, on PA2.
+    Four,
+    /// WKUP5, on PC5.
+    Five,
+    /// WKUP6, on PB5.
+    Six,
+    /// WKUP7, on PB15.
+    Seven,
+    /// WKUP8, on PF2.
+    Eight,
+}
+
+impl Field for WakeupPin {
+    fn mask(&self) -> u32 {
+        match *self {
+            WakeupPin::One => CSR_EWUP1,
+            WakeupPin::Two => CSR_EWUP2,
+            WakeupPin::Three => CSR_EWUP3,
+            WakeupPin::Four => CSR_EWUP4,
+            WakeupPin::Five => CSR_EWUP5,
+            WakeupPin::Six => CSR_EWUP6,
+            WakeupPin::Seven => CSR_EWUP7,
+            WakeupPin::Eight => CSR_EWUP8,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct CSR(u32);
+
+impl CSR {
+    /* Bits 15:8 EWUPx: Enable WKUPx pin
+     *   These bits are set and cleared by software.
+     *   0: WKUPx pin is used for general purpose I/O. An event on the WKUPx pin does not wakeup
+     *      the device from Standby mode.
+     *   1: WKUPx pin is used for wakeup from Standby mode and forced in input pull down
+     *      configuration (rising edge on WKUPx pin wakes-up the system from Standby mode).
+     */
+    pub fn set_wakeup_pin(&mut self, enable: bool, pin: WakeupPin) {
+        let mask = pin.mask();
+
+        self.0 &= !mask;
+        if enable {
+            self.0 |= mask;
+        }
+    }
+
+    pub fn get_wakeup_pin(&self, pin: WakeupPin) -> bool {
+        self.0 & pin.mask() != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csr_set_wakeup_pin_selects_ewup_bit() {
+        let pins = [WakeupPin::One, WakeupPin::Two, WakeupPin::Three, WakeupPin::Four,
+                    WakeupPin::Five, WakeupPin::Six, WakeupPin::Seven, WakeupPin::Eight];
+        for (i, pin) in pins.iter().enumerate() {
+            let mut csr = CSR(0);
+
+            csr.set_wakeup_pin(true, *pin);
+            assert_eq!(csr.0, 0b1 << (8 + i));
+        }
+    }
+
+    #[test]
+    fn test_csr_set_wakeup_pin_keeps_other_pins() {
+        let mut csr = CSR(0);
+
+        csr.set_wakeup_pin(true, WakeupPin::One);
+        csr.set_wakeup_pin(true, WakeupPin::Two);
+        assert_eq!(csr.0, 0b11 << 8);
+
+        csr.set_wakeup_pin(false, WakeupPin::One);
+        assert_eq!(csr.0, 0b1 << 9);
+    }
+
+    #[test]
+    fn test_csr_get_wakeup_pin() {
+        let csr = CSR(0b1 << 9);
+
+        assert!(csr.get_wakeup_pin(WakeupPin::Two));
+        assert!(!csr.get_wakeup_pin(WakeupPin::One));
+    }
+}
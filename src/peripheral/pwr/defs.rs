@@ -0,0 +1,35 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use peripheral::addresses;
+
+pub const PWR_ADDR: *const u32 = addresses::PWR_ADDR;
+
+// Power Control Register
+pub const CR_OFFSET: u32 = 0x00;
+pub const CR_DBP: u32 = 0b1 << 8;
+
+// Power Control/Status Register
+pub const CSR_OFFSET: u32 = 0x04;
+pub const CSR_EWUP1: u32 = 0b1 << 8;
+pub const CSR_EWUP2: u32 = 0b1 << 9;
+pub const CSR_EWUP3: u32 = 0b1 << 10;
+pub const CSR_EWUP4: u32 = 0b1 << 11;
+pub const CSR_EWUP5: u32 = 0b1 << 12;
+pub const CSR_EWUP6: u32 = 0b1 << 13;
+pub const CSR_EWUP7: u32 = 0b1 << 14;
+pub const CSR_EWUP8: u32 = 0b1 << 15;
@@ -0,0 +1,160 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! This module controls the power controller, which manages the low power modes of the device.
+//!
+//! The power controller is clocked through the RCC, the `PowerInterface` peripheral must be
+//! enabled before any of its registers can be written.
+
+mod cr;
+mod csr;
+mod defs;
+
+use core::ops::{Deref, DerefMut};
+use volatile::Volatile;
+use self::defs::*;
+use self::cr::CR;
+use self::csr::CSR;
+
+pub use self::csr::WakeupPin;
+
+/// Defines the edge on a wakeup pin that brings the device out of Standby mode.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum WakeupEdge {
+    /// Wake on a rising edge.
+    Rising,
+    /// Wake on a falling edge.
+    Falling,
+}
+
+/// Returns an instance of the PWR struct so it can be used to configure the low power modes.
+pub fn pwr() -> PWR {
+    PWR::pwr()
+}
+
+#[derive(Copy, Clone, Debug)]
+#[repr(C)]
+#[doc(hidden)]
+pub struct RawPWR {
+    cr: CR,
+    csr: CSR,
+}
+
+/// Power Controller
+#[derive(Copy, Clone, Debug)]
+pub struct PWR(Volatile<RawPWR>);
+
+impl PWR {
+    fn pwr() -> Self {
+        unsafe {
+            PWR(Volatile::new(PWR_ADDR as *const _))
+        }
+    }
+}
+
+impl Deref for PWR {
+    type Target = RawPWR;
+
+    fn deref(&self) -> &Self::Target {
+        &*(self.0)
+    }
+}
+
+impl DerefMut for PWR {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut *(self.0)
+    }
+}
+
+impl RawPWR {
+    /// Allow writes to the RTC and backup domain registers, including the RCC's BDCR. They are
+    /// write protected out of reset.
+    pub fn enable_backup_access(&mut self) {
+        self.cr.set_backup_access(true);
+    }
+
+    /// Write protect the RTC and backup domain registers again.
+    pub fn disable_backup_access(&mut self) {
+        self.cr.set_backup_access(false);
+    }
+
+    /// Check if the RTC and backup domain registers can be written.
+    pub fn is_backup_access_enabled(&self) -> bool {
+        self.cr.get_backup_access()
+    }
+
+    /// Use the wakeup pin to bring the device out of Standby mode.
+    ///
+    /// While enabled, the pin is forced into an input pull down configuration and can't be used
+    /// for general purpose I/O.
+    ///
+    /// # Panics
+    ///
+    /// The STM32F0 wakeup pins only detect rising edges, there is no polarity selection, so
+    /// asking for `WakeupEdge::Falling` will cause the kernel to panic.
+    pub fn enable_wakeup_pin(&mut self, pin: WakeupPin, edge: WakeupEdge) {
+        if edge != WakeupEdge::Rising {
+            panic!("PWR::enable_wakeup_pin - wakeup pins can only detect a rising edge!");
+        }
+        self.csr.set_wakeup_pin(true, pin);
+    }
+
+    /// Stop using the wakeup pin to wake from Standby mode, returning it to general purpose I/O.
+    pub fn disable_wakeup_pin(&mut self, pin: WakeupPin) {
+        self.csr.set_wakeup_pin(false, pin);
+    }
+
+    /// Check if the wakeup pin is enabled.
+    pub fn is_wakeup_pin_enabled(&self, pin: WakeupPin) -> bool {
+        self.csr.get_wakeup_pin(pin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use peripheral::raw_word;
+    use core::mem;
+
+    #[test]
+    fn test_enable_wakeup_pin() {
+        let mut pwr: RawPWR = unsafe { mem::zeroed() };
+
+        pwr.enable_wakeup_pin(WakeupPin::Two, WakeupEdge::Rising);
+        assert_eq!(raw_word(&pwr.csr), 0b1 << 9);
+        assert!(pwr.is_wakeup_pin_enabled(WakeupPin::Two));
+        assert!(!pwr.is_wakeup_pin_enabled(WakeupPin::One));
+    }
+
+    #[test]
+    fn test_disable_wakeup_pin() {
+        let mut pwr: RawPWR = unsafe { mem::zeroed() };
+        pwr.enable_wakeup_pin(WakeupPin::One, WakeupEdge::Rising);
+        pwr.enable_wakeup_pin(WakeupPin::Two, WakeupEdge::Rising);
+
+        pwr.disable_wakeup_pin(WakeupPin::One);
+        assert_eq!(raw_word(&pwr.csr), 0b1 << 9);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_enable_wakeup_pin_falling_edge_panics() {
+        let mut pwr: RawPWR = unsafe { mem::zeroed() };
+
+        pwr.enable_wakeup_pin(WakeupPin::One, WakeupEdge::Falling);
+    }
+}
@@ -0,0 +1,165 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use super::super::Field;
+use super::defs::*;
+
+/// Defines the drive capability of the LSE oscillator. A higher drive starts a crystal more
+/// reliably, at the cost of more current.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LseDrive {
+    /// Lowest drive.
+    Low,
+    /// Medium low drive.
+    MediumLow,
+    /// Medium high drive.
+    MediumHigh,
+    /// Highest drive, the reset value.
+    High,
+}
+
+impl Field for LseDrive {
+    fn mask(&self) -> u32 {
+        match *self {
+            LseDrive::Low => LSEDRV_LOW,
+            LseDrive::MediumLow => LSEDRV_MEDIUM_LOW,
+            LseDrive::MediumHigh => LSEDRV_MEDIUM_HIGH,
+            LseDrive::High => LSEDRV_HIGH,
+        }
+    }
+}
+
+impl LseDrive {
+    fn from_mask(mask: u32) -> Self {
+        match mask {
+            LSEDRV_LOW => LseDrive::Low,
+            LSEDRV_MEDIUM_LOW => LseDrive::MediumLow,
+            LSEDRV_MEDIUM_HIGH => LseDrive::MediumHigh,
+            LSEDRV_HIGH => LseDrive::High,
+            _ => panic!("LseDrive::from_mask - mask was not a valid value!"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BDCR(u32);
+
+impl BDCR {
+    // A cleared register, with the backup domain reset released and the LSE and RTC off.
+    pub fn cleared() -> Self {
+        BDCR(0)
+    }
+
+    /* Bit 16 BDRST: RTC domain software reset
+     *   Set and cleared by software.
+     *   0: Reset not activated
+     *   1: Resets the entire RTC domain
+     */
+    pub fn set_backup_domain_reset(&mut self, reset: bool) {
+        self.0 &= !BDCR_BDRST;
+        if reset {
+            self.0 |= BDCR_BDRST;
+        }
+    }
+
+    pub fn get_backup_domain_reset(&self) -> bool {
+        self.0 & BDCR_BDRST != 0
+    }
+
+    /* Bit 0 LSEON: LSE oscillator enable
+     *   Set and cleared by software.
+     *   0: LSE oscillator OFF
+     *   1: LSE oscillator ON
+     */
+    pub fn is_lse_on(&self) -> bool {
+        self.0 & BDCR_LSEON != 0
+    }
+
+    /* Bit 1 LSERDY: LSE oscillator ready
+     *   Set and cleared by hardware to indicate when the external 32 kHz
+     *   oscillator is stable.
+     *   0: LSE oscillator not ready
+     *   1: LSE oscillator ready
+     */
+    pub fn is_lse_ready(&self) -> bool {
+        self.0 & BDCR_LSERDY != 0
+    }
+
+    /* Bits 4:3 LSEDRV: LSE oscillator drive capability
+     *   Set and reset by software to modulate the LSE oscillator's drive
+     *   capability. A reset of the RTC domain restores the default value.
+     *   00: 'Xtal mode' lower driving capability
+     *   01: 'Xtal mode' medium high driving capability
+     *   10: 'Xtal mode' medium low driving capability
+     *   11: 'Xtal mode' higher driving capability (reset value)
+     */
+    pub fn set_lse_drive(&mut self, drive: LseDrive) {
+        self.0 &= !BDCR_LSEDRV_MASK;
+        self.0 |= drive.mask() << BDCR_LSEDRV_SHIFT;
+    }
+
+    pub fn get_lse_drive(&self) -> LseDrive {
+        LseDrive::from_mask((self.0 & BDCR_LSEDRV_MASK) >> BDCR_LSEDRV_SHIFT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bdcr_set_backup_domain_reset() {
+        let mut bdcr = BDCR(0);
+
+        bdcr.set_backup_domain_reset(true);
+        assert_eq!(bdcr.0, 0b1 << 16);
+        assert!(bdcr.get_backup_domain_reset());
+
+        bdcr.set_backup_domain_reset(false);
+        assert_eq!(bdcr.0, 0);
+    }
+
+    #[test]
+    fn test_bdcr_set_lse_drive_encoding() {
+        let mut bdcr = BDCR(0);
+
+        bdcr.set_lse_drive(LseDrive::MediumHigh);
+        assert_eq!(bdcr.0, 0b01 << 3);
+        bdcr.set_lse_drive(LseDrive::MediumLow);
+        assert_eq!(bdcr.0, 0b10 << 3);
+        bdcr.set_lse_drive(LseDrive::High);
+        assert_eq!(bdcr.0, 0b11 << 3);
+        bdcr.set_lse_drive(LseDrive::Low);
+        assert_eq!(bdcr.0, 0);
+    }
+
+    #[test]
+    fn test_bdcr_set_lse_drive_keeps_other_bits() {
+        let mut bdcr = BDCR(0b1 << 16 | 0b11);
+
+        bdcr.set_lse_drive(LseDrive::MediumLow);
+        assert_eq!(bdcr.0, 0b1 << 16 | 0b10 << 3 | 0b11);
+        assert_eq!(bdcr.get_lse_drive(), LseDrive::MediumLow);
+    }
+
+    #[test]
+    fn test_bdcr_lse_status() {
+        assert!(BDCR(0b01).is_lse_on());
+        assert!(!BDCR(0b01).is_lse_ready());
+        assert!(BDCR(0b11).is_lse_ready());
+    }
+}
@@ -20,44 +20,174 @@
 use super::defs::*;
 
 pub mod clock_rate {
-    static mut CLOCK_RATE: u32 = 0;
+    use super::Clock;
+    use super::super::RawRCC;
+    use super::super::defs::{HSI_VALUE, HSE_VALUE, HSI48_VALUE};
 
-    pub fn get_system_clock_rate() -> u32 {
+    /// The system clock rate before the clock has been configured.
+    ///
+    /// The processor comes out of reset running off of the HSI, so this is the rate the system
+    /// clock rate is initialized to.
+    pub const DEFAULT_SYSTEM_CLOCK: u32 = HSI_VALUE;
+
+    // The clock rates the rest of the crate reads, kept together so the tests can check what
+    // they start out as without touching the global copy.
+    struct ClockRates {
+        system: u32,
+        hse: u32,
+    }
+
+    impl ClockRates {
+        const fn new() -> Self {
+            ClockRates {
+                system: DEFAULT_SYSTEM_CLOCK,
+                hse: HSE_VALUE,
+            }
+        }
+    }
+
+    static mut RATES: ClockRates = ClockRates::new();
+
+    /// Calculate the output rate of the PLL given the rate of its source, the prediv factor and the
+    /// multiplier.
+    ///
+    /// This can be evaluated at compile time for setups where the clock configuration is fixed.
+    pub const fn pll_clock_rate(source_rate: u32, prediv_factor: u32, multiplier: u32) -> u32 {
+        (source_rate / prediv_factor) * multiplier
+    }
+
+    /// Calculate the output rate of the PLL when driven by the specified clock.
+    ///
+    /// When driven by the HSI the PLL input is always HSI/2, the prediv factor only applies to the
+    /// other sources.
+    pub fn pll_output_rate(source: Clock, prediv_factor: u32, multiplier: u32) -> u32 {
+        pll_output_rate_with_hse(source, get_hse_rate(), prediv_factor, multiplier)
+    }
+
+    /// Calculate the output rate of the PLL when driven by the specified clock, with the HSE
+    /// running at `hse_rate` rather than the registered HSE rate.
+    pub fn pll_output_rate_with_hse(source: Clock, hse_rate: u32, prediv_factor: u32,
+                                    multiplier: u32) -> u32 {
+        match source {
+            Clock::HSE => pll_clock_rate(hse_rate, prediv_factor, multiplier),
+            Clock::HSI48 => pll_clock_rate(HSI48_VALUE, prediv_factor, multiplier),
+            Clock::HSI => pll_clock_rate(HSI_VALUE, 2, multiplier),
+            _ => panic!("CRR::pll_output_rate - invalid clock driving the PLL!"),
+        }
+    }
+
+    /// Register the frequency of the HSE oscillator on this board. Until this is called the HSE
+    /// is assumed to run at 8 MHz.
+    ///
+    /// This should be called before the HSE is used as a clock source, the system clock rate is
+    /// only recalculated when the system clock is next changed. Use `RCC::set_hse_frequency` to
+    /// recalculate it straight away.
+    pub fn set_hse_rate(rate: u32) {
+        unsafe { RATES.hse = rate; }
+    }
+
+    /// Get the registered frequency of the HSE oscillator.
+    pub fn get_hse_rate() -> u32 {
         unsafe {
-            CLOCK_RATE
+            RATES.hse
         }
     }
 
-    pub fn update_system_clock_rate() {
-        use super::Clock;
-        use super::super::super::systick;
-        use super::super::defs::*;
+    // The frequency of an oscillator, with the HSE running at `hse_rate`. The PLL has no fixed
+    // frequency, so it isn't handled here.
+    fn oscillator_rate(clock: Clock, hse_rate: u32) -> Option<u32> {
+        match clock {
+            Clock::HSE => Some(hse_rate),
+            _ => clock.nominal_rate(),
+        }
+    }
 
-        let rcc = super::super::rcc();
-        let rate = match rcc.get_system_clock_source() {
-            Clock::HSI => HSI_VALUE,
-            Clock::HSE => HSE_VALUE,
-            Clock::HSI48 => HSI48_VALUE,
+    pub fn get_system_clock_rate() -> u32 {
+        unsafe {
+            RATES.system
+        }
+    }
+
+    // The rate the system clock runs at with the RCC configured as in `rcc`, and the HSE running
+    // at `hse_rate`.
+    fn system_clock_rate(rcc: &RawRCC, hse_rate: u32) -> u32 {
+        match rcc.get_system_clock_source() {
             Clock::PLL => {
                 let multiplier = rcc.get_pll_multiplier() as u32;
                 let source = rcc.get_pll_source();
                 let prediv_factor = rcc.get_pll_prediv_factor() as u32;
 
-                match source {
-                    Clock::HSE => (HSE_VALUE/prediv_factor) * multiplier,
-                    Clock::HSI48 => (HSI48_VALUE/prediv_factor) * multiplier,
-                    Clock::HSI => (HSI_VALUE/2) * multiplier,
-                    _ => panic!("CRR::update_system_core_clock - invalid clock driving the PLL!"),
-                }
+                pll_output_rate_with_hse(source, hse_rate, prediv_factor, multiplier)
             },
-            _ => panic!("CRR::update_system_core_clock - invalid clock for the system clock!"),
-        };
+            // The SWS field can only select the HSI, HSE, HSI48 or PLL
+            clock => oscillator_rate(clock, hse_rate)
+                .expect("CRR::update_system_core_clock - invalid clock for the system clock!"),
+        }
+    }
+
+    pub fn update_system_clock_rate() {
+        use super::super::super::systick;
 
-        unsafe { CLOCK_RATE = rate; }
+        let rcc = super::super::rcc();
+        let rate = system_clock_rate(&rcc, get_hse_rate());
+
+        unsafe { RATES.system = rate; }
         let mut systick = systick::systick();
         // Interrupt every millisecond
         systick.set_reload_value(rate / 1000);
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use super::super::super::config::CFGR;
+        use super::super::super::defs::{CFGR_SWS_HSE, CFGR_SWS_PLL};
+        use core::mem;
+
+        // An RCC register block living in regular memory, with the switch status bits set to
+        // `sws`.
+        fn mock_rcc(sws: u32) -> RawRCC {
+            let mut rcc: RawRCC = unsafe { mem::zeroed() };
+            unsafe { *(&mut rcc.cfgr as *mut CFGR as *mut u32) = sws; }
+            rcc
+        }
+
+        #[test]
+        fn test_clock_rate_initializes_to_default_system_clock() {
+            assert_eq!(ClockRates::new().system, DEFAULT_SYSTEM_CLOCK);
+        }
+
+        #[test]
+        fn test_hse_rate_defaults_to_8mhz() {
+            assert_eq!(ClockRates::new().hse, 8_000_000);
+        }
+
+        #[test]
+        fn test_oscillator_rate_uses_hse_rate() {
+            assert_eq!(oscillator_rate(Clock::HSE, 12_000_000), Some(12_000_000));
+            assert_eq!(oscillator_rate(Clock::HSI, 12_000_000), Some(8_000_000));
+            assert_eq!(oscillator_rate(Clock::PLL, 12_000_000), None);
+        }
+
+        #[test]
+        fn test_system_clock_rate_with_hse_driving_sysclk() {
+            let rcc = mock_rcc(CFGR_SWS_HSE);
+
+            assert_eq!(system_clock_rate(&rcc, 12_000_000), 12_000_000);
+            assert_eq!(system_clock_rate(&rcc, 8_000_000), 8_000_000);
+        }
+
+        #[test]
+        fn test_system_clock_rate_with_hse_driving_pll() {
+            let mut rcc = mock_rcc(CFGR_SWS_PLL);
+            rcc.set_pll_source(Clock::HSE);
+            rcc.set_pll_prediv_factor(1);
+            rcc.set_pll_multiplier(4);
+
+            // 12 MHz / 1 * 4 = 48 MHz
+            assert_eq!(system_clock_rate(&rcc, 12_000_000), 48_000_000);
+        }
+    }
 }
 
 /// Defines available system clocks.
@@ -75,6 +205,19 @@ pub enum Clock {
     PLL,
 }
 
+impl Clock {
+    /// Get the fixed frequency in Hz of an internal oscillator. The HSE depends on the board and
+    /// the PLL on its configuration, so `None` is returned for those.
+    pub fn nominal_rate(self) -> Option<u32> {
+        match self {
+            Clock::HSI => Some(HSI_VALUE),
+            Clock::HSI48 => Some(HSI48_VALUE),
+            Clock::HSI14 => Some(HSI14_VALUE),
+            Clock::HSE | Clock::PLL => None,
+        }
+    }
+}
+
 /// The CR register only controls the PLL, HSE, and HSI clocks. If another clock is passed in as an
 /// argument to any of the methods that take a clock argument, the kernel will panic.
 #[derive(Copy, Clone, Debug)]
@@ -211,6 +354,61 @@ impl CR2 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use super::clock_rate;
+
+    #[test]
+    fn test_default_system_clock_is_hsi() {
+        assert_eq!(clock_rate::DEFAULT_SYSTEM_CLOCK, 8_000_000);
+    }
+
+    #[test]
+    fn test_pll_clock_rate() {
+        // HSI / 2 * 12 = 48 MHz
+        assert_eq!(clock_rate::pll_clock_rate(8_000_000, 2, 12), 48_000_000);
+        // HSE / 1 * 6 = 48 MHz
+        assert_eq!(clock_rate::pll_clock_rate(8_000_000, 1, 6), 48_000_000);
+    }
+
+    #[test]
+    fn test_pll_output_rate_hsi_ignores_prediv() {
+        assert_eq!(clock_rate::pll_output_rate(Clock::HSI, 1, 12), 48_000_000);
+        assert_eq!(clock_rate::pll_output_rate(Clock::HSI, 4, 12), 48_000_000);
+    }
+
+    #[test]
+    fn test_pll_output_rate_uses_prediv() {
+        assert_eq!(clock_rate::pll_output_rate_with_hse(Clock::HSE, 8_000_000, 2, 12), 48_000_000);
+        assert_eq!(clock_rate::pll_output_rate(Clock::HSI48, 6, 6), 48_000_000);
+    }
+
+    #[test]
+    fn test_nominal_rate_internal_oscillators() {
+        assert_eq!(Clock::HSI.nominal_rate(), Some(8_000_000));
+        assert_eq!(Clock::HSI14.nominal_rate(), Some(14_000_000));
+        assert_eq!(Clock::HSI48.nominal_rate(), Some(48_000_000));
+    }
+
+    #[test]
+    fn test_nominal_rate_board_dependent_clocks() {
+        assert_eq!(Clock::HSE.nominal_rate(), None);
+        assert_eq!(Clock::PLL.nominal_rate(), None);
+    }
+
+    #[test]
+    fn test_pll_output_rate_with_12mhz_hse() {
+        // 12 MHz / 1 * 4 = 48 MHz
+        assert_eq!(clock_rate::pll_output_rate_with_hse(Clock::HSE, 12_000_000, 1, 4), 48_000_000);
+        // 12 MHz / 3 * 12 = 48 MHz
+        assert_eq!(clock_rate::pll_output_rate_with_hse(Clock::HSE, 12_000_000, 3, 12), 48_000_000);
+        // The HSE rate doesn't matter when the HSI drives the PLL
+        assert_eq!(clock_rate::pll_output_rate_with_hse(Clock::HSI, 12_000_000, 1, 12), 48_000_000);
+    }
+
+    #[test]
+    fn test_pll_clock_rate_is_const() {
+        const RATE: u32 = clock_rate::pll_clock_rate(48_000_000, 4, 4);
+        assert_eq!(RATE, 48_000_000);
+    }
 
     #[test]
     fn test_cr_set_clock_pll_on() {
@@ -0,0 +1,141 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! This module provides a summary of the clock tree for debugging clock configuration.
+
+use core::fmt;
+use super::Clock;
+
+/// The state of an oscillator.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ClockState {
+    /// The oscillator has been turned on.
+    pub enabled: bool,
+    /// The oscillator is stable and ready to be used.
+    pub ready: bool,
+}
+
+/// The resolved frequencies of the clock tree, along with the state of each oscillator.
+///
+/// All frequencies are in Hz.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ClockTree {
+    /// The clock driving the system clock.
+    pub source: Clock,
+    /// The system clock frequency.
+    pub sysclk: u32,
+    /// The AHB clock frequency, SYSCLK divided by the AHB prescaler.
+    pub hclk: u32,
+    /// The APB clock frequency, HCLK divided by the APB prescaler.
+    pub pclk: u32,
+    /// The clock driving the PLL.
+    pub pll_source: Clock,
+    /// The PLL output frequency, based on its current configuration.
+    pub pll: u32,
+    /// The state of the 8 MHz HSI oscillator.
+    pub hsi: ClockState,
+    /// The state of the 48 MHz HSI oscillator.
+    pub hsi48: ClockState,
+    /// The state of the 14 MHz HSI oscillator.
+    pub hsi14: ClockState,
+    /// The state of the HSE oscillator.
+    pub hse: ClockState,
+    /// The state of the PLL.
+    pub pll_state: ClockState,
+}
+
+impl fmt::Display for ClockTree {
+    /// Formats the bus clocks like `SYSCLK=48MHz(PLL) HCLK=48MHz PCLK=24MHz`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SYSCLK={}({:?}) HCLK={} PCLK={}",
+               Rate(self.sysclk), self.source, Rate(self.hclk), Rate(self.pclk))
+    }
+}
+
+// A frequency in Hz, displayed in the largest unit it's a whole number of.
+struct Rate(u32);
+
+impl fmt::Display for Rate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let hz = self.0;
+        if hz != 0 && hz % 1_000_000 == 0 {
+            write!(f, "{}MHz", hz / 1_000_000)
+        }
+        else if hz != 0 && hz % 1_000 == 0 {
+            write!(f, "{}kHz", hz / 1_000)
+        }
+        else {
+            write!(f, "{}Hz", hz)
+        }
+    }
+}
+
+/// The on and ready flags of the oscillators and the PLL, as returned by
+/// `RCC::oscillator_status`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct OscillatorStatus {
+    /// The state of the 8 MHz HSI oscillator.
+    pub hsi: ClockState,
+    /// The state of the HSE oscillator.
+    pub hse: ClockState,
+    /// The state of the PLL.
+    pub pll: ClockState,
+    /// The state of the 48 MHz HSI oscillator.
+    pub hsi48: ClockState,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree(source: Clock, sysclk: u32, hclk: u32, pclk: u32) -> ClockTree {
+        let off = ClockState { enabled: false, ready: false };
+        ClockTree {
+            source: source,
+            sysclk: sysclk,
+            hclk: hclk,
+            pclk: pclk,
+            pll_source: Clock::HSI,
+            pll: 48_000_000,
+            hsi: off,
+            hsi48: off,
+            hsi14: off,
+            hse: off,
+            pll_state: off,
+        }
+    }
+
+    #[test]
+    fn test_clock_tree_display() {
+        let tree = tree(Clock::PLL, 48_000_000, 48_000_000, 24_000_000);
+
+        assert_eq!(format!("{}", tree), "SYSCLK=48MHz(PLL) HCLK=48MHz PCLK=24MHz");
+    }
+
+    #[test]
+    fn test_clock_tree_display_uneven_rates() {
+        let tree = tree(Clock::HSI, 8_000_000, 62_500, 7);
+
+        assert_eq!(format!("{}", tree), "SYSCLK=8MHz(HSI) HCLK=62500Hz PCLK=7Hz");
+    }
+
+    #[test]
+    fn test_rate_display_units() {
+        assert_eq!(format!("{}", Rate(1_500_000)), "1500kHz");
+        assert_eq!(format!("{}", Rate(0)), "0Hz");
+    }
+}
@@ -96,6 +96,187 @@ impl CFGR {
         self.0 &= !CFGR_PLLMUL_MASK;
         self.0 |= mask;
     }
+
+    pub fn get_ahb_prescaler(&self) -> u16 {
+        let set_bits = (self.0 & CFGR_HPRE_MASK) >> CFGR_HPRE_SHIFT;
+
+        match set_bits {
+            0b0000...0b0111 => 1,
+            0b1000 => 2,
+            0b1001 => 4,
+            0b1010 => 8,
+            0b1011 => 16,
+            0b1100 => 64,
+            0b1101 => 128,
+            0b1110 => 256,
+            _ => 512,
+        }
+    }
+
+    pub fn set_ahb_prescaler(&mut self, div: u16) {
+        let set_bits = match div {
+            1 => 0b0000,
+            2 => 0b1000,
+            4 => 0b1001,
+            8 => 0b1010,
+            16 => 0b1011,
+            64 => 0b1100,
+            128 => 0b1101,
+            256 => 0b1110,
+            512 => 0b1111,
+            _ => panic!("CFGR::set_ahb_prescaler - the prescaler must be one of 1, 2, 4, 8, 16, 64, 128, 256 or 512!"),
+        };
+
+        // Zero the register field
+        self.0 &= !CFGR_HPRE_MASK;
+        self.0 |= set_bits << CFGR_HPRE_SHIFT;
+    }
+
+    pub fn get_apb_prescaler(&self) -> u8 {
+        let set_bits = (self.0 & CFGR_PPRE_MASK) >> CFGR_PPRE_SHIFT;
+
+        match set_bits {
+            0b000...0b011 => 1,
+            0b100 => 2,
+            0b101 => 4,
+            0b110 => 8,
+            _ => 16,
+        }
+    }
+
+    pub fn set_apb_prescaler(&mut self, div: u8) {
+        let set_bits = match div {
+            1 => 0b000,
+            2 => 0b100,
+            4 => 0b101,
+            8 => 0b110,
+            16 => 0b111,
+            _ => panic!("CFGR::set_apb_prescaler - the prescaler must be one of 1, 2, 4, 8 or 16!"),
+        };
+
+        // Zero the register field
+        self.0 &= !CFGR_PPRE_MASK;
+        self.0 |= set_bits << CFGR_PPRE_SHIFT;
+    }
+
+    /* Bits 27:24 MCO[3:0]: Microcontroller clock output
+     *   Set and cleared by software.
+     *   0000: MCO output disabled, no clock on MCO
+     *   0001: Internal RC 14 MHz (HSI14) oscillator clock selected
+     *   0101: Internal RC 8 MHz (HSI) oscillator clock selected
+     *   0110: External 4-32 MHz (HSE) oscillator clock selected
+     *   0111: PLL clock selected (divided by 1 or 2, depending on PLLNODIV)
+     *   1000: Internal RC 48 MHz (HSI48) oscillator clock selected
+     */
+    pub fn get_mco_source(&self) -> Option<Clock> {
+        let set_bits = (self.0 & CFGR_MCO_MASK) >> CFGR_MCO_SHIFT;
+
+        match set_bits {
+            CFGR_MCO_HSI14 => Some(Clock::HSI14),
+            CFGR_MCO_HSI => Some(Clock::HSI),
+            CFGR_MCO_HSE => Some(Clock::HSE),
+            CFGR_MCO_PLL => Some(Clock::PLL),
+            CFGR_MCO_HSI48 => Some(Clock::HSI48),
+            _ => None,
+        }
+    }
+
+    pub fn set_mco_source(&mut self, clock: Option<Clock>) {
+        let set_bits = match clock {
+            Some(Clock::HSI14) => CFGR_MCO_HSI14,
+            Some(Clock::HSI) => CFGR_MCO_HSI,
+            Some(Clock::HSE) => CFGR_MCO_HSE,
+            Some(Clock::PLL) => CFGR_MCO_PLL,
+            Some(Clock::HSI48) => CFGR_MCO_HSI48,
+            None => 0,
+        };
+
+        // Zero the register field
+        self.0 &= !CFGR_MCO_MASK;
+        self.0 |= set_bits << CFGR_MCO_SHIFT;
+    }
+
+    /* Bits 30:28 MCOPRE[2:0]: Microcontroller Clock Output Prescaler
+     *   These bits are set and cleared by software to select the MCO prescaler division factor.
+     *   To avoid glitches, it is highly recommended to change this prescaler only when the MCO
+     *   output is disabled.
+     *   000: MCO is divided by 1 ... 111: MCO is divided by 128
+     */
+    pub fn get_mco_prescaler(&self) -> u8 {
+        let set_bits = (self.0 & CFGR_MCOPRE_MASK) >> CFGR_MCOPRE_SHIFT;
+
+        1 << set_bits
+    }
+
+    pub fn set_mco_prescaler(&mut self, div: u8) {
+        if !div.is_power_of_two() {
+            panic!("CFGR::set_mco_prescaler - the prescaler must be a power of 2 between 1..128!");
+        }
+        let set_bits = div.trailing_zeros();
+
+        // Zero the register field
+        self.0 &= !CFGR_MCOPRE_MASK;
+        self.0 |= set_bits << CFGR_MCOPRE_SHIFT;
+    }
+}
+
+/// A snapshot of the system clock configuration.
+///
+/// This can be taken with `RCC::save_config` before entering a low power mode and handed back to
+/// `RCC::restore_config` on wake up to bring the clocks back to how they were.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ClockConfig {
+    /// The clock driving the system clock.
+    pub source: Clock,
+    /// The clock driving the PLL.
+    pub pll_source: Clock,
+    /// The PLL multiplier, in the range [2..16].
+    pub pll_multiplier: u8,
+    /// The PLL prediv factor, in the range [1..16].
+    pub pll_prediv_factor: u8,
+    /// The AHB prescaler.
+    pub ahb_prescaler: u16,
+    /// The APB prescaler.
+    pub apb_prescaler: u8,
+}
+
+/// Common clock setups for board bring up, see `RCC::use_preset`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ClockPreset {
+    /// An 8 MHz crystal on the HSE, multiplied by 6 with the PLL for a 48 MHz system clock.
+    Hse8MhzTo48Mhz,
+    /// The 48 MHz HSI48 oscillator driving the system clock directly.
+    Hsi48,
+    /// The 8 MHz HSI oscillator driving the system clock, as it is coming out of reset.
+    HsiDefault8Mhz,
+}
+
+impl ClockPreset {
+    /// The clock configuration the preset sets up. The AHB and APB aren't divided. When the PLL
+    /// isn't used its settings are left at their reset values.
+    pub fn config(&self) -> ClockConfig {
+        let (source, pll_source, pll_multiplier) = match *self {
+            ClockPreset::Hse8MhzTo48Mhz => (Clock::PLL, Clock::HSE, 6),
+            ClockPreset::Hsi48 => (Clock::HSI48, Clock::HSI, 2),
+            ClockPreset::HsiDefault8Mhz => (Clock::HSI, Clock::HSI, 2),
+        };
+        ClockConfig {
+            source: source,
+            pll_source: pll_source,
+            pll_multiplier: pll_multiplier,
+            pll_prediv_factor: 1,
+            ahb_prescaler: 1,
+            apb_prescaler: 1,
+        }
+    }
+
+    /// The number of flash wait states needed at the preset's system clock rate.
+    pub fn flash_latency(&self) -> u8 {
+        match *self {
+            ClockPreset::Hse8MhzTo48Mhz | ClockPreset::Hsi48 => 1,
+            ClockPreset::HsiDefault8Mhz => 0,
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -121,6 +302,37 @@ impl CFGR2 {
     }
 }
 
+/// Defines the clocks that can drive the USB peripheral.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum UsbClockSource {
+    /// The 48 MHz HSI oscillator.
+    HSI48,
+    /// The PLL, driven by the specified clock and configured for exactly 48 MHz.
+    PLL(Clock),
+}
+
+/// Clock Configuration Register 3
+#[derive(Copy, Clone, Debug)]
+pub struct CFGR3(u32);
+
+impl CFGR3 {
+    /* Bit 7 USBSW: USB clock source selection
+     *   This bit is set and cleared by software.
+     *      0: HSI48 clock selected as USB clock source
+     *      1: PLL clock (PLLCLK) selected as USB clock source
+     */
+    pub fn set_usb_clock_source(&mut self, source: UsbClockSource) {
+        self.0 &= !CFGR3_USBSW;
+        if let UsbClockSource::PLL(_) = source {
+            self.0 |= CFGR3_USBSW;
+        }
+    }
+
+    pub fn usb_clock_is_pll(&self) -> bool {
+        self.0 & CFGR3_USBSW != 0
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -212,6 +424,55 @@ mod tests {
         cfgr.set_pll_multiplier(17);
     }
 
+    #[test]
+    fn test_cfgr_get_ahb_prescaler() {
+        assert_eq!(CFGR(0).get_ahb_prescaler(), 1);
+        assert_eq!(CFGR(0b0101 << 4).get_ahb_prescaler(), 1);
+        assert_eq!(CFGR(0b1011 << 4).get_ahb_prescaler(), 16);
+        assert_eq!(CFGR(0b1100 << 4).get_ahb_prescaler(), 64);
+        assert_eq!(CFGR(0b1111 << 4).get_ahb_prescaler(), 512);
+    }
+
+    #[test]
+    fn test_cfgr_set_ahb_prescaler() {
+        let mut cfgr = CFGR(0b1111 << 4);
+
+        cfgr.set_ahb_prescaler(64);
+        assert_eq!(cfgr.0, 0b1100 << 4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_cfgr_set_ahb_prescaler_invalid_panics() {
+        let mut cfgr = CFGR(0);
+
+        cfgr.set_ahb_prescaler(32);
+    }
+
+    #[test]
+    fn test_cfgr_get_apb_prescaler() {
+        assert_eq!(CFGR(0).get_apb_prescaler(), 1);
+        assert_eq!(CFGR(0b011 << 8).get_apb_prescaler(), 1);
+        assert_eq!(CFGR(0b101 << 8).get_apb_prescaler(), 4);
+        assert_eq!(CFGR(0b111 << 8).get_apb_prescaler(), 16);
+    }
+
+    #[test]
+    fn test_cfgr_set_apb_prescaler() {
+        let mut cfgr = CFGR(0b111 << 8);
+
+        cfgr.set_apb_prescaler(2);
+        assert_eq!(cfgr.0, 0b100 << 8);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_cfgr_set_apb_prescaler_invalid_panics() {
+        let mut cfgr = CFGR(0);
+
+        cfgr.set_apb_prescaler(3);
+    }
+
     #[test]
     fn test_cfgr2_get_pll_prediv_factor() {
         // Prediv factor field starts at 12, should get 13 out
@@ -243,4 +504,54 @@ mod tests {
 
         cfgr2.set_pll_prediv_factor(17);
     }
+
+    #[test]
+    fn test_cfgr_set_mco_source() {
+        let mut cfgr = CFGR(0);
+
+        cfgr.set_mco_source(Some(Clock::HSI48));
+        assert_eq!(cfgr.0, 0b1000 << 24);
+
+        cfgr.set_mco_source(Some(Clock::HSI));
+        assert_eq!(cfgr.0, 0b0101 << 24);
+
+        cfgr.set_mco_source(None);
+        assert_eq!(cfgr.0, 0);
+    }
+
+    #[test]
+    fn test_cfgr_get_mco_source() {
+        assert_eq!(CFGR(0b0111 << 24).get_mco_source(), Some(Clock::PLL));
+        assert_eq!(CFGR(0).get_mco_source(), None);
+    }
+
+    #[test]
+    fn test_cfgr_set_mco_prescaler() {
+        let mut cfgr = CFGR(0);
+
+        cfgr.set_mco_prescaler(16);
+        assert_eq!(cfgr.0, 0b100 << 28);
+        assert_eq!(cfgr.get_mco_prescaler(), 16);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_cfgr_set_mco_prescaler_not_power_of_two_panics() {
+        let mut cfgr = CFGR(0);
+
+        cfgr.set_mco_prescaler(3);
+    }
+
+    #[test]
+    fn test_cfgr3_set_usb_clock_source() {
+        let mut cfgr3 = CFGR3(0);
+
+        cfgr3.set_usb_clock_source(UsbClockSource::PLL(Clock::HSE));
+        assert_eq!(cfgr3.0, 0b1 << 7);
+        assert!(cfgr3.usb_clock_is_pll());
+
+        cfgr3.set_usb_clock_source(UsbClockSource::HSI48);
+        assert_eq!(cfgr3.0, 0);
+        assert!(!cfgr3.usb_clock_is_pll());
+    }
 }
@@ -15,11 +15,14 @@
 * along with this program. If not, see <http://www.gnu.org/licenses/>.
 */
 
-pub const RCC_ADDR: *const u32 = 0x4002_1000 as *const _;
+use peripheral::addresses;
+
+pub const RCC_ADDR: *const u32 = addresses::RCC_ADDR;
 
 pub const HSI_VALUE: u32 = 8_000_000;
 pub const HSE_VALUE: u32 = 8_000_000;
 pub const HSI48_VALUE: u32 = 48_000_000;
+pub const HSI14_VALUE: u32 = 14_000_000;
 
 pub const CR_OFFSET: u32 = 0x00;
 
@@ -52,6 +55,20 @@ pub const CFGR_PLLSRC_HSI48_PREDIV: u32 = CFGR_CLOCK_HSI48 << 15;
 
 pub const CFGR_PLLMUL_MASK: u32 = 0b1111 << 18;
 
+pub const CFGR_HPRE_MASK: u32 = 0b1111 << 4;
+pub const CFGR_HPRE_SHIFT: u32 = 4;
+pub const CFGR_PPRE_MASK: u32 = 0b111 << 8;
+pub const CFGR_PPRE_SHIFT: u32 = 8;
+pub const CFGR_MCO_MASK: u32 = 0b1111 << 24;
+pub const CFGR_MCO_SHIFT: u32 = 24;
+pub const CFGR_MCO_HSI14: u32 = 0b0001;
+pub const CFGR_MCO_HSI: u32 = 0b0101;
+pub const CFGR_MCO_HSE: u32 = 0b0110;
+pub const CFGR_MCO_PLL: u32 = 0b0111;
+pub const CFGR_MCO_HSI48: u32 = 0b1000;
+pub const CFGR_MCOPRE_MASK: u32 = 0b111 << 28;
+pub const CFGR_MCOPRE_SHIFT: u32 = 28;
+
 // AHBENR Bit Offsets
 pub const AHBENR_OFFSET: u32 = 0x14;
 pub const TSCEN: u32 = 0b1 << 24;
@@ -102,10 +119,29 @@ pub const SPI1EN: u32 = 0b1 << 12;
 pub const ADCEN: u32 = 0b1 << 9;
 pub const SYSCFGCOMPEN: u32 = 0b1 << 0;
 
+// BDCR Bit Offsets
+pub const BDCR_OFFSET: u32 = 0x20;
+pub const BDCR_LSEON: u32 = 0b1;
+pub const BDCR_LSERDY: u32 = 0b1 << 1;
+pub const BDCR_LSEDRV_SHIFT: u32 = 3;
+pub const BDCR_LSEDRV_MASK: u32 = 0b11 << BDCR_LSEDRV_SHIFT;
+pub const LSEDRV_LOW: u32 = 0b00;
+pub const LSEDRV_MEDIUM_HIGH: u32 = 0b01;
+pub const LSEDRV_MEDIUM_LOW: u32 = 0b10;
+pub const LSEDRV_HIGH: u32 = 0b11;
+pub const BDCR_BDRST: u32 = 0b1 << 16;
+
 // CFGR2 Bit Offsets
 pub const CFGR2_OFFSET: u32 = 0x2C;
 pub const CFGR2_PREDIV_MASK: u32 = 0b1111;
 
+// CFGR3 Bit Offsets
+pub const CFGR3_OFFSET: u32 = 0x30;
+pub const CFGR3_USBSW: u32 = 0b1 << 7;
+
+// The USB peripheral needs exactly 48 MHz
+pub const USB_CLOCK_RATE: u32 = 48_000_000;
+
 // CR2 Bit Offsets
 pub const CR2_OFFSET: u32 = 0x34;
 pub const CR2_HSI14ON: u32 = 0b1 << 0;
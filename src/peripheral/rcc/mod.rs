@@ -21,25 +21,74 @@
 mod clock_control;
 mod config;
 mod enable;
+mod clock_tree;
+mod backup;
+mod scoped;
 mod defs;
 
 use core::ops::{Deref, DerefMut};
+use core::ptr;
 use volatile::Volatile;
-use arm::asm::dsb;
+use arm::asm::data_barrier;
 use self::defs::*;
+use super::{Control, Field};
+use super::{flash, pwr, wait_until};
+use super::flash::RawFLASH;
+use time::Hertz;
 
 use self::clock_control::{CR, CR2};
-use self::config::{CFGR, CFGR2};
+use self::config::{CFGR, CFGR2, CFGR3};
 use self::enable::{AHBENR, APBENR1, APBENR2};
+use self::backup::BDCR;
 
 pub use self::clock_control::Clock;
+pub use self::config::{ClockConfig, ClockPreset, UsbClockSource};
+pub use self::clock_tree::{ClockTree, ClockState, OscillatorStatus};
+pub use self::clock_control::clock_rate::{DEFAULT_SYSTEM_CLOCK, pll_clock_rate};
+pub use self::clock_control::clock_rate::{set_hse_rate, get_hse_rate};
 pub use self::enable::Peripheral;
+pub use self::backup::LseDrive;
+pub use self::scoped::PeripheralClockGuard;
+
+// The number of times to poll a clock's ready flag before giving up on it.
+const CLOCK_READY_ATTEMPTS: u32 = 100_000;
+
+/// Errors that can be reported while configuring the clocks.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ClockError {
+    /// The clock was turned on but never became ready.
+    NotReady(Clock),
+    /// The PLL can't produce the required frequency exactly when driven by the clock.
+    InexactFrequency(Clock),
+    /// The PLL needs to be reconfigured, but it's driving the system clock.
+    PllInUse,
+    /// The PLL configuration can't be changed while the PLL is on.
+    PllOn,
+    /// The system clock was switched to the clock, but the SWS bits never showed it taking over.
+    /// This usually means the clock wasn't on and ready.
+    SwitchFailed(Clock),
+    /// The LSE configuration can't be changed while the LSE is on.
+    LseOn,
+    /// The PLL multiplier is outside of the range [2..16].
+    InvalidPllMultiplier(u8),
+    /// The PLL prediv factor is outside of the range [1..16].
+    InvalidPllPredivFactor(u8),
+}
 
 /// Returns an instance of the RCC struct so it can be used to modify clock configuration.
 pub fn rcc() -> RCC {
     RCC::rcc()
 }
 
+/// A copy of the peripheral clock enable registers, taken with `RCC::enable_snapshot` and
+/// written back with `RCC::restore_enable`.
+#[derive(Copy, Clone, Debug)]
+pub struct EnableSnapshot {
+    ahbenr: AHBENR,
+    apbenr1: APBENR1,
+    apbenr2: APBENR2,
+}
+
 #[derive(Copy, Clone, Debug)]
 #[repr(C)]
 #[doc(hidden)]
@@ -52,11 +101,11 @@ pub struct RawRCC {
     ahbenr: AHBENR,
     apbenr2: APBENR2,
     apbenr1: APBENR1,
-    bdcr: u32,
+    bdcr: BDCR,
     csr: u32,
     ahbrstr: u32,
     cfgr2: CFGR2,
-    cfgr3: u32,
+    cfgr3: CFGR3,
     cr2: CR2,
 }
 
@@ -86,6 +135,12 @@ impl DerefMut for RCC {
     }
 }
 
+impl Control for RawRCC {
+    fn mem_addr(&self) -> *const u32 {
+        self as *const RawRCC as *const u32
+    }
+}
+
 impl RawRCC {
     /// Enable the specified clock.
     pub fn enable_clock(&mut self, clock: Clock) {
@@ -132,8 +187,36 @@ impl RawRCC {
         // We need a memory barrier here since the hardware is writing to the system clock bit
         // the barrier ensures that the write to the control register takes effect before we
         // try to access the clock rate
-        unsafe { dsb(); }
+        data_barrier();
+        clock_control::clock_rate::update_system_clock_rate();
+    }
+
+    /// Set the system clock source, then wait for the hardware to report that the switch has
+    /// happened. If it hasn't after a bounded wait `ClockError::SwitchFailed` is returned, and
+    /// the system keeps running off of the old clock. The system clock rate is updated to match
+    /// whichever clock is actually in use.
+    ///
+    /// The system clock can only be run off of the HSI, HSE, PLL, or HSI48 clocks. If another
+    /// clock is specified, the kernel will panic.
+    pub fn set_system_clock_source_checked(&mut self, clock: Clock) -> Result<(), ClockError> {
+        let result = self.switch_system_clock(clock, CLOCK_READY_ATTEMPTS);
         clock_control::clock_rate::update_system_clock_rate();
+        result
+    }
+
+    // Write the SW bits and poll the SWS bits up to `attempts` times for the switch to happen. On
+    // a timeout the SW bits are put back to the clock in use, so the switch can't still happen
+    // later on.
+    fn switch_system_clock(&mut self, clock: Clock, attempts: u32) -> Result<(), ClockError> {
+        let previous = self.get_system_clock_source();
+        self.cfgr.set_system_clock_source(clock);
+        data_barrier();
+        if !wait_until(attempts, || self.get_system_clock_source() == clock) {
+            self.cfgr.set_system_clock_source(previous);
+            data_barrier();
+            return Err(ClockError::SwitchFailed(clock));
+        }
+        Ok(())
     }
 
     /// Get the clock driving the PLL.
@@ -158,6 +241,19 @@ impl RawRCC {
         self.cfgr.set_pll_multiplier(mul);
     }
 
+    /// Set the PLL multiplier, returning an error instead of panicking if the multiplier is
+    /// outside of the range [2..16] or if the PLL is on.
+    pub fn try_set_pll_multiplier(&mut self, mul: u8) -> Result<(), ClockError> {
+        if mul < 2 || mul > 16 {
+            return Err(ClockError::InvalidPllMultiplier(mul));
+        }
+        if self.clock_is_on(Clock::PLL) {
+            return Err(ClockError::PllOn);
+        }
+        self.set_pll_multiplier(mul);
+        Ok(())
+    }
+
     /// Get the current prediv factor for the PLL. The factor is in a range of [1..16].
     pub fn get_pll_prediv_factor(&self) -> u8 {
         self.cfgr2.get_pll_prediv_factor()
@@ -169,9 +265,424 @@ impl RawRCC {
         self.cfgr2.set_pll_prediv_factor(factor);
     }
 
+    /// Set the PLL prediv factor, returning an error instead of panicking if the factor is
+    /// outside of the range [1..16] or if the PLL is on.
+    pub fn try_set_pll_prediv_factor(&mut self, factor: u8) -> Result<(), ClockError> {
+        if factor < 1 || factor > 16 {
+            return Err(ClockError::InvalidPllPredivFactor(factor));
+        }
+        if self.clock_is_on(Clock::PLL) {
+            return Err(ClockError::PllOn);
+        }
+        self.set_pll_prediv_factor(factor);
+        Ok(())
+    }
+
+    /// Turn the PLL on. Its configuration can't be changed while it's on, see
+    /// `try_set_pll_multiplier` and `try_set_pll_prediv_factor`.
+    pub fn enable_pll(&mut self) {
+        self.enable_clock(Clock::PLL);
+    }
+
+    /// Turn the PLL off so it can be reconfigured. Returns false, leaving the PLL on, if it's
+    /// driving the system clock. The PLL takes a moment to stop, wait for `pll_is_ready` to
+    /// return false before changing its configuration.
+    pub fn disable_pll(&mut self) -> bool {
+        if self.get_system_clock_source() == Clock::PLL {
+            return false;
+        }
+        self.disable_clock(Clock::PLL)
+    }
+
+    /// Return true if the PLL is on (PLLON is set), it may not be locked yet.
+    pub fn pll_is_on(&self) -> bool {
+        self.clock_is_on(Clock::PLL)
+    }
+
+    /// Return true if the PLL is locked and can be used as the system clock.
+    pub fn pll_is_ready(&self) -> bool {
+        self.clock_is_ready(Clock::PLL)
+    }
+
+    /// Get the AHB prescaler, this is one of 1, 2, 4, 8, 16, 64, 128, 256 or 512.
+    pub fn get_ahb_prescaler(&self) -> u16 {
+        self.cfgr.get_ahb_prescaler()
+    }
+
+    /// Set the AHB prescaler. The prescaler MUST be one of 1, 2, 4, 8, 16, 64, 128, 256 or 512. If
+    /// it is not, the kernel will panic.
+    pub fn set_ahb_prescaler(&mut self, div: u16) {
+        self.cfgr.set_ahb_prescaler(div);
+    }
+
+    /// Get the APB prescaler, this is one of 1, 2, 4, 8 or 16.
+    pub fn get_apb_prescaler(&self) -> u8 {
+        self.cfgr.get_apb_prescaler()
+    }
+
+    /// Set the APB prescaler. The prescaler MUST be one of 1, 2, 4, 8 or 16. If it is not, the
+    /// kernel will panic.
+    pub fn set_apb_prescaler(&mut self, div: u8) {
+        self.cfgr.set_apb_prescaler(div);
+    }
+
+    /// Take a snapshot of the current clock configuration.
+    pub fn save_config(&self) -> ClockConfig {
+        ClockConfig {
+            source: self.get_system_clock_source(),
+            pll_source: self.get_pll_source(),
+            pll_multiplier: self.get_pll_multiplier(),
+            pll_prediv_factor: self.get_pll_prediv_factor(),
+            ahb_prescaler: self.get_ahb_prescaler(),
+            apb_prescaler: self.get_apb_prescaler(),
+        }
+    }
+
+    /// Reprogram the clocks with a configuration taken by `save_config`.
+    ///
+    /// When waking up from Stop mode the system clock falls back to the HSI and the PLL and HSE are
+    /// turned off. This turns the needed oscillator back on, reprograms the PLL if it drives the
+    /// system clock, and switches the system clock over once everything is ready. Each step is a
+    /// bounded wait, if a clock never becomes ready `ClockError::NotReady` is returned, and
+    /// `ClockError::SwitchFailed` if the switch itself doesn't happen. The system clock rate is
+    /// updated to match whichever clock is actually in use.
+    pub fn restore_config(&mut self, config: ClockConfig) -> Result<(), ClockError> {
+        let result = self.apply_config(config);
+        clock_control::clock_rate::update_system_clock_rate();
+        result
+    }
+
+    // The register writes for `restore_config`, leaving the system clock rate alone.
+    fn apply_config(&mut self, config: ClockConfig) -> Result<(), ClockError> {
+        let oscillator = match config.source {
+            Clock::PLL => config.pll_source,
+            clock => clock,
+        };
+        self.enable_clock(oscillator);
+        if !wait_until(CLOCK_READY_ATTEMPTS, || self.clock_is_ready(oscillator)) {
+            return Err(ClockError::NotReady(oscillator));
+        }
+
+        if config.source == Clock::PLL {
+            // The PLL can't be configured while it's on, so get off of it first
+            if self.get_system_clock_source() == Clock::PLL {
+                self.enable_clock(Clock::HSI);
+                if !wait_until(CLOCK_READY_ATTEMPTS, || self.clock_is_ready(Clock::HSI)) {
+                    return Err(ClockError::NotReady(Clock::HSI));
+                }
+                if let Err(err) = self.switch_system_clock(Clock::HSI, CLOCK_READY_ATTEMPTS) {
+                    return Err(err);
+                }
+            }
+            self.disable_clock(Clock::PLL);
+            if !wait_until(CLOCK_READY_ATTEMPTS, || !self.clock_is_ready(Clock::PLL)) {
+                return Err(ClockError::NotReady(Clock::PLL));
+            }
+
+            self.write_pll_config(&config);
+
+            self.enable_clock(Clock::PLL);
+            if !wait_until(CLOCK_READY_ATTEMPTS, || self.clock_is_ready(Clock::PLL)) {
+                return Err(ClockError::NotReady(Clock::PLL));
+            }
+        }
+
+        self.write_prescalers(&config);
+        self.switch_system_clock(config.source, CLOCK_READY_ATTEMPTS)
+    }
+
+    /// Bring the clocks up in one of the common board configurations.
+    ///
+    /// The preset's oscillator is turned on and, for the PLL presets, the PLL is configured and
+    /// started. The flash wait states are raised (with the prefetch buffer turned on) before the
+    /// system clock is switched to a faster clock, and lowered only once it has switched to a
+    /// slower one. The waits are bounded as in `restore_config`, which gives the errors returned
+    /// if the preset's clocks can't be brought up (e.g. there is no HSE crystal fitted).
+    pub fn use_preset(&mut self, preset: ClockPreset) -> Result<(), ClockError> {
+        let mut flash = flash::flash();
+        let result = self.apply_preset(preset, &mut flash);
+        clock_control::clock_rate::update_system_clock_rate();
+        result
+    }
+
+    // The register writes for `use_preset`, leaving the system clock rate alone. On an error the
+    // raised wait states are kept, they're safe at any clock rate.
+    fn apply_preset(&mut self, preset: ClockPreset, flash: &mut RawFLASH)
+        -> Result<(), ClockError> {
+        let latency = preset.flash_latency();
+
+        if latency > flash.get_latency() {
+            // We're at 24 MHz or less here, so the prefetch buffer can still be turned on
+            flash.set_prefetch(true);
+            flash.set_latency(latency);
+        }
+        if let Err(err) = self.apply_config(preset.config()) {
+            return Err(err);
+        }
+        if latency < flash.get_latency() {
+            flash.set_latency(latency);
+        }
+        Ok(())
+    }
+
+    /// Output a clock on the MCO pin.
+    ///
+    /// If the clock is off it is turned on first, and it's only routed to the MCO pin once it's
+    /// ready, so the pin never outputs a dead clock. If the clock doesn't become ready it's left
+    /// on, the MCO output is left unchanged, and `ClockError::NotReady` is returned. The PLL is
+    /// output divided by 2.
+    pub fn set_mco_source(&mut self, clock: Clock) -> Result<(), ClockError> {
+        if !self.clock_is_on(clock) {
+            self.enable_clock(clock);
+        }
+        if !wait_until(CLOCK_READY_ATTEMPTS, || self.clock_is_ready(clock)) {
+            return Err(ClockError::NotReady(clock));
+        }
+        self.cfgr.set_mco_source(Some(clock));
+        Ok(())
+    }
+
+    /// Set up an exact 48 MHz clock for the USB peripheral.
+    ///
+    /// For `UsbClockSource::HSI48` the HSI48 is turned on and selected once it's ready. For
+    /// `UsbClockSource::PLL` the PLL is configured for exactly 48 MHz from the specified clock,
+    /// since USB doesn't tolerate any frequency error. If no prediv factor and multiplier give
+    /// exactly 48 MHz then `ClockError::InexactFrequency` is returned before anything is changed.
+    ///
+    /// If the PLL already runs at 48 MHz from the clock it's selected as is. Otherwise it has to
+    /// be turned off to reconfigure it, so `ClockError::PllInUse` is returned if it's driving the
+    /// system clock.
+    pub fn configure_usb_clock(&mut self, source: UsbClockSource) -> Result<(), ClockError> {
+        let clock = match source {
+            UsbClockSource::HSI48 => Clock::HSI48,
+            UsbClockSource::PLL(pll_source) => {
+                let (prediv, mul) = match usb_pll_factors(pll_source) {
+                    Some(factors) => factors,
+                    None => return Err(ClockError::InexactFrequency(pll_source)),
+                };
+                if let Err(err) = self.configure_pll(pll_source, prediv, mul) {
+                    return Err(err);
+                }
+                Clock::PLL
+            },
+        };
+        if !self.clock_is_on(clock) {
+            self.enable_clock(clock);
+        }
+        if !wait_until(CLOCK_READY_ATTEMPTS, || self.clock_is_ready(clock)) {
+            return Err(ClockError::NotReady(clock));
+        }
+        self.cfgr3.set_usb_clock_source(source);
+        Ok(())
+    }
+
+    // Set the PLL up with the specified source and factors, leaving it off, unless it's already
+    // running with a configuration that gives the same rate.
+    fn configure_pll(&mut self, source: Clock, prediv: u8, mul: u8) -> Result<(), ClockError> {
+        let rate = clock_control::clock_rate::pll_output_rate(source, prediv as u32, mul as u32);
+        if self.clock_is_on(Clock::PLL) && self.get_pll_source() == source &&
+            self.clock_tree().pll == rate {
+            return Ok(());
+        }
+        if self.get_system_clock_source() == Clock::PLL {
+            return Err(ClockError::PllInUse);
+        }
+        if !self.clock_is_on(source) {
+            self.enable_clock(source);
+        }
+        if !wait_until(CLOCK_READY_ATTEMPTS, || self.clock_is_ready(source)) {
+            return Err(ClockError::NotReady(source));
+        }
+        self.disable_clock(Clock::PLL);
+        if !wait_until(CLOCK_READY_ATTEMPTS, || !self.clock_is_ready(Clock::PLL)) {
+            return Err(ClockError::NotReady(Clock::PLL));
+        }
+        self.set_pll_source(source);
+        self.set_pll_prediv_factor(prediv);
+        self.set_pll_multiplier(mul);
+        Ok(())
+    }
+
+    /// Reset the backup domain, which holds the RTC, its clock selection and the LSE settings.
+    ///
+    /// The RTC clock source can only be changed after a backup domain reset. The backup domain is
+    /// write protected out of reset, so the power interface is enabled and backup domain write
+    /// access is turned on first. Access is left on afterwards so the RTC can be reconfigured.
+    pub fn reset_backup_domain(&mut self) {
+        self.enable_peripheral(Peripheral::PowerInterface);
+        pwr::pwr().enable_backup_access();
+        self.pulse_backup_domain_reset();
+    }
+
+    /// Set the drive capability of the LSE oscillator. The drive can only be changed while the
+    /// LSE is off, `ClockError::LseOn` is returned otherwise.
+    ///
+    /// The LSE settings are in the backup domain, so backup domain write access has to be turned
+    /// on through the PWR first.
+    ///
+    /// The STM32F0 has no clock security system for the LSE, only the HSE one in `CR`, so there
+    /// is no LSE CSS to configure alongside the drive.
+    pub fn set_lse_drive(&mut self, level: LseDrive) -> Result<(), ClockError> {
+        if self.bdcr.is_lse_on() {
+            return Err(ClockError::LseOn);
+        }
+        self.bdcr.set_lse_drive(level);
+        Ok(())
+    }
+
+    /// Get the drive capability of the LSE oscillator.
+    pub fn get_lse_drive(&self) -> LseDrive {
+        self.bdcr.get_lse_drive()
+    }
+
+    fn pulse_backup_domain_reset(&mut self) {
+        // Both writes have to reach the register, plain stores could be merged into one
+        for bdcr in &backup_domain_reset_sequence(self.bdcr) {
+            unsafe { ptr::write_volatile(&mut self.bdcr, *bdcr) };
+        }
+    }
+
+    /// Get the clock being output on the MCO pin, if any.
+    pub fn get_mco_source(&self) -> Option<Clock> {
+        self.cfgr.get_mco_source()
+    }
+
+    /// Stop outputting a clock on the MCO pin.
+    pub fn disable_mco(&mut self) {
+        self.cfgr.set_mco_source(None);
+    }
+
+    /// Set the division factor for the MCO output, it must be a power of 2 between [1..128] or
+    /// the kernel will panic.
+    pub fn set_mco_prescaler(&mut self, div: u8) {
+        self.cfgr.set_mco_prescaler(div);
+    }
+
+    /// Get the division factor for the MCO output.
+    pub fn get_mco_prescaler(&self) -> u8 {
+        self.cfgr.get_mco_prescaler()
+    }
+
+    /// Get the frequency expected on the MCO pin, after the prescaler. Returns 0 if the MCO
+    /// output is disabled.
+    pub fn get_mco_rate(&self) -> Hertz {
+        let rate = match self.get_mco_source() {
+            Some(Clock::HSI) => HSI_VALUE,
+            Some(Clock::HSI14) => HSI14_VALUE,
+            Some(Clock::HSI48) => HSI48_VALUE,
+            Some(Clock::HSE) => get_hse_rate(),
+            Some(Clock::PLL) => self.clock_tree().pll / 2,
+            None => 0,
+        };
+        Hertz(rate / self.get_mco_prescaler() as u32)
+    }
+
+    /// Get a summary of the clock tree, with the frequency of each clock resolved from the current
+    /// register values.
+    pub fn clock_tree(&self) -> ClockTree {
+        self.clock_tree_with_hse(get_hse_rate())
+    }
+
+    fn clock_tree_with_hse(&self, hse_rate: u32) -> ClockTree {
+        let source = self.get_system_clock_source();
+        let pll_source = self.get_pll_source();
+        let pll = clock_control::clock_rate::pll_output_rate_with_hse(pll_source, hse_rate,
+            self.get_pll_prediv_factor() as u32, self.get_pll_multiplier() as u32);
+        let sysclk = match source {
+            Clock::HSI => HSI_VALUE,
+            Clock::HSE => hse_rate,
+            Clock::HSI48 => HSI48_VALUE,
+            Clock::PLL => pll,
+            Clock::HSI14 => panic!("RCC::clock_tree - invalid clock for the system clock!"),
+        };
+        let hclk = sysclk / self.get_ahb_prescaler() as u32;
+        let pclk = hclk / self.get_apb_prescaler() as u32;
+
+        ClockTree {
+            source: source,
+            sysclk: sysclk,
+            hclk: hclk,
+            pclk: pclk,
+            pll_source: pll_source,
+            pll: pll,
+            hsi: self.clock_state(Clock::HSI),
+            hsi48: self.clock_state(Clock::HSI48),
+            hsi14: self.clock_state(Clock::HSI14),
+            hse: self.clock_state(Clock::HSE),
+            pll_state: self.clock_state(Clock::PLL),
+        }
+    }
+
+    /// Get the on and ready flags of the HSI, HSE, PLL and HSI48 in one call.
+    ///
+    /// CR and CR2 are each read only once, so the flags from each register are consistent with
+    /// each other.
+    pub fn oscillator_status(&self) -> OscillatorStatus {
+        let cr = self.cr;
+        let cr2 = self.cr2;
+
+        OscillatorStatus {
+            hsi: ClockState {
+                enabled: cr.clock_is_on(Clock::HSI),
+                ready: cr.clock_is_ready(Clock::HSI),
+            },
+            hse: ClockState {
+                enabled: cr.clock_is_on(Clock::HSE),
+                ready: cr.clock_is_ready(Clock::HSE),
+            },
+            pll: ClockState {
+                enabled: cr.clock_is_on(Clock::PLL),
+                ready: cr.clock_is_ready(Clock::PLL),
+            },
+            hsi48: ClockState {
+                enabled: cr2.clock_is_on(Clock::HSI48),
+                ready: cr2.clock_is_ready(Clock::HSI48),
+            },
+        }
+    }
+
+    fn clock_state(&self, clock: Clock) -> ClockState {
+        ClockState {
+            enabled: self.clock_is_on(clock),
+            ready: self.clock_is_ready(clock),
+        }
+    }
+
+    fn write_pll_config(&mut self, config: &ClockConfig) {
+        self.set_pll_source(config.pll_source);
+        self.set_pll_multiplier(config.pll_multiplier);
+        self.set_pll_prediv_factor(config.pll_prediv_factor);
+    }
+
+    fn write_prescalers(&mut self, config: &ClockConfig) {
+        self.set_ahb_prescaler(config.ahb_prescaler);
+        self.set_apb_prescaler(config.apb_prescaler);
+    }
+
+    /// Register the frequency of the HSE oscillator on this board, as with `set_hse_rate`, then
+    /// recalculate the system clock rate in case the HSE is already driving it.
+    pub fn set_hse_frequency(&mut self, hz: u32) {
+        set_hse_rate(hz);
+        clock_control::clock_rate::update_system_clock_rate();
+    }
+
     /// Get the rate of the current system clock.
-    pub fn get_system_clock_rate(&self) -> u32 {
-        clock_control::clock_rate::get_system_clock_rate()
+    pub fn get_system_clock_rate(&self) -> Hertz {
+        Hertz(clock_control::clock_rate::get_system_clock_rate())
+    }
+
+    /// Get the rate of the clock feeding the specified peripheral.
+    ///
+    /// Peripherals on the AHB run off of HCLK, and peripherals on the APB run off of PCLK. Both are
+    /// derived from the current system clock rate and the bus prescalers.
+    pub fn get_peripheral_clock_rate(&self, peripheral: Peripheral) -> Hertz {
+        let hclk = self.get_system_clock_rate().0 / self.get_ahb_prescaler() as u32;
+        if self.ahbenr.serves_peripheral(peripheral) {
+            Hertz(hclk)
+        }
+        else {
+            Hertz(hclk / self.get_apb_prescaler() as u32)
+        }
     }
 
     /// Enable a peripheral
@@ -191,6 +702,57 @@ impl RawRCC {
         }
     }
 
+    /// Enable several peripherals at once.
+    ///
+    /// The peripherals are grouped by the enable register that controls them, and each register
+    /// is written at most once, rather than once per peripheral.
+    pub fn enable_peripherals(&mut self, peripherals: &[Peripheral]) {
+        let (ahb, apb1, apb2) = self.enable_masks(peripherals);
+
+        let registers = [(AHBENR_OFFSET, ahb), (APBENR1_OFFSET, apb1), (APBENR2_OFFSET, apb2)];
+        for &(offset, mask) in &registers {
+            if mask != 0 {
+                self.modify_bits(offset, mask, mask);
+            }
+        }
+    }
+
+    /// Turn off the clock for every peripheral except the ones in `keep`.
+    ///
+    /// The flash interface, SRAM and SYSCFG clocks are needed by the system itself, so they are
+    /// always kept on whether or not they're in the list.
+    pub fn disable_unused_peripherals(&mut self, keep: &[Peripheral]) {
+        let (ahb, apb1, apb2) = self.enable_masks(keep);
+        let (sys_ahb, sys_apb1, sys_apb2) = self.enable_masks(&SYSTEM_PERIPHERALS);
+
+        self.modify_bits(AHBENR_OFFSET, !(ahb | sys_ahb), 0);
+        self.modify_bits(APBENR1_OFFSET, !(apb1 | sys_apb1), 0);
+        self.modify_bits(APBENR2_OFFSET, !(apb2 | sys_apb2), 0);
+    }
+
+    // Group the enable bits for the peripherals by the AHB, APB1 and APB2 register that holds
+    // them.
+    fn enable_masks(&self, peripherals: &[Peripheral]) -> (u32, u32, u32) {
+        let (mut ahb, mut apb1, mut apb2) = (0, 0, 0);
+
+        for &peripheral in peripherals {
+            if self.ahbenr.serves_peripheral(peripheral) {
+                ahb |= peripheral.mask();
+            }
+            else if self.apbenr1.serves_peripheral(peripheral) {
+                apb1 |= peripheral.mask();
+            }
+            else if self.apbenr2.serves_peripheral(peripheral) {
+                apb2 |= peripheral.mask();
+            }
+            else {
+                panic!("RCC::enable_masks - specified peripheral not served, did you
+                forget to add it to a control register?");
+            }
+        }
+        (ahb, apb1, apb2)
+    }
+
     /// Turn off clock for the specified peripheral.
     pub fn disable_peripheral(&mut self, peripheral: Peripheral) {
         if self.ahbenr.serves_peripheral(peripheral) {
@@ -208,6 +770,23 @@ impl RawRCC {
         }
     }
 
+    /// Take a snapshot of which peripheral clocks are enabled.
+    pub fn enable_snapshot(&self) -> EnableSnapshot {
+        EnableSnapshot {
+            ahbenr: self.ahbenr,
+            apbenr1: self.apbenr1,
+            apbenr2: self.apbenr2,
+        }
+    }
+
+    /// Write a snapshot of the peripheral clock enables back, every peripheral clock is turned
+    /// on or off to match the snapshot.
+    pub fn restore_enable(&mut self, snapshot: EnableSnapshot) {
+        self.ahbenr = snapshot.ahbenr;
+        self.apbenr1 = snapshot.apbenr1;
+        self.apbenr2 = snapshot.apbenr2;
+    }
+
     /// Check if clock is turned on for specified peripheral.
     pub fn peripheral_is_enabled(&self, peripheral: Peripheral) -> bool {
         if self.ahbenr.serves_peripheral(peripheral) {
@@ -224,4 +803,736 @@ impl RawRCC {
             forget to add it to a control register?");
         }
     }
+
+    /// Check if a peripheral is held in reset by its bit in the AHBRSTR, APB1RSTR or APB2RSTR
+    /// register. The reset bits sit at the same positions as the enable bits.
+    pub fn peripheral_is_in_reset(&self, peripheral: Peripheral) -> bool {
+        let rstr = if self.ahbenr.serves_peripheral(peripheral) {
+            self.ahbrstr
+        }
+        else if self.apbenr1.serves_peripheral(peripheral) {
+            self.apb1rstr
+        }
+        else if self.apbenr2.serves_peripheral(peripheral) {
+            self.apb2rstr
+        }
+        else {
+            panic!("RCC::peripheral_is_in_reset - specified peripheral not served, did you
+            forget to add it to a control register?");
+        };
+        rstr & peripheral.mask() != 0
+    }
+
+    /// Check if a peripheral can actually be used, meaning its clock is enabled and it isn't held
+    /// in reset. This is meant as a precondition check for drivers.
+    ///
+    /// The AHB and APB clocks can't be stopped on their own on this part, they run whenever the
+    /// core does, so a peripheral held in reset is the only way the bus side can be unusable.
+    pub fn peripheral_clock_is_usable(&self, peripheral: Peripheral) -> bool {
+        clock_is_usable(self.peripheral_is_enabled(peripheral),
+                        self.peripheral_is_in_reset(peripheral))
+    }
+}
+
+fn clock_is_usable(enabled: bool, in_reset: bool) -> bool {
+    enabled && !in_reset
+}
+
+// Peripherals the system can't run without, these are never turned off by
+// `disable_unused_peripherals`.
+const SYSTEM_PERIPHERALS: [Peripheral; 3] = [
+    Peripheral::FLITF,
+    Peripheral::SRAMInterface,
+    Peripheral::SysCfgComp,
+];
+
+// Find the PLL prediv factor and multiplier that give exactly the USB clock rate from the clock.
+fn usb_pll_factors(source: Clock) -> Option<(u8, u8)> {
+    match source {
+        // The HSI is always divided by 2 before the PLL, the prediv factor doesn't apply
+        Clock::HSI => exact_pll_factors(HSI_VALUE / 2, 1, USB_CLOCK_RATE),
+        Clock::HSE => exact_pll_factors(get_hse_rate(), 16, USB_CLOCK_RATE),
+        Clock::HSI48 => exact_pll_factors(HSI48_VALUE, 16, USB_CLOCK_RATE),
+        _ => None,
+    }
+}
+
+// Search for a prediv factor in [1..max_prediv] and a multiplier in [2..16] that give exactly
+// `target` from `input_rate`, preferring the smallest prediv factor. A factor that doesn't
+// divide the input evenly would give a slightly wrong rate, so those are skipped.
+fn exact_pll_factors(input_rate: u32, max_prediv: u8, target: u32) -> Option<(u8, u8)> {
+    for prediv in 1..max_prediv + 1 {
+        if input_rate % prediv as u32 != 0 {
+            continue;
+        }
+        let divided = input_rate / prediv as u32;
+        for mul in 2..17 {
+            if divided * mul == target {
+                return Some((prediv, mul as u8));
+            }
+        }
+    }
+    None
+}
+
+// The BDCR writes that pulse the backup domain reset, BDRST is set and then the register is
+// cleared. Writing the old RTC and LSE settings back on release would undo the reset.
+fn backup_domain_reset_sequence(bdcr: BDCR) -> [BDCR; 2] {
+    let mut assert = bdcr;
+    assert.set_backup_domain_reset(true);
+    [assert, BDCR::cleared()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use peripheral::raw_word;
+    use core::mem;
+    use time::Megahertz;
+
+    // An RCC register block living in regular memory, with the switch status bits reporting that
+    // the system clock is running off of the PLL.
+    fn mock_rcc() -> RawRCC {
+        let mut rcc: RawRCC = unsafe { mem::zeroed() };
+        unsafe { *(&mut rcc.cfgr as *mut CFGR as *mut u32) = CFGR_SWS_PLL; }
+        rcc
+    }
+
+    #[test]
+    fn test_set_mco_source_enables_hsi48_first() {
+        let mut rcc = mock_rcc();
+        // HSI48 is off, but reports ready as soon as it's checked
+        unsafe { *(&mut rcc.cr2 as *mut CR2 as *mut u32) = CR2_HSI48RDY; }
+
+        assert_eq!(rcc.set_mco_source(Clock::HSI48), Ok(()));
+        assert!(rcc.clock_is_on(Clock::HSI48));
+        assert_eq!(rcc.get_mco_source(), Some(Clock::HSI48));
+    }
+
+    #[test]
+    fn test_set_mco_source_clock_never_ready() {
+        let mut rcc = mock_rcc();
+
+        assert_eq!(rcc.set_mco_source(Clock::HSI48), Err(ClockError::NotReady(Clock::HSI48)));
+        assert!(rcc.clock_is_on(Clock::HSI48));
+        assert_eq!(rcc.get_mco_source(), None);
+    }
+
+    #[test]
+    fn test_mco_rate_applies_prescaler() {
+        let mut rcc = mock_rcc();
+        unsafe { *(&mut rcc.cr2 as *mut CR2 as *mut u32) = CR2_HSI48RDY; }
+        rcc.set_mco_source(Clock::HSI48).unwrap();
+        rcc.set_mco_prescaler(8);
+
+        assert_eq!(rcc.get_mco_rate(), Hertz(6_000_000));
+
+        rcc.disable_mco();
+        assert_eq!(rcc.get_mco_rate(), Hertz(0));
+    }
+
+    #[test]
+    fn test_exact_pll_factors() {
+        // 8 MHz * 6
+        assert_eq!(exact_pll_factors(8_000_000, 16, 48_000_000), Some((1, 6)));
+        // 4 MHz * 12, the HSI after its fixed divide by 2
+        assert_eq!(exact_pll_factors(4_000_000, 1, 48_000_000), Some((1, 12)));
+        // 48 MHz / 2 * 2
+        assert_eq!(exact_pll_factors(48_000_000, 16, 48_000_000), Some((2, 2)));
+        // 25 MHz / 5 = 5 MHz, and no multiplier gives 48 MHz from there
+        assert_eq!(exact_pll_factors(25_000_000, 16, 48_000_000), None);
+    }
+
+    #[test]
+    fn test_exact_pll_factors_skips_uneven_division() {
+        // 14.7456 MHz / 3 * 10 truncates to 49.152 MHz, 48 MHz isn't reachable at all
+        assert_eq!(exact_pll_factors(14_745_600, 16, 48_000_000), None);
+        // 36 MHz only reaches 48 MHz through a prediv factor of 3
+        assert_eq!(exact_pll_factors(36_000_000, 16, 48_000_000), Some((3, 4)));
+    }
+
+    #[test]
+    fn test_usb_pll_factors() {
+        assert_eq!(usb_pll_factors(Clock::HSI), Some((1, 12)));
+        assert_eq!(usb_pll_factors(Clock::HSE), Some((1, 6)));
+        assert_eq!(usb_pll_factors(Clock::HSI14), None);
+    }
+
+    #[test]
+    fn test_configure_usb_clock_hsi48() {
+        let mut rcc = mock_rcc();
+        unsafe { *(&mut rcc.cr2 as *mut CR2 as *mut u32) = CR2_HSI48RDY; }
+        unsafe { *(&mut rcc.cfgr3 as *mut CFGR3 as *mut u32) = CFGR3_USBSW; }
+
+        assert_eq!(rcc.configure_usb_clock(UsbClockSource::HSI48), Ok(()));
+        assert!(rcc.clock_is_on(Clock::HSI48));
+        assert_eq!(raw_word(&rcc.cfgr3), 0);
+    }
+
+    #[test]
+    fn test_configure_usb_clock_uses_running_pll() {
+        // The system clock already runs off of HSI/2 * 12 = 48 MHz
+        let mut rcc = mock_rcc();
+        rcc.set_pll_source(Clock::HSI);
+        rcc.set_pll_multiplier(12);
+        unsafe { *(&mut rcc.cr as *mut CR as *mut u32) = CR_PLLON | CR_PLLRDY; }
+
+        assert_eq!(rcc.configure_usb_clock(UsbClockSource::PLL(Clock::HSI)), Ok(()));
+        assert_eq!(raw_word(&rcc.cfgr3), CFGR3_USBSW);
+    }
+
+    #[test]
+    fn test_configure_usb_clock_pll_in_use() {
+        // The system clock runs off of HSI/2 * 8 = 32 MHz, which can't be changed under it
+        let mut rcc = mock_rcc();
+        rcc.set_pll_source(Clock::HSI);
+        rcc.set_pll_multiplier(8);
+        unsafe { *(&mut rcc.cr as *mut CR as *mut u32) = CR_PLLON | CR_PLLRDY; }
+
+        assert_eq!(rcc.configure_usb_clock(UsbClockSource::PLL(Clock::HSE)),
+                   Err(ClockError::PllInUse));
+        assert_eq!(rcc.get_pll_multiplier(), 8);
+        assert_eq!(raw_word(&rcc.cfgr3), 0);
+    }
+
+    #[test]
+    fn test_configure_usb_clock_inexact_source() {
+        let mut rcc = mock_rcc();
+
+        assert_eq!(rcc.configure_usb_clock(UsbClockSource::PLL(Clock::HSI14)),
+                   Err(ClockError::InexactFrequency(Clock::HSI14)));
+        assert_eq!(raw_word(&rcc.cfgr3), 0);
+    }
+
+    #[test]
+    fn test_try_set_pll_multiplier() {
+        let mut rcc: RawRCC = unsafe { mem::zeroed() };
+
+        assert_eq!(rcc.try_set_pll_multiplier(6), Ok(()));
+        assert_eq!(rcc.get_pll_multiplier(), 6);
+    }
+
+    #[test]
+    fn test_try_set_pll_multiplier_out_of_range() {
+        let mut rcc: RawRCC = unsafe { mem::zeroed() };
+        rcc.set_pll_multiplier(4);
+
+        assert_eq!(rcc.try_set_pll_multiplier(1), Err(ClockError::InvalidPllMultiplier(1)));
+        assert_eq!(rcc.try_set_pll_multiplier(17), Err(ClockError::InvalidPllMultiplier(17)));
+        assert_eq!(rcc.get_pll_multiplier(), 4);
+    }
+
+    #[test]
+    fn test_try_set_pll_prediv_factor() {
+        let mut rcc: RawRCC = unsafe { mem::zeroed() };
+
+        assert_eq!(rcc.try_set_pll_prediv_factor(16), Ok(()));
+        assert_eq!(rcc.get_pll_prediv_factor(), 16);
+    }
+
+    #[test]
+    fn test_try_set_pll_prediv_factor_out_of_range() {
+        let mut rcc: RawRCC = unsafe { mem::zeroed() };
+
+        assert_eq!(rcc.try_set_pll_prediv_factor(0), Err(ClockError::InvalidPllPredivFactor(0)));
+        assert_eq!(rcc.try_set_pll_prediv_factor(17), Err(ClockError::InvalidPllPredivFactor(17)));
+        assert_eq!(rcc.get_pll_prediv_factor(), 1);
+    }
+
+    #[test]
+    fn test_try_set_pll_factors_pll_on() {
+        let mut rcc: RawRCC = unsafe { mem::zeroed() };
+        rcc.set_pll_multiplier(12);
+        rcc.enable_clock(Clock::PLL);
+
+        assert_eq!(rcc.try_set_pll_multiplier(6), Err(ClockError::PllOn));
+        assert_eq!(rcc.try_set_pll_prediv_factor(2), Err(ClockError::PllOn));
+        assert_eq!(rcc.get_pll_multiplier(), 12);
+        assert_eq!(rcc.get_pll_prediv_factor(), 1);
+    }
+
+    #[test]
+    fn test_enable_pll() {
+        let mut rcc: RawRCC = unsafe { mem::zeroed() };
+
+        rcc.enable_pll();
+        assert!(rcc.pll_is_on());
+        assert_eq!(raw_word(&rcc.cr), CR_PLLON);
+        // Not ready until the hardware reports it has locked
+        assert!(!rcc.pll_is_ready());
+
+        unsafe { *(&mut rcc.cr as *mut CR as *mut u32) |= CR_PLLRDY; }
+        assert!(rcc.pll_is_ready());
+    }
+
+    #[test]
+    fn test_disable_pll() {
+        let mut rcc: RawRCC = unsafe { mem::zeroed() };
+        rcc.enable_pll();
+
+        assert!(rcc.disable_pll());
+        assert!(!rcc.pll_is_on());
+        assert_eq!(rcc.try_set_pll_multiplier(6), Ok(()));
+    }
+
+    #[test]
+    fn test_disable_pll_driving_system_clock() {
+        let mut rcc = mock_rcc();
+        rcc.enable_pll();
+
+        assert!(!rcc.disable_pll());
+        assert!(rcc.pll_is_on());
+        assert_eq!(rcc.try_set_pll_multiplier(6), Err(ClockError::PllOn));
+    }
+
+    #[test]
+    fn test_backup_domain_reset_sequence_sets_then_clears() {
+        let mut rcc: RawRCC = unsafe { mem::zeroed() };
+        // The old settings don't survive the release
+        unsafe { *(&mut rcc.bdcr as *mut BDCR as *mut u32) = BDCR_LSEON; }
+
+        let sequence = backup_domain_reset_sequence(rcc.bdcr);
+
+        assert_eq!(raw_word(&sequence[0]) & BDCR_BDRST, BDCR_BDRST);
+        assert_eq!(raw_word(&sequence[1]), 0);
+    }
+
+    #[test]
+    fn test_set_lse_drive_while_lse_off() {
+        let mut rcc: RawRCC = unsafe { mem::zeroed() };
+
+        assert_eq!(rcc.set_lse_drive(LseDrive::High), Ok(()));
+        assert_eq!(rcc.get_lse_drive(), LseDrive::High);
+    }
+
+    #[test]
+    fn test_set_lse_drive_while_lse_on_is_rejected() {
+        let mut rcc: RawRCC = unsafe { mem::zeroed() };
+        unsafe { *(&mut rcc.bdcr as *mut BDCR as *mut u32) = BDCR_LSEON; }
+
+        assert_eq!(rcc.set_lse_drive(LseDrive::High), Err(ClockError::LseOn));
+        assert_eq!(rcc.get_lse_drive(), LseDrive::Low);
+    }
+
+    #[test]
+    fn test_pulse_backup_domain_reset_leaves_reset_released() {
+        let mut rcc: RawRCC = unsafe { mem::zeroed() };
+        unsafe { *(&mut rcc.bdcr as *mut BDCR as *mut u32) = BDCR_BDRST | BDCR_LSEON; }
+
+        rcc.pulse_backup_domain_reset();
+        assert_eq!(raw_word(&rcc.bdcr), 0);
+    }
+
+    #[test]
+    fn test_use_preset_without_hse_fails() {
+        let mut rcc = mock_rcc();
+        let mut flash: RawFLASH = unsafe { mem::zeroed() };
+
+        // HSERDY never comes up in the mock, like a board without a crystal
+        assert_eq!(rcc.apply_preset(ClockPreset::Hse8MhzTo48Mhz, &mut flash),
+                   Err(ClockError::NotReady(Clock::HSE)));
+        assert_eq!(rcc.get_system_clock_source(), Clock::PLL);
+    }
+
+    #[test]
+    fn test_use_preset_hsi48() {
+        let mut rcc: RawRCC = unsafe { mem::zeroed() };
+        let mut flash: RawFLASH = unsafe { mem::zeroed() };
+        // The switch to the HSI48 takes effect straight away in the mock
+        unsafe {
+            *(&mut rcc.cfgr as *mut CFGR as *mut u32) = CFGR_SWS_HSI48;
+            *(&mut rcc.cr2 as *mut CR2 as *mut u32) = CR2_HSI48RDY;
+        }
+
+        assert_eq!(rcc.apply_preset(ClockPreset::Hsi48, &mut flash), Ok(()));
+        assert_eq!(raw_word(&rcc.cfgr) & CFGR_SW_CLEAR_MASK, CFGR_CLOCK_HSI48);
+        assert!(rcc.clock_is_on(Clock::HSI48));
+        assert_eq!(flash.get_latency(), 1);
+    }
+
+    #[test]
+    fn test_switch_system_clock_sws_matches() {
+        // The mock reports the PLL as the system clock
+        let mut rcc = mock_rcc();
+
+        assert_eq!(rcc.switch_system_clock(Clock::PLL, 10), Ok(()));
+        assert_eq!(raw_word(&rcc.cfgr) & CFGR_SW_CLEAR_MASK, CFGR_CLOCK_PLL);
+    }
+
+    #[test]
+    fn test_switch_system_clock_times_out() {
+        // SWS never changes in the mock, like a switch to a clock that isn't ready
+        let mut rcc = mock_rcc();
+
+        assert_eq!(rcc.switch_system_clock(Clock::HSE, 10),
+                   Err(ClockError::SwitchFailed(Clock::HSE)));
+        // The selection goes back to the clock that is still in use
+        assert_eq!(raw_word(&rcc.cfgr) & CFGR_SW_CLEAR_MASK, CFGR_CLOCK_PLL);
+        assert_eq!(rcc.get_system_clock_source(), Clock::PLL);
+    }
+
+    #[test]
+    fn test_system_clock_rate_is_in_hertz() {
+        let rcc = mock_rcc();
+
+        // Every clock the system can run off of is at least 8 MHz
+        assert!(rcc.get_system_clock_rate() >= Hertz::from(Megahertz(8)));
+    }
+
+    #[test]
+    fn test_peripheral_clock_rate_undivided() {
+        let rcc = mock_rcc();
+        let sysclk = rcc.get_system_clock_rate();
+
+        assert_eq!(rcc.get_peripheral_clock_rate(Peripheral::DMA), sysclk);
+        assert_eq!(rcc.get_peripheral_clock_rate(Peripheral::USART2), sysclk);
+    }
+
+    #[test]
+    fn test_peripheral_clock_rate_applies_prescalers() {
+        let mut rcc = mock_rcc();
+        rcc.set_ahb_prescaler(2);
+        rcc.set_apb_prescaler(4);
+        let sysclk = rcc.get_system_clock_rate();
+
+        assert_eq!(rcc.get_peripheral_clock_rate(Peripheral::GPIOA), Hertz(sysclk.0 / 2));
+        assert_eq!(rcc.get_peripheral_clock_rate(Peripheral::USART1), Hertz(sysclk.0 / 8));
+        assert_eq!(rcc.get_peripheral_clock_rate(Peripheral::USART2), Hertz(sysclk.0 / 8));
+    }
+
+    #[test]
+    fn test_enable_snapshot_round_trip() {
+        let mut rcc = mock_rcc();
+        rcc.enable_peripherals(&[Peripheral::GPIOA, Peripheral::USART2, Peripheral::USART1]);
+        let snapshot = rcc.enable_snapshot();
+
+        rcc.disable_unused_peripherals(&[]);
+        rcc.enable_peripheral(Peripheral::SPI1);
+        rcc.restore_enable(snapshot);
+
+        assert_eq!(raw_word(&rcc.ahbenr), IOPAEN);
+        assert_eq!(raw_word(&rcc.apbenr1), USART2EN);
+        assert_eq!(raw_word(&rcc.apbenr2), USART1EN);
+    }
+
+    #[test]
+    fn test_enable_peripherals_combines_same_register() {
+        let mut rcc = mock_rcc();
+        rcc.enable_peripherals(&[Peripheral::GPIOA, Peripheral::DMA, Peripheral::GPIOB]);
+
+        assert_eq!(raw_word(&rcc.ahbenr), IOPAEN | DMAEN | IOPBEN);
+        assert_eq!(raw_word(&rcc.apbenr1), 0);
+        assert_eq!(raw_word(&rcc.apbenr2), 0);
+    }
+
+    #[test]
+    fn test_enable_peripherals_across_registers() {
+        let mut rcc = mock_rcc();
+        rcc.enable_peripherals(&[Peripheral::USART1, Peripheral::GPIOA,
+                                 Peripheral::USART2, Peripheral::SPI1]);
+
+        assert_eq!(raw_word(&rcc.ahbenr), IOPAEN);
+        assert_eq!(raw_word(&rcc.apbenr1), USART2EN);
+        assert_eq!(raw_word(&rcc.apbenr2), USART1EN | SPI1EN);
+    }
+
+    #[test]
+    fn test_enable_peripherals_keeps_enabled_peripherals() {
+        let mut rcc = mock_rcc();
+        rcc.enable_peripheral(Peripheral::GPIOC);
+        rcc.enable_peripherals(&[Peripheral::GPIOA]);
+
+        assert!(rcc.peripheral_is_enabled(Peripheral::GPIOC));
+        assert!(rcc.peripheral_is_enabled(Peripheral::GPIOA));
+    }
+
+    #[test]
+    fn test_enable_peripherals_empty_list_changes_nothing() {
+        let mut rcc = mock_rcc();
+        rcc.enable_peripherals(&[]);
+
+        assert_eq!(raw_word(&rcc.ahbenr), 0);
+        assert_eq!(raw_word(&rcc.apbenr1), 0);
+        assert_eq!(raw_word(&rcc.apbenr2), 0);
+    }
+
+    #[test]
+    fn test_disable_unused_peripherals_clears_others() {
+        let mut rcc = mock_rcc();
+        rcc.enable_peripherals(&[Peripheral::GPIOA, Peripheral::GPIOB, Peripheral::DMA,
+                                 Peripheral::USART2, Peripheral::TIM2,
+                                 Peripheral::USART1, Peripheral::ADC]);
+        rcc.disable_unused_peripherals(&[Peripheral::GPIOA, Peripheral::USART2]);
+
+        assert!(rcc.peripheral_is_enabled(Peripheral::GPIOA));
+        assert!(rcc.peripheral_is_enabled(Peripheral::USART2));
+        assert!(!rcc.peripheral_is_enabled(Peripheral::GPIOB));
+        assert!(!rcc.peripheral_is_enabled(Peripheral::DMA));
+        assert!(!rcc.peripheral_is_enabled(Peripheral::TIM2));
+        assert!(!rcc.peripheral_is_enabled(Peripheral::USART1));
+        assert!(!rcc.peripheral_is_enabled(Peripheral::ADC));
+    }
+
+    #[test]
+    fn test_disable_unused_peripherals_keeps_system_clocks() {
+        let mut rcc = mock_rcc();
+        rcc.enable_peripherals(&[Peripheral::FLITF, Peripheral::SRAMInterface,
+                                 Peripheral::SysCfgComp, Peripheral::GPIOC]);
+        rcc.disable_unused_peripherals(&[]);
+
+        assert_eq!(raw_word(&rcc.ahbenr), FLITFEN | SRAMEN);
+        assert_eq!(raw_word(&rcc.apbenr1), 0);
+        assert_eq!(raw_word(&rcc.apbenr2), SYSCFGCOMPEN);
+    }
+
+    #[test]
+    fn test_disable_unused_peripherals_doesnt_enable_kept() {
+        let mut rcc = mock_rcc();
+        rcc.disable_unused_peripherals(&[Peripheral::GPIOA, Peripheral::USART1]);
+
+        assert!(!rcc.peripheral_is_enabled(Peripheral::GPIOA));
+        assert!(!rcc.peripheral_is_enabled(Peripheral::USART1));
+        assert!(!rcc.peripheral_is_enabled(Peripheral::SysCfgComp));
+    }
+
+    #[test]
+    fn test_clock_is_usable() {
+        assert!(clock_is_usable(true, false));
+        assert!(!clock_is_usable(true, true));
+        assert!(!clock_is_usable(false, false));
+        assert!(!clock_is_usable(false, true));
+    }
+
+    #[test]
+    fn test_peripheral_clock_is_usable_needs_enable() {
+        let mut rcc: RawRCC = unsafe { mem::zeroed() };
+        assert!(!rcc.peripheral_clock_is_usable(Peripheral::USART2));
+
+        rcc.enable_peripheral(Peripheral::USART2);
+        assert!(rcc.peripheral_clock_is_usable(Peripheral::USART2));
+    }
+
+    #[test]
+    fn test_peripheral_clock_is_usable_checks_matching_reset_register() {
+        let mut rcc: RawRCC = unsafe { mem::zeroed() };
+        rcc.enable_peripherals(&[Peripheral::USART2, Peripheral::GPIOA, Peripheral::SysCfgComp]);
+
+        rcc.apb1rstr = Peripheral::USART2.mask();
+        assert!(!rcc.peripheral_clock_is_usable(Peripheral::USART2));
+        assert!(rcc.peripheral_clock_is_usable(Peripheral::GPIOA));
+        assert!(rcc.peripheral_clock_is_usable(Peripheral::SysCfgComp));
+
+        rcc.apb1rstr = 0;
+        rcc.ahbrstr = Peripheral::GPIOA.mask();
+        rcc.apb2rstr = Peripheral::SysCfgComp.mask();
+        assert!(rcc.peripheral_clock_is_usable(Peripheral::USART2));
+        assert!(!rcc.peripheral_clock_is_usable(Peripheral::GPIOA));
+        assert!(!rcc.peripheral_clock_is_usable(Peripheral::SysCfgComp));
+    }
+
+    #[test]
+    fn test_clock_tree_pll_from_hsi() {
+        // The default setup, HSI/2 * 12 with the AHB and APB undivided
+        let mut rcc = mock_rcc();
+        rcc.set_pll_source(Clock::HSI);
+        rcc.set_pll_multiplier(12);
+        rcc.enable_clock(Clock::HSI);
+        rcc.enable_clock(Clock::PLL);
+
+        let tree = rcc.clock_tree();
+        assert_eq!(tree.source, Clock::PLL);
+        assert_eq!(tree.pll_source, Clock::HSI);
+        assert_eq!(tree.pll, 48_000_000);
+        assert_eq!(tree.sysclk, 48_000_000);
+        assert_eq!(tree.hclk, 48_000_000);
+        assert_eq!(tree.pclk, 48_000_000);
+        assert_eq!(tree.hsi, ClockState { enabled: true, ready: false });
+        assert_eq!(tree.pll_state, ClockState { enabled: true, ready: false });
+        assert_eq!(tree.hse, ClockState { enabled: false, ready: false });
+        assert_eq!(tree.hsi48, ClockState { enabled: false, ready: false });
+    }
+
+    #[test]
+    fn test_oscillator_status_all_off() {
+        let rcc: RawRCC = unsafe { mem::zeroed() };
+        let off = ClockState { enabled: false, ready: false };
+
+        assert_eq!(rcc.oscillator_status(), OscillatorStatus {
+            hsi: off,
+            hse: off,
+            pll: off,
+            hsi48: off,
+        });
+    }
+
+    #[test]
+    fn test_oscillator_status_decodes_flags() {
+        let mut rcc: RawRCC = unsafe { mem::zeroed() };
+        // HSI on and ready, HSE on but not ready, PLL off
+        unsafe { *(&mut rcc.cr as *mut CR as *mut u32) = CR_HSION | CR_HSIRDY | CR_HSEON; }
+        // HSI48 and HSI14 on and ready, HSI14 shouldn't show up
+        let cr2 = CR2_HSI48ON | CR2_HSI48RDY | CR2_HSI14ON | CR2_HSI14RDY;
+        unsafe { *(&mut rcc.cr2 as *mut CR2 as *mut u32) = cr2; }
+
+        let status = rcc.oscillator_status();
+        assert_eq!(status.hsi, ClockState { enabled: true, ready: true });
+        assert_eq!(status.hse, ClockState { enabled: true, ready: false });
+        assert_eq!(status.pll, ClockState { enabled: false, ready: false });
+        assert_eq!(status.hsi48, ClockState { enabled: true, ready: true });
+    }
+
+    #[test]
+    fn test_oscillator_status_pll_ready() {
+        let mut rcc: RawRCC = unsafe { mem::zeroed() };
+        unsafe { *(&mut rcc.cr as *mut CR as *mut u32) = CR_PLLON | CR_PLLRDY; }
+
+        let status = rcc.oscillator_status();
+        assert_eq!(status.pll, ClockState { enabled: true, ready: true });
+        assert_eq!(status.hsi, ClockState { enabled: false, ready: false });
+        assert_eq!(status.hsi48, ClockState { enabled: false, ready: false });
+    }
+
+    #[test]
+    fn test_clock_tree_applies_prescalers() {
+        let mut rcc = mock_rcc();
+        rcc.set_pll_source(Clock::HSE);
+        rcc.set_pll_prediv_factor(2);
+        rcc.set_pll_multiplier(8);
+        rcc.set_ahb_prescaler(2);
+        rcc.set_apb_prescaler(4);
+
+        let tree = rcc.clock_tree();
+        assert_eq!(tree.pll, 32_000_000);
+        assert_eq!(tree.sysclk, 32_000_000);
+        assert_eq!(tree.hclk, 16_000_000);
+        assert_eq!(tree.pclk, 4_000_000);
+    }
+
+    #[test]
+    fn test_clock_tree_pll_from_12mhz_hse() {
+        let mut rcc = mock_rcc();
+        rcc.set_pll_source(Clock::HSE);
+        rcc.set_pll_prediv_factor(1);
+        rcc.set_pll_multiplier(4);
+
+        let tree = rcc.clock_tree_with_hse(12_000_000);
+        assert_eq!(tree.source, Clock::PLL);
+        assert_eq!(tree.pll, 48_000_000);
+        assert_eq!(tree.sysclk, 48_000_000);
+    }
+
+    #[test]
+    fn test_clock_tree_hsi_system_clock() {
+        // Out of reset the system clock runs off of the HSI
+        let mut rcc: RawRCC = unsafe { mem::zeroed() };
+        rcc.set_pll_source(Clock::HSI);
+
+        let tree = rcc.clock_tree();
+        assert_eq!(tree.source, Clock::HSI);
+        assert_eq!(tree.sysclk, 8_000_000);
+        assert_eq!(tree.hclk, 8_000_000);
+        assert_eq!(tree.pclk, 8_000_000);
+    }
+
+    #[test]
+    fn test_save_config_reads_registers() {
+        let mut rcc = mock_rcc();
+        rcc.set_pll_source(Clock::HSE);
+        rcc.set_pll_multiplier(6);
+        rcc.set_pll_prediv_factor(1);
+        rcc.set_ahb_prescaler(2);
+        rcc.set_apb_prescaler(4);
+
+        assert_eq!(rcc.save_config(), ClockConfig {
+            source: Clock::PLL,
+            pll_source: Clock::HSE,
+            pll_multiplier: 6,
+            pll_prediv_factor: 1,
+            ahb_prescaler: 2,
+            apb_prescaler: 4,
+        });
+    }
+
+    #[test]
+    fn test_config_round_trips_through_save_and_write() {
+        let config = ClockConfig {
+            source: Clock::PLL,
+            pll_source: Clock::HSI,
+            pll_multiplier: 12,
+            pll_prediv_factor: 2,
+            ahb_prescaler: 1,
+            apb_prescaler: 16,
+        };
+
+        // Woken up from Stop mode, everything is back to its reset value
+        let mut rcc = mock_rcc();
+        rcc.write_pll_config(&config);
+        rcc.write_prescalers(&config);
+
+        assert_eq!(rcc.save_config(), config);
+    }
+
+    #[test]
+    fn test_preset_hse_8mhz_to_48mhz_registers() {
+        let preset = ClockPreset::Hse8MhzTo48Mhz;
+        let mut rcc = mock_rcc();
+        rcc.write_pll_config(&preset.config());
+        rcc.write_prescalers(&preset.config());
+
+        let cfgr = raw_word(&rcc.cfgr);
+        assert_eq!(cfgr & CFGR_PLLSRC_MASK, CFGR_PLLSRC_HSE_PREDIV);
+        // PLLMUL = 0100 is a multiplier of 6
+        assert_eq!(cfgr & CFGR_PLLMUL_MASK, 0b0100 << 18);
+        assert_eq!(raw_word(&rcc.cfgr2), 0);
+        assert_eq!(preset.config().source, Clock::PLL);
+        assert_eq!(clock_control::clock_rate::pll_output_rate(Clock::HSE, 1, 6), 48_000_000);
+        assert_eq!(preset.flash_latency(), 1);
+    }
+
+    #[test]
+    fn test_preset_hsi48_registers() {
+        let preset = ClockPreset::Hsi48;
+        let mut rcc = mock_rcc();
+        rcc.set_ahb_prescaler(4);
+        rcc.set_apb_prescaler(2);
+        rcc.write_prescalers(&preset.config());
+
+        assert_eq!(rcc.get_ahb_prescaler(), 1);
+        assert_eq!(rcc.get_apb_prescaler(), 1);
+        assert_eq!(preset.config().source, Clock::HSI48);
+        assert_eq!(preset.flash_latency(), 1);
+    }
+
+    #[test]
+    fn test_preset_hsi_default_matches_reset() {
+        let preset = ClockPreset::HsiDefault8Mhz;
+        let mut rcc: RawRCC = unsafe { mem::zeroed() };
+        rcc.write_pll_config(&preset.config());
+        rcc.write_prescalers(&preset.config());
+
+        // Everything is at its reset value, and the system clock is running off of the HSI
+        assert_eq!(raw_word(&rcc.cfgr), 0);
+        assert_eq!(raw_word(&rcc.cfgr2), 0);
+        assert_eq!(rcc.get_system_clock_source(), preset.config().source);
+        assert_eq!(preset.flash_latency(), 0);
+    }
+
+    #[test]
+    fn test_write_config_overwrites_previous_settings() {
+        let mut rcc = mock_rcc();
+        rcc.set_pll_source(Clock::HSI48);
+        rcc.set_pll_multiplier(16);
+        rcc.set_pll_prediv_factor(16);
+        rcc.set_ahb_prescaler(512);
+        rcc.set_apb_prescaler(16);
+        let saved = rcc.save_config();
+
+        let mut other = mock_rcc();
+        other.set_pll_multiplier(3);
+        other.set_ahb_prescaler(4);
+        other.write_pll_config(&saved);
+        other.write_prescalers(&saved);
+
+        assert_eq!(other.save_config(), saved);
+    }
 }
@@ -0,0 +1,134 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+/* This submodule contains a guard that keeps a peripheral's clock enabled for as long as it is
+ * alive, for peripherals that are only used briefly.
+ */
+
+use core::ops::{Deref, DerefMut};
+use super::{RawRCC, Peripheral};
+
+/// Keeps a peripheral's clock enabled while it's alive, see `RCC::enable_peripheral_scoped`.
+///
+/// The guard derefs to the RCC it borrows, so the RCC can still be used (and further guards
+/// taken) while it's alive.
+pub struct PeripheralClockGuard<'a> {
+    rcc: &'a mut RawRCC,
+    peripheral: Peripheral,
+    was_enabled: bool,
+}
+
+impl<'a> PeripheralClockGuard<'a> {
+    fn new(rcc: &'a mut RawRCC, peripheral: Peripheral) -> Self {
+        let was_enabled = rcc.peripheral_is_enabled(peripheral);
+        rcc.enable_peripheral(peripheral);
+        PeripheralClockGuard {
+            rcc: rcc,
+            peripheral: peripheral,
+            was_enabled: was_enabled,
+        }
+    }
+
+    /// Get the peripheral whose clock the guard keeps enabled.
+    pub fn peripheral(&self) -> Peripheral {
+        self.peripheral
+    }
+}
+
+impl<'a> Deref for PeripheralClockGuard<'a> {
+    type Target = RawRCC;
+
+    fn deref(&self) -> &Self::Target {
+        self.rcc
+    }
+}
+
+impl<'a> DerefMut for PeripheralClockGuard<'a> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.rcc
+    }
+}
+
+impl<'a> Drop for PeripheralClockGuard<'a> {
+    fn drop(&mut self) {
+        if !self.was_enabled {
+            self.rcc.disable_peripheral(self.peripheral);
+        }
+    }
+}
+
+impl RawRCC {
+    /// Enable the clock for a peripheral until the returned guard is dropped.
+    ///
+    /// The guard puts the clock back the way it found it, so if the clock was already enabled it
+    /// is left enabled on drop. Nested guards for the same peripheral are taken through the outer
+    /// guard and so always drop first, which means only the outermost guard that actually turned
+    /// the clock on turns it off again.
+    pub fn enable_peripheral_scoped(&mut self, peripheral: Peripheral) -> PeripheralClockGuard {
+        PeripheralClockGuard::new(self, peripheral)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::mem;
+
+    #[test]
+    fn test_guard_disables_on_drop() {
+        let mut rcc: RawRCC = unsafe { mem::zeroed() };
+        {
+            let guard = rcc.enable_peripheral_scoped(Peripheral::CRC);
+            assert!(guard.peripheral_is_enabled(Peripheral::CRC));
+        }
+        assert!(!rcc.peripheral_is_enabled(Peripheral::CRC));
+    }
+
+    #[test]
+    fn test_guard_leaves_already_enabled_clock_on() {
+        let mut rcc: RawRCC = unsafe { mem::zeroed() };
+        rcc.enable_peripheral(Peripheral::CRC);
+        {
+            let _guard = rcc.enable_peripheral_scoped(Peripheral::CRC);
+        }
+        assert!(rcc.peripheral_is_enabled(Peripheral::CRC));
+    }
+
+    #[test]
+    fn test_nested_guards_only_outer_disables() {
+        let mut rcc: RawRCC = unsafe { mem::zeroed() };
+        {
+            let mut outer = rcc.enable_peripheral_scoped(Peripheral::CRC);
+            {
+                let _inner = outer.enable_peripheral_scoped(Peripheral::CRC);
+            }
+            assert!(outer.peripheral_is_enabled(Peripheral::CRC));
+        }
+        assert!(!rcc.peripheral_is_enabled(Peripheral::CRC));
+    }
+
+    #[test]
+    fn test_guard_only_touches_its_peripheral() {
+        let mut rcc: RawRCC = unsafe { mem::zeroed() };
+        {
+            let mut guard = rcc.enable_peripheral_scoped(Peripheral::CRC);
+            guard.enable_peripheral(Peripheral::GPIOA);
+        }
+        assert!(!rcc.peripheral_is_enabled(Peripheral::CRC));
+        assert!(rcc.peripheral_is_enabled(Peripheral::GPIOA));
+    }
+}
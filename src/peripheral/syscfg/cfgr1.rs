@@ -0,0 +1,180 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use super::super::Field;
+use super::defs::*;
+
+/// Defines the memory mapped at address 0x0000_0000, which the processor boots from.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MemMode {
+    /// Main flash memory.
+    MainFlash,
+    /// System flash memory, where the built in bootloader lives.
+    SystemFlash,
+    /// Embedded SRAM.
+    Sram,
+}
+
+impl Field for MemMode {
+    fn mask(&self) -> u32 {
+        match *self {
+            MemMode::MainFlash => MEM_MODE_MAIN_FLASH,
+            MemMode::SystemFlash => MEM_MODE_SYSTEM_FLASH,
+            MemMode::Sram => MEM_MODE_SRAM,
+        }
+    }
+}
+
+impl MemMode {
+    fn from_mask(mask: u32) -> Self {
+        match mask {
+            MEM_MODE_MAIN_FLASH | MEM_MODE_MAIN_FLASH_ALT => MemMode::MainFlash,
+            MEM_MODE_SYSTEM_FLASH => MemMode::SystemFlash,
+            MEM_MODE_SRAM => MemMode::Sram,
+            _ => panic!("MemMode::from_mask - mask was not a valid value!"),
+        }
+    }
+}
+
+/// Defines the DMA requests that can be moved to another channel.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DmaRemap {
+    /// ADC, moved from channel 1 to channel 2.
+    Adc,
+    /// USART1 TX, moved from channel 2 to channel 4.
+    Usart1Tx,
+    /// USART1 RX, moved from channel 3 to channel 5.
+    Usart1Rx,
+    /// TIM16, moved from channel 3 to channel 4.
+    Tim16,
+    /// TIM17, moved from channel 1 to channel 2.
+    Tim17,
+}
+
+impl Field for DmaRemap {
+    fn mask(&self) -> u32 {
+        match *self {
+            DmaRemap::Adc => CFGR1_ADC_DMA_RMP,
+            DmaRemap::Usart1Tx => CFGR1_USART1TX_DMA_RMP,
+            DmaRemap::Usart1Rx => CFGR1_USART1RX_DMA_RMP,
+            DmaRemap::Tim16 => CFGR1_TIM16_DMA_RMP,
+            DmaRemap::Tim17 => CFGR1_TIM17_DMA_RMP,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct CFGR1(u32);
+
+impl CFGR1 {
+    /* Bits 1:0 MEM_MODE: Memory mapping selection bits
+     *   These bits are set and cleared by software. They control the memory
+     *   internal mapping at address 0x0000 0000. After reset these bits take
+     *   on the value selected by the actual boot mode configuration.
+     *      x0: Main Flash memory mapped at 0x0000 0000
+     *      01: System Flash memory mapped at 0x0000 0000
+     *      11: Embedded SRAM mapped at 0x0000 0000
+     */
+    pub fn set_memory_remap(&mut self, mode: MemMode) {
+        self.0 &= !CFGR1_MEM_MODE_MASK;
+        self.0 |= mode.mask();
+    }
+
+    pub fn get_memory_remap(&self) -> MemMode {
+        MemMode::from_mask(self.0 & CFGR1_MEM_MODE_MASK)
+    }
+
+    /* Bit 8 ADC_DMA_RMP: ADC DMA request remapping bit
+     *      0: No remap (ADC DMA request mapped on DMA channel 1)
+     *      1: Remap (ADC DMA request mapped on DMA channel 2)
+     * Bit 9 USART1_TX_DMA_RMP: USART1_TX DMA request remapping bit
+     *      0: No remap (USART1_TX DMA request mapped on DMA channel 2)
+     *      1: Remap (USART1_TX DMA request mapped on DMA channel 4)
+     * Bit 10 USART1_RX_DMA_RMP: USART1_RX DMA request remapping bit
+     *      0: No remap (USART1_RX DMA request mapped on DMA channel 3)
+     *      1: Remap (USART1_RX DMA request mapped on DMA channel 5)
+     * Bit 11 TIM16_DMA_RMP: TIM16 DMA request remapping bit
+     *      0: No remap (TIM16_CH1 and TIM16_UP DMA request mapped on DMA channel 3)
+     *      1: Remap (TIM16_CH1 and TIM16_UP DMA request mapped on DMA channel 4)
+     * Bit 12 TIM17_DMA_RMP: TIM17 DMA request remapping bit
+     *      0: No remap (TIM17_CH1 and TIM17_UP DMA request mapped on DMA channel 1)
+     *      1: Remap (TIM17_CH1 and TIM17_UP DMA request mapped on DMA channel 2)
+     */
+    pub fn set_dma_remap(&mut self, remap: DmaRemap, enable: bool) {
+        self.0 &= !remap.mask();
+        if enable {
+            self.0 |= remap.mask();
+        }
+    }
+
+    pub fn get_dma_remap(&self, remap: DmaRemap) -> bool {
+        self.0 & remap.mask() != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cfgr1_set_memory_remap() {
+        let mut cfgr1 = CFGR1(0);
+
+        cfgr1.set_memory_remap(MemMode::SystemFlash);
+        assert_eq!(cfgr1.0, 0b01);
+
+        cfgr1.set_memory_remap(MemMode::Sram);
+        assert_eq!(cfgr1.0, 0b11);
+
+        cfgr1.set_memory_remap(MemMode::MainFlash);
+        assert_eq!(cfgr1.0, 0b00);
+    }
+
+    #[test]
+    fn test_cfgr1_set_memory_remap_keeps_other_bits() {
+        let mut cfgr1 = CFGR1(0b1 << 8);
+
+        cfgr1.set_memory_remap(MemMode::Sram);
+        assert_eq!(cfgr1.0, 0b1 << 8 | 0b11);
+    }
+
+    #[test]
+    fn test_cfgr1_set_dma_remap() {
+        let mut cfgr1 = CFGR1(0b11);
+
+        cfgr1.set_dma_remap(DmaRemap::Adc, true);
+        cfgr1.set_dma_remap(DmaRemap::Usart1Rx, true);
+        assert_eq!(cfgr1.0, 0b1 << 10 | 0b1 << 8 | 0b11);
+        assert!(cfgr1.get_dma_remap(DmaRemap::Adc));
+        assert!(!cfgr1.get_dma_remap(DmaRemap::Usart1Tx));
+
+        cfgr1.set_dma_remap(DmaRemap::Adc, false);
+        assert_eq!(cfgr1.0, 0b1 << 10 | 0b11);
+    }
+
+    #[test]
+    fn test_cfgr1_get_memory_remap() {
+        assert_eq!(CFGR1(0b00).get_memory_remap(), MemMode::MainFlash);
+        assert_eq!(CFGR1(0b01).get_memory_remap(), MemMode::SystemFlash);
+        assert_eq!(CFGR1(0b11).get_memory_remap(), MemMode::Sram);
+    }
+
+    #[test]
+    fn test_cfgr1_get_memory_remap_x0_is_main_flash() {
+        assert_eq!(CFGR1(0b10).get_memory_remap(), MemMode::MainFlash);
+    }
+}
@@ -0,0 +1,41 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use peripheral::addresses;
+
+pub const SYSCFG_ADDR: *const u32 = addresses::SYSCFG_ADDR;
+
+// Configuration Register 1
+pub const CFGR1_OFFSET: u32 = 0x00;
+pub const CFGR1_MEM_MODE_MASK: u32 = 0b11;
+pub const MEM_MODE_MAIN_FLASH: u32 = 0b00;
+pub const MEM_MODE_SYSTEM_FLASH: u32 = 0b01;
+pub const MEM_MODE_MAIN_FLASH_ALT: u32 = 0b10;
+pub const MEM_MODE_SRAM: u32 = 0b11;
+pub const CFGR1_ADC_DMA_RMP: u32 = 0b1 << 8;
+pub const CFGR1_USART1TX_DMA_RMP: u32 = 0b1 << 9;
+pub const CFGR1_USART1RX_DMA_RMP: u32 = 0b1 << 10;
+pub const CFGR1_TIM16_DMA_RMP: u32 = 0b1 << 11;
+pub const CFGR1_TIM17_DMA_RMP: u32 = 0b1 << 12;
+
+// External Interrupt Configuration Registers 1 - 4
+pub const EXTICR1_OFFSET: u32 = 0x08;
+pub const EXTICR_MASK: u32 = 0b1111;
+pub const EXTI_PA: u32 = 0b0000;
+pub const EXTI_PB: u32 = 0b0001;
+pub const EXTI_PC: u32 = 0b0010;
+pub const EXTI_PF: u32 = 0b0101;
@@ -0,0 +1,101 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+use peripheral::gpio::Group;
+use super::defs::*;
+
+// The value selecting each GPIO group as the source of an EXTI line.
+fn group_bits(group: Group) -> u32 {
+    match group {
+        Group::A => EXTI_PA,
+        Group::B => EXTI_PB,
+        Group::C => EXTI_PC,
+        Group::F => EXTI_PF,
+    }
+}
+
+// Each of the four EXTICR registers selects the source for four of the EXTI lines 0 - 15.
+#[derive(Copy, Clone, Debug)]
+pub struct EXTICR(u32);
+
+impl EXTICR {
+    /* Bits 15:0 EXTIx[3:0]: EXTI x configuration bits
+     *   These bits are written by software to select the source input for the EXTIx external
+     *   interrupt. `index` is the position of the line within this register (line % 4).
+     *   x000: PA[x] pin
+     *   x001: PB[x] pin
+     *   x010: PC[x] pin
+     *   x011: PD[x] pin
+     *   x100: PE[x] pin
+     *   x101: PF[x] pin
+     */
+    pub fn set_source(&mut self, index: u8, group: Group) {
+        if index > 3 {
+            panic!("EXTICR::set_source - index must be between [0..3]!");
+        }
+        let shift = index * 4;
+
+        self.0 &= !(EXTICR_MASK << shift);
+        self.0 |= group_bits(group) << shift;
+    }
+
+    pub fn get_source(&self, index: u8) -> Option<Group> {
+        if index > 3 {
+            panic!("EXTICR::get_source - index must be between [0..3]!");
+        }
+        match (self.0 >> (index * 4)) & EXTICR_MASK {
+            EXTI_PA => Some(Group::A),
+            EXTI_PB => Some(Group::B),
+            EXTI_PC => Some(Group::C),
+            EXTI_PF => Some(Group::F),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exticr_set_source() {
+        let mut exticr = EXTICR(0);
+
+        exticr.set_source(1, Group::B);
+        exticr.set_source(3, Group::F);
+        assert_eq!(exticr.0, 0b0001 << 4 | 0b0101 << 12);
+
+        exticr.set_source(1, Group::C);
+        assert_eq!(exticr.0, 0b0010 << 4 | 0b0101 << 12);
+    }
+
+    #[test]
+    fn test_exticr_get_source() {
+        let exticr = EXTICR(0b0101 << 8 | 0b0011 << 12);
+
+        assert_eq!(exticr.get_source(0), Some(Group::A));
+        assert_eq!(exticr.get_source(2), Some(Group::F));
+        // Group D isn't supported
+        assert_eq!(exticr.get_source(3), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_exticr_set_source_index_greater_than_3_panics() {
+        EXTICR(0).set_source(4, Group::A);
+    }
+}
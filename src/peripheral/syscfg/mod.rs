@@ -0,0 +1,165 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! This module controls the system configuration controller (SYSCFG).
+//!
+//! The SYSCFG is clocked through the RCC, the `SysCfgComp` peripheral must be enabled before any
+//! of its registers can be written.
+
+mod cfgr1;
+mod exticr;
+mod defs;
+
+use core::ops::{Deref, DerefMut};
+use volatile::Volatile;
+use arm::asm::{data_barrier, instruction_barrier};
+use self::defs::*;
+use self::cfgr1::CFGR1;
+use self::exticr::EXTICR;
+use peripheral::gpio::Group;
+
+pub use self::cfgr1::{MemMode, DmaRemap};
+
+/// Returns an instance of the SYSCFG struct so it can be used to configure the system.
+pub fn syscfg() -> SYSCFG {
+    SYSCFG::syscfg()
+}
+
+#[derive(Copy, Clone, Debug)]
+#[repr(C)]
+#[doc(hidden)]
+pub struct RawSYSCFG {
+    cfgr1: CFGR1,
+    _res: u32,
+    exticr: [EXTICR; 4],
+}
+
+/// System Configuration Controller
+#[derive(Copy, Clone, Debug)]
+pub struct SYSCFG(Volatile<RawSYSCFG>);
+
+impl SYSCFG {
+    fn syscfg() -> Self {
+        unsafe {
+            SYSCFG(Volatile::new(SYSCFG_ADDR as *const _))
+        }
+    }
+}
+
+impl Deref for SYSCFG {
+    type Target = RawSYSCFG;
+
+    fn deref(&self) -> &Self::Target {
+        &*(self.0)
+    }
+}
+
+impl DerefMut for SYSCFG {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut *(self.0)
+    }
+}
+
+impl RawSYSCFG {
+    /// Select the memory mapped at address 0x0000_0000.
+    ///
+    /// A bootloader that copies an application's vector table into SRAM remaps SRAM here before
+    /// jumping to the application, since the Cortex-M0 can't relocate its vector table. The remap
+    /// doesn't survive a reset, software resets included, after which the mapping is selected by
+    /// the BOOT0 pin and the boot option bits again.
+    pub fn set_memory_remap(&mut self, mode: MemMode) {
+        self.cfgr1.set_memory_remap(mode);
+        // Make sure the new mapping is in place before anything is fetched through it
+        data_barrier();
+        instruction_barrier();
+    }
+
+    /// Get the memory mapped at address 0x0000_0000.
+    pub fn get_memory_remap(&self) -> MemMode {
+        self.cfgr1.get_memory_remap()
+    }
+
+    /// Move a peripheral's DMA request to its alternate channel if `enable` is true, or back to
+    /// its default channel otherwise.
+    pub fn set_dma_remap(&mut self, remap: DmaRemap, enable: bool) {
+        self.cfgr1.set_dma_remap(remap, enable);
+    }
+
+    /// Return true if a peripheral's DMA request is moved to its alternate channel.
+    pub fn get_dma_remap(&self, remap: DmaRemap) -> bool {
+        self.cfgr1.get_dma_remap(remap)
+    }
+
+    /// Select the GPIO group whose pin `line` drives the EXTI line. Only one group can drive
+    /// each line, so e.g. PA3 and PB3 can't both be used as interrupts.
+    ///
+    /// # Panics
+    ///
+    /// Line must be a value between [0..15] or the kernel will panic.
+    pub fn set_exti_source(&mut self, line: u8, group: Group) {
+        if line > 15 {
+            panic!("SYSCFG::set_exti_source - line must be between [0..15]!");
+        }
+        self.exticr[(line / 4) as usize].set_source(line % 4, group);
+    }
+
+    /// Get the GPIO group driving the EXTI line, `None` if it's set to a group this crate doesn't
+    /// support.
+    ///
+    /// # Panics
+    ///
+    /// Line must be a value between [0..15] or the kernel will panic.
+    pub fn get_exti_source(&self, line: u8) -> Option<Group> {
+        if line > 15 {
+            panic!("SYSCFG::get_exti_source - line must be between [0..15]!");
+        }
+        self.exticr[(line / 4) as usize].get_source(line % 4)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use peripheral::raw_word;
+    use core::mem;
+
+    #[test]
+    fn test_set_exti_source_selects_register() {
+        let mut syscfg: RawSYSCFG = unsafe { mem::zeroed() };
+
+        syscfg.set_exti_source(13, Group::C);
+        let exticr4 = raw_word(&syscfg.exticr[3]);
+        assert_eq!(exticr4, 0b0010 << 4);
+        assert_eq!(syscfg.get_exti_source(13), Some(Group::C));
+        assert_eq!(syscfg.get_exti_source(12), Some(Group::A));
+    }
+
+    #[test]
+    fn test_exticr_offset() {
+        let syscfg: RawSYSCFG = unsafe { mem::zeroed() };
+        let base = &syscfg as *const _ as usize;
+
+        assert_eq!(&syscfg.exticr as *const _ as usize - base, EXTICR1_OFFSET as usize);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_set_exti_source_line_greater_than_15_panics() {
+        let mut syscfg: RawSYSCFG = unsafe { mem::zeroed() };
+        syscfg.set_exti_source(16, Group::A);
+    }
+}
@@ -18,6 +18,7 @@
 use super::defs::*;
 
 /// The clock source for the SysTick device
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum ClockSource {
     /// Use a reference clock
     Reference,
@@ -39,6 +40,11 @@ impl CSR {
         }
     }
 
+    /// Returns true if the counter is enabled.
+    pub fn is_enabled(&self) -> bool {
+        (self.0 & ENABLE) != 0
+    }
+
     pub fn set_interrupt(&mut self, enable: bool) {
         if enable {
             self.0 |= TICKINT;
@@ -48,6 +54,11 @@ impl CSR {
         }
     }
 
+    /// Returns true if the SysTick interrupt is enabled.
+    pub fn is_interrupt_enabled(&self) -> bool {
+        (self.0 & TICKINT) != 0
+    }
+
     pub fn set_source(&mut self, source: ClockSource) {
         match source {
             ClockSource::Reference => self.0 &= !CLKSOURCE,
@@ -55,7 +66,19 @@ impl CSR {
         };
     }
 
+    /// Returns the clock source currently driving the counter.
+    pub fn get_source(&self) -> ClockSource {
+        if self.0 & CLKSOURCE != 0 {
+            ClockSource::Processor
+        }
+        else {
+            ClockSource::Reference
+        }
+    }
+
     /// Returns true if the counter has reached zero since the last time it was checked.
+    ///
+    /// The hardware clears this bit whenever the register is read.
     pub fn did_underflow(&self) -> bool {
         (self.0 & COUNTFLAG) != 0
     }
@@ -97,6 +120,28 @@ mod tests {
         assert_eq!(csr.0, 0);
     }
 
+    #[test]
+    fn test_csr_is_interrupt_enabled() {
+        assert!(CSR(0b1 << 1).is_interrupt_enabled());
+        assert!(!CSR(0b1).is_interrupt_enabled());
+    }
+
+    #[test]
+    fn test_csr_enable_bits_are_masked_from_sample_values() {
+        // COUNTFLAG and CLKSOURCE set alongside
+        let csr = CSR(0b1 << 16 | 0b1 << 2 | 0b1);
+        assert!(csr.is_enabled());
+        assert!(!csr.is_interrupt_enabled());
+
+        let csr = CSR(0b1 << 16 | 0b1 << 2 | 0b1 << 1);
+        assert!(!csr.is_enabled());
+        assert!(csr.is_interrupt_enabled());
+
+        let csr = CSR(0b111);
+        assert!(csr.is_enabled());
+        assert!(csr.is_interrupt_enabled());
+    }
+
     #[test]
     fn test_csr_set_source() {
         let mut csr = CSR(0);
@@ -105,6 +150,18 @@ mod tests {
         assert_eq!(csr.0, 0b1 << 2);
     }
 
+    #[test]
+    fn test_csr_is_enabled() {
+        assert_eq!(CSR(0).is_enabled(), false);
+        assert_eq!(CSR(0b1).is_enabled(), true);
+    }
+
+    #[test]
+    fn test_csr_get_source() {
+        assert_eq!(CSR(0).get_source(), ClockSource::Reference);
+        assert_eq!(CSR(0b1 << 2).get_source(), ClockSource::Processor);
+    }
+
     #[test]
     fn test_csr_did_underflow_false_if_underflow_bit_not_set() {
         let csr = CSR(0);
@@ -15,7 +15,9 @@
 * along with this program. If not, see <http://www.gnu.org/licenses/>.
 */
 
-pub const SYSTICK_ADDR: *const u32 = 0xE000E010 as *const _;
+use peripheral::addresses;
+
+pub const SYSTICK_ADDR: *const u32 = addresses::SYSTICK_ADDR;
 
 // Control Status Register
 pub const CSR_OFFSET: u32 = 0x00;
@@ -32,3 +34,8 @@ pub const RELOAD: u32 = 0xFFFFFF;
 pub const CVR_OFFSET: u32 = 0x08;
 pub const CURRENT: u32 = 0xFFFFFF;
 pub const CLEAR_VALUE: u32 = 0xFFFFFFFF;
+
+// Calibration Value Register
+pub const CALIB_OFFSET: u32 = 0x0C;
+pub const TENMS: u32 = 0xFFFFFF;
+pub const NOREF: u32 = 0b1 << 31;
@@ -0,0 +1,37 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! `embedded-hal` delay trait implementations for the SysTick.
+
+use hal::blocking::delay::{DelayMs, DelayUs};
+use super::SysTick;
+use time;
+
+impl DelayMs<u32> for SysTick {
+    /// Put the current task to sleep for the specified number of milliseconds.
+    fn delay_ms(&mut self, ms: u32) {
+        time::delay_ms(ms as usize);
+    }
+}
+
+impl DelayUs<u32> for SysTick {
+    /// Busy wait for the specified number of microseconds, the tick configuration is left
+    /// untouched.
+    fn delay_us(&mut self, us: u32) {
+        time::delay_us(us as usize);
+    }
+}
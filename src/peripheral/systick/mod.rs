@@ -21,15 +21,47 @@ mod control_status;
 mod reload_value;
 mod current_value;
 mod defs;
+mod timer_wheel;
+#[cfg(feature="embedded-hal")]
+mod hal;
 
 use core::ops::{Deref, DerefMut};
 use volatile::Volatile;
+use altos_core::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
+use altos_core::sync::CriticalSection;
+use peripheral::callback::{CallbackSlot, CALLBACK_SLOT_INIT};
+use time::Hertz;
 use self::defs::*;
 use self::control_status::CSR;
 use self::current_value::CVR;
 use self::reload_value::RVR;
 
 pub use self::control_status::ClockSource;
+pub use self::timer_wheel::{TimerWheel, Timer, TimerId};
+
+// Software copy of the COUNTFLAG bit. The hardware clears COUNTFLAG whenever the CSR is read, so
+// every read that sees the flag set latches it here, and the flag is only consumed by
+// `take_underflow`.
+static UNDERFLOW_LATCH: AtomicUsize = ATOMIC_USIZE_INIT;
+
+// The user registered tick handler.
+static HANDLER: CallbackSlot = CALLBACK_SLOT_INIT;
+
+// The counter configuration saved by `prepare_for_stop`, see `stop_state` for the encoding. A
+// value of 0 means nothing has been saved.
+static STOP_STATE: AtomicUsize = ATOMIC_USIZE_INIT;
+
+const STOP_SAVED: usize = 0b1 << 0;
+const STOP_ENABLED: usize = 0b1 << 1;
+const STOP_INTERRUPT: usize = 0b1 << 2;
+const STOP_PROCESSOR: usize = 0b1 << 3;
+
+/// Errors that can be reported when configuring the SysTick.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SysTickError {
+    /// The reload value doesn't fit in the 24 bit reload register, it must be <= 0xFFFFFF.
+    ReloadOutOfRange,
+}
 
 /// Returns an instance of the SysTick to modify system tick behavior.
 pub fn systick() -> SysTick {
@@ -103,14 +135,79 @@ impl RawSysTick {
         self.csr.set_source(control_status::ClockSource::Processor);
     }
 
-    /// Use an alternate clock for the counter.
-    pub fn use_reference_clock(&mut self) {
-        self.csr.set_source(control_status::ClockSource::Reference);
+    /// Use the reference clock for the counter, if the part has one. Returns true if the
+    /// reference clock is now driving the counter.
+    ///
+    /// If the NOREF bit of the calibration register is set there is no reference clock, and
+    /// selecting it would stop the counter. In that case the processor clock is used instead and
+    /// false is returned, so the tick rate will be the system clock rate rather than the
+    /// reference rate.
+    pub fn use_reference_clock(&mut self) -> bool {
+        let source = reference_or_fallback(self.calib);
+        self.csr.set_source(source);
+        source == ClockSource::Reference
     }
 
-    /// Check if counter reached zero.
+    /// Check if the part provides a reference clock for the counter.
+    pub fn has_reference_clock(&self) -> bool {
+        has_reference_clock(self.calib)
+    }
+
+    /// Check if the counter is enabled.
+    pub fn is_counter_enabled(&self) -> bool {
+        self.csr.is_enabled()
+    }
+
+    /// Check if the SysTick exception is raised when the counter reaches zero. Together with
+    /// `is_counter_enabled` this lets the timer be checked before it gets reconfigured.
+    pub fn is_interrupt_enabled(&self) -> bool {
+        self.csr.is_interrupt_enabled()
+    }
+
+    /// Get the clock source currently driving the counter.
+    pub fn get_clock_source(&self) -> ClockSource {
+        self.csr.get_source()
+    }
+
+    /// Check if counter reached zero since the last time the CSR was read.
+    ///
+    /// # Note
+    ///
+    /// Reading the CSR clears COUNTFLAG in hardware, so two consecutive calls will disagree, and
+    /// any other access to the CSR (such as enabling the counter) can silently consume the flag.
+    /// If more than one piece of code needs to know about underflows, use `peek_underflow` and
+    /// `take_underflow` instead, which remember the flag in software.
+    /// Don't call this while `take_underflow` is being used as a tick source.
     pub fn did_underflow(&self) -> bool {
-        self.csr.did_underflow()
+        let underflow = self.csr.did_underflow();
+        latch_underflow(&UNDERFLOW_LATCH, underflow);
+        underflow
+    }
+
+    /// Check if the counter reached zero without consuming the underflow.
+    ///
+    /// The hardware flag can't be read without clearing it, so the flag is latched in software
+    /// and repeated calls agree until `take_underflow` is called.
+    pub fn peek_underflow(&self) -> bool {
+        latch_underflow(&UNDERFLOW_LATCH, self.csr.did_underflow());
+        peek_latch(&UNDERFLOW_LATCH)
+    }
+
+    /// Check if the counter reached zero, consuming the underflow.
+    ///
+    /// Each underflow is reported exactly once, even if COUNTFLAG was already cleared by a
+    /// `peek_underflow` in between. Only one piece of code (typically the scheduler's tick source)
+    /// should take the underflow, everyone else should use `peek_underflow`. `did_underflow` must
+    /// not be used alongside it, since its result is unreliable once another reader clears
+    /// COUNTFLAG.
+    pub fn take_underflow(&self) -> bool {
+        take_tick_from(&UNDERFLOW_LATCH, self.csr.did_underflow())
+    }
+
+    /// Check if a tick period elapsed since the last call, for use as the scheduler's tick source.
+    /// This is the same as `take_underflow`.
+    pub fn take_tick(&mut self) -> bool {
+        self.take_underflow()
     }
 
     /// Get the reload value for the counter.
@@ -119,10 +216,21 @@ impl RawSysTick {
     }
 
     /// Set the reload value for the counter.
+    ///
+    /// # Panics
+    ///
+    /// The reload register is only 24 bits, a value > 0xFFFFFF will cause the kernel to panic.
+    /// Use `try_set_reload_value` for values that haven't been checked.
     pub fn set_reload_value(&mut self, value: u32) {
         self.rvr.set_reload_value(value);
     }
 
+    /// Set the reload value for the counter, or return `SysTickError::ReloadOutOfRange` without
+    /// changing it if the value doesn't fit in the 24 bit reload register.
+    pub fn try_set_reload_value(&mut self, value: u32) -> Result<(), SysTickError> {
+        self.rvr.try_set_reload_value(value)
+    }
+
     /// Get the current value for the counter.
     pub fn get_current_value(&self) -> u32 {
         self.cvr.get_current_value()
@@ -132,4 +240,370 @@ impl RawSysTick {
     pub fn clear_current_value(&mut self) {
         self.cvr.clear_current_value();
     }
+
+    /// Get the TENMS calibration value, the reload value for a 10 ms period when the counter is
+    /// driven by the reference clock. Returns 0 if the calibration value is unknown.
+    pub fn get_calibration_tenms(&self) -> u32 {
+        self.calib & TENMS
+    }
+
+    /// Save the counter configuration and stop the counter before the processor enters Stop mode.
+    ///
+    /// Stop mode halts HCLK, and both clocks the counter can run from are derived from it (the
+    /// reference clock is HCLK/8 on this part), so there is no source that keeps counting. The
+    /// time spent stopped is lost from the system time and has to be made up from another source
+    /// such as the RTC. The counter and its interrupt are stopped so a partly counted tick period
+    /// doesn't fire straight after waking, and an underflow that's already pending is latched
+    /// for `take_underflow`.
+    pub fn prepare_for_stop(&mut self) {
+        latch_underflow(&UNDERFLOW_LATCH, self.csr.did_underflow());
+        let state = stop_state(self.csr.is_enabled(), self.csr.is_interrupt_enabled(),
+                               self.csr.get_source());
+        STOP_STATE.store(state, Ordering::SeqCst);
+        self.csr.set_interrupt(false);
+        self.csr.set_enable(false);
+    }
+
+    /// Restore the counter configuration saved by `prepare_for_stop` after waking from Stop mode.
+    /// The count starts over from the reload value. Does nothing if `prepare_for_stop` wasn't
+    /// called first.
+    pub fn resume_from_stop(&mut self) {
+        let (enabled, interrupt, source) = match take_stop_state(&STOP_STATE) {
+            Some(state) => state,
+            None => return,
+        };
+        self.csr.set_source(source);
+        self.cvr.clear_current_value();
+        self.csr.set_interrupt(interrupt);
+        self.csr.set_enable(enabled);
+    }
+
+    /// Busy wait until the counter has counted down the specified number of ticks.
+    ///
+    /// The counter keeps running with its current configuration, this only watches the current
+    /// value and accounts for any reloads that happen while waiting.
+    ///
+    /// # Panics
+    ///
+    /// The counter must be enabled or the kernel will panic.
+    pub fn wait_ticks(&self, ticks: u32) {
+        if !self.is_counter_enabled() {
+            panic!("SysTick::wait_ticks - the counter is not enabled!");
+        }
+        let reload = self.get_reload_value();
+        let mut last = self.get_current_value();
+        let mut elapsed = 0;
+        while elapsed < ticks {
+            let current = self.get_current_value();
+            elapsed = elapsed.saturating_add(elapsed_ticks(last, current, reload));
+            last = current;
+        }
+    }
+}
+
+/// Register a function to be called on every SysTick interrupt.
+///
+/// The handler is called by the crate's SysTick exception handler after the system tick has been
+/// accounted for, so it should be kept short. Registering a new handler replaces the old one.
+pub fn set_handler(handler: fn()) {
+    HANDLER.store(handler);
+}
+
+#[doc(hidden)]
+pub fn run_handler() {
+    HANDLER.run();
+}
+
+fn stop_state(enabled: bool, interrupt: bool, source: ClockSource) -> usize {
+    let mut state = STOP_SAVED;
+    if enabled {
+        state |= STOP_ENABLED;
+    }
+    if interrupt {
+        state |= STOP_INTERRUPT;
+    }
+    if source == ClockSource::Processor {
+        state |= STOP_PROCESSOR;
+    }
+    state
+}
+
+// Takes the saved configuration so it's only restored once.
+fn take_stop_state(slot: &AtomicUsize) -> Option<(bool, bool, ClockSource)> {
+    let state = {
+        let _g = CriticalSection::begin();
+        let state = slot.load(Ordering::SeqCst);
+        slot.store(0, Ordering::SeqCst);
+        state
+    };
+    if state & STOP_SAVED == 0 {
+        return None;
+    }
+    let source = if state & STOP_PROCESSOR != 0 {
+        ClockSource::Processor
+    }
+    else {
+        ClockSource::Reference
+    };
+    Some((state & STOP_ENABLED != 0, state & STOP_INTERRUPT != 0, source))
+}
+
+fn latch_underflow(latch: &AtomicUsize, underflow: bool) {
+    if underflow {
+        latch.store(1, Ordering::SeqCst);
+    }
+}
+
+fn take_tick_from(latch: &AtomicUsize, underflow: bool) -> bool {
+    latch_underflow(latch, underflow);
+    take_latch(latch)
+}
+
+fn peek_latch(latch: &AtomicUsize) -> bool {
+    latch.load(Ordering::SeqCst) != 0
+}
+
+fn take_latch(latch: &AtomicUsize) -> bool {
+    let _g = CriticalSection::begin();
+    let set = latch.load(Ordering::SeqCst) != 0;
+    latch.store(0, Ordering::SeqCst);
+    set
+}
+
+/// Get the number of counter ticks that make up the specified number of microseconds, given the
+/// system clock rate and the clock source driving the counter.
+///
+/// The reference clock runs at the system clock rate divided by 8.
+pub fn ticks_for_us(us: u32, clock_rate: Hertz, source: ClockSource) -> u32 {
+    let tick_rate = match source {
+        ClockSource::Processor => clock_rate.0,
+        ClockSource::Reference => clock_rate.0 / 8,
+    };
+    let ticks_per_us = tick_rate / 1_000_000;
+    // Never return 0 ticks for a nonzero delay, even on a slow clock.
+    if us != 0 && ticks_per_us == 0 {
+        return 1;
+    }
+    us.saturating_mul(ticks_per_us)
+}
+
+// The counter counts down from the reload value to 0, then gets reset back to the reload value.
+// If the current value is above the last one we saw, the counter must have reloaded in between.
+fn elapsed_ticks(last: u32, current: u32, reload: u32) -> u32 {
+    if current <= last {
+        last - current
+    }
+    else {
+        last + (reload - current) + 1
+    }
+}
+
+// NOREF in the calibration register is set when the part has no reference clock.
+fn has_reference_clock(calib: u32) -> bool {
+    calib & NOREF == 0
+}
+
+// The clock source to use when the reference clock is asked for, falling back to the processor
+// clock if there isn't one.
+fn reference_or_fallback(calib: u32) -> ClockSource {
+    if has_reference_clock(calib) {
+        ClockSource::Reference
+    }
+    else {
+        ClockSource::Processor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Simulates COUNTFLAG, which reads as set once and then clears itself.
+    struct ClearOnRead(bool);
+
+    impl ClearOnRead {
+        fn read(&mut self) -> bool {
+            let flag = self.0;
+            self.0 = false;
+            flag
+        }
+    }
+
+    #[test]
+    fn test_counter_and_interrupt_enabled_are_independent() {
+        let mut systick: RawSysTick = unsafe { ::core::mem::zeroed() };
+        assert!(!systick.is_counter_enabled());
+        assert!(!systick.is_interrupt_enabled());
+
+        systick.enable_interrupts();
+        assert!(!systick.is_counter_enabled());
+        assert!(systick.is_interrupt_enabled());
+
+        systick.enable_counter();
+        systick.disable_interrupts();
+        assert!(systick.is_counter_enabled());
+        assert!(!systick.is_interrupt_enabled());
+    }
+
+    #[test]
+    fn test_underflow_latch_peek_is_stable_across_reads() {
+        let latch = AtomicUsize::new(0);
+        let mut countflag = ClearOnRead(true);
+
+        latch_underflow(&latch, countflag.read());
+        assert!(peek_latch(&latch));
+
+        // The hardware flag is gone now, but the latch still remembers it
+        latch_underflow(&latch, countflag.read());
+        assert!(peek_latch(&latch));
+    }
+
+    #[test]
+    fn test_underflow_latch_take_consumes_once() {
+        let latch = AtomicUsize::new(0);
+        let mut countflag = ClearOnRead(true);
+
+        latch_underflow(&latch, countflag.read());
+        assert!(take_latch(&latch));
+
+        latch_underflow(&latch, countflag.read());
+        assert!(!take_latch(&latch));
+        assert!(!peek_latch(&latch));
+    }
+
+    #[test]
+    fn test_underflow_latch_no_underflow() {
+        let latch = AtomicUsize::new(0);
+        let mut countflag = ClearOnRead(false);
+
+        latch_underflow(&latch, countflag.read());
+        assert!(!peek_latch(&latch));
+        assert!(!take_latch(&latch));
+    }
+
+    #[test]
+    fn test_take_tick_reports_each_underflow_once() {
+        let latch = AtomicUsize::new(0);
+        let mut countflag = ClearOnRead(true);
+
+        assert!(take_tick_from(&latch, countflag.read()));
+        assert!(!take_tick_from(&latch, countflag.read()));
+
+        // The next period
+        countflag.0 = true;
+        assert!(take_tick_from(&latch, countflag.read()));
+        assert!(!take_tick_from(&latch, countflag.read()));
+    }
+
+    #[test]
+    fn test_take_tick_after_peek_still_reports_tick() {
+        let latch = AtomicUsize::new(0);
+        let mut countflag = ClearOnRead(true);
+
+        // A peek clears COUNTFLAG in hardware, the tick must not be lost
+        latch_underflow(&latch, countflag.read());
+        assert!(peek_latch(&latch));
+        assert!(take_tick_from(&latch, countflag.read()));
+        assert!(!peek_latch(&latch));
+    }
+
+    #[test]
+    fn test_stop_state_round_trip() {
+        let slot = AtomicUsize::new(0);
+
+        slot.store(stop_state(true, true, ClockSource::Processor), Ordering::SeqCst);
+        assert_eq!(take_stop_state(&slot), Some((true, true, ClockSource::Processor)));
+
+        slot.store(stop_state(true, false, ClockSource::Reference), Ordering::SeqCst);
+        assert_eq!(take_stop_state(&slot), Some((true, false, ClockSource::Reference)));
+    }
+
+    #[test]
+    fn test_stop_state_disabled_counter_is_still_saved() {
+        let slot = AtomicUsize::new(0);
+
+        slot.store(stop_state(false, false, ClockSource::Reference), Ordering::SeqCst);
+        assert_eq!(take_stop_state(&slot), Some((false, false, ClockSource::Reference)));
+    }
+
+    #[test]
+    fn test_stop_state_nothing_saved() {
+        let slot = AtomicUsize::new(0);
+
+        assert_eq!(take_stop_state(&slot), None);
+    }
+
+    #[test]
+    fn test_stop_state_is_restored_once() {
+        let slot = AtomicUsize::new(0);
+
+        slot.store(stop_state(true, true, ClockSource::Processor), Ordering::SeqCst);
+        assert!(take_stop_state(&slot).is_some());
+        assert_eq!(take_stop_state(&slot), None);
+    }
+
+    #[test]
+    fn test_ticks_for_us_processor_clock() {
+        assert_eq!(ticks_for_us(10, Hertz(48_000_000), ClockSource::Processor), 480);
+    }
+
+    #[test]
+    fn test_ticks_for_us_reference_clock_is_divided_by_8() {
+        assert_eq!(ticks_for_us(10, Hertz(48_000_000), ClockSource::Reference), 60);
+    }
+
+    #[test]
+    fn test_ticks_for_us_zero_is_zero() {
+        assert_eq!(ticks_for_us(0, Hertz(48_000_000), ClockSource::Processor), 0);
+    }
+
+    #[test]
+    fn test_ticks_for_us_slow_clock_waits_at_least_one_tick() {
+        assert_eq!(ticks_for_us(5, Hertz(4_000_000), ClockSource::Reference), 1);
+    }
+
+    #[test]
+    fn test_ticks_for_us_saturates() {
+        let ticks = ticks_for_us(0xFFFF_FFFF, Hertz(48_000_000), ClockSource::Processor);
+        assert_eq!(ticks, 0xFFFF_FFFF);
+    }
+
+    #[test]
+    fn test_reference_clock_used_when_available() {
+        assert!(has_reference_clock(6000));
+        assert_eq!(reference_or_fallback(6000), ClockSource::Reference);
+    }
+
+    #[test]
+    fn test_reference_clock_falls_back_with_noref() {
+        assert!(!has_reference_clock(NOREF | 6000));
+        assert_eq!(reference_or_fallback(NOREF | 6000), ClockSource::Processor);
+    }
+
+    #[test]
+    fn test_use_reference_clock_reports_fallback() {
+        let mut systick: RawSysTick = unsafe { ::core::mem::zeroed() };
+        assert!(systick.use_reference_clock());
+        assert_eq!(systick.get_clock_source(), ClockSource::Reference);
+
+        systick.calib = NOREF;
+        assert!(!systick.use_reference_clock());
+        assert_eq!(systick.get_clock_source(), ClockSource::Processor);
+    }
+
+    #[test]
+    fn test_elapsed_ticks_without_reload() {
+        assert_eq!(elapsed_ticks(1000, 400, 47_999), 600);
+    }
+
+    #[test]
+    fn test_elapsed_ticks_across_reload() {
+        // 100 ticks down to 0, one tick to reload, then 10 more.
+        assert_eq!(elapsed_ticks(100, 47_989, 47_999), 111);
+    }
+
+    #[test]
+    fn test_elapsed_ticks_no_change_is_zero() {
+        assert_eq!(elapsed_ticks(500, 500, 47_999), 0);
+    }
 }
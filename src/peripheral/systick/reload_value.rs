@@ -16,6 +16,7 @@
 */
 
 use super::defs::*;
+use super::SysTickError;
 
 /// The Reload Value Register specifies the start value to load into the SYST_CVR
 /// (Current Value Register).
@@ -36,6 +37,17 @@ impl RVR {
 
         self.0 = value;
     }
+
+    /// Set the reload value of the register, or leave it alone and return an error if the value
+    /// is > 0xFFFFFF.
+    pub fn try_set_reload_value(&mut self, value: u32) -> Result<(), SysTickError> {
+        if value & !RELOAD != 0 {
+            return Err(SysTickError::ReloadOutOfRange);
+        }
+
+        self.0 = value;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -64,4 +76,29 @@ mod tests {
 
         rvr.set_reload_value(0xFFFFFF + 1);
     }
+
+    #[test]
+    fn test_rvr_set_reload_value_max() {
+        let mut rvr = RVR(0);
+
+        rvr.set_reload_value(0xFFFFFF);
+        assert_eq!(rvr.get_reload_value(), 0xFFFFFF);
+    }
+
+    #[test]
+    fn test_rvr_try_set_reload_value_max() {
+        let mut rvr = RVR(0);
+
+        assert_eq!(rvr.try_set_reload_value(0xFFFFFF), Ok(()));
+        assert_eq!(rvr.0, 0xFFFFFF);
+    }
+
+    #[test]
+    fn test_rvr_try_set_reload_value_greater_than_0xffffff_is_rejected() {
+        let mut rvr = RVR(0x1234);
+
+        assert_eq!(rvr.try_set_reload_value(0xFFFFFF + 1), Err(SysTickError::ReloadOutOfRange));
+        assert_eq!(rvr.try_set_reload_value(0xFFFFFFFF), Err(SysTickError::ReloadOutOfRange));
+        assert_eq!(rvr.0, 0x1234);
+    }
 }
@@ -0,0 +1,265 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! This module provides lightweight periodic software timers driven by the system tick.
+//!
+//! Each timer counts down once per tick, when it reaches zero its callback is called and it's
+//! reloaded with its period.
+
+use altos_core::sync::CriticalSection;
+
+/// Identifies a timer that has been added to a `TimerWheel`.
+///
+/// The id carries the generation of the timer as well as its slot, so an id kept around after
+/// its timer was cancelled doesn't refer to a newer timer that reused the slot.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TimerId {
+    index: usize,
+    generation: u32,
+}
+
+/// A periodic timer slot in a `TimerWheel`.
+#[derive(Copy, Clone)]
+pub struct Timer {
+    period: u32,
+    remaining: u32,
+    callback: fn(),
+    generation: u32,
+}
+
+/// A fixed capacity set of periodic timers, the capacity is the length of the backing slots.
+///
+/// Example Usage:
+/// ```
+///   static mut SLOTS: [Option<Timer>; 4] = [None; 4];
+///   static mut WHEEL: Option<TimerWheel<'static>> = None;
+///
+///   fn on_tick() {
+///       unsafe { WHEEL.as_mut().unwrap().tick(); }
+///   }
+///
+///   // During initialization
+///   unsafe { WHEEL = Some(TimerWheel::new(&mut SLOTS)); }
+///   systick::set_handler(on_tick);
+///   let id = unsafe { WHEEL.as_mut().unwrap().add_timer(500, blink).unwrap() };
+/// ```
+pub struct TimerWheel<'a> {
+    slots: &'a mut [Option<Timer>],
+    generation: u32,
+}
+
+impl<'a> TimerWheel<'a> {
+    /// Create a new timer wheel using `slots` as storage. Any timers already in the slots are
+    /// cleared.
+    pub fn new(slots: &'a mut [Option<Timer>]) -> Self {
+        for slot in slots.iter_mut() {
+            *slot = None;
+        }
+        TimerWheel {
+            slots: slots,
+            generation: 0,
+        }
+    }
+
+    /// Add a timer that calls `callback` every `period` ticks.
+    ///
+    /// Returns `None` if every slot is already in use.
+    ///
+    /// # Panics
+    ///
+    /// The period must be nonzero or the kernel will panic.
+    pub fn add_timer(&mut self, period: u32, callback: fn()) -> Option<TimerId> {
+        if period == 0 {
+            panic!("TimerWheel::add_timer - period must be nonzero!");
+        }
+        let _g = CriticalSection::begin();
+        let generation = self.generation;
+        for (index, slot) in self.slots.iter_mut().enumerate() {
+            if slot.is_none() {
+                *slot = Some(Timer {
+                    period: period,
+                    remaining: period,
+                    callback: callback,
+                    generation: generation,
+                });
+                self.generation = generation.wrapping_add(1);
+                return Some(TimerId {
+                    index: index,
+                    generation: generation,
+                });
+            }
+        }
+        None
+    }
+
+    /// Stop a timer and free its slot. Cancelling a timer that was already cancelled does nothing,
+    /// even if its slot has since been reused by another timer.
+    pub fn cancel(&mut self, id: TimerId) {
+        let _g = CriticalSection::begin();
+        if self.is_active(id) {
+            self.slots[id.index] = None;
+        }
+    }
+
+    /// Check if a timer is still running.
+    pub fn is_active(&self, id: TimerId) -> bool {
+        match self.slots.get(id.index) {
+            Some(&Some(ref timer)) => timer.generation == id.generation,
+            _ => false,
+        }
+    }
+
+    /// Advance every timer by one tick, this should be called from the SysTick handler.
+    ///
+    /// All of the timers are counted down before any callbacks are run, so timers that expire on
+    /// the same tick all fire on that tick, in slot order.
+    pub fn tick(&mut self) {
+        for slot in self.slots.iter_mut() {
+            if let Some(ref mut timer) = *slot {
+                timer.remaining -= 1;
+            }
+        }
+        for slot in self.slots.iter_mut() {
+            if let Some(ref mut timer) = *slot {
+                if timer.remaining == 0 {
+                    timer.remaining = timer.period;
+                    (timer.callback)();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use altos_core::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
+
+    // Each test uses its own counters since tests run concurrently.
+    static FIRE_A: AtomicUsize = ATOMIC_USIZE_INIT;
+    static FIRE_B: AtomicUsize = ATOMIC_USIZE_INIT;
+    static FIRE_C: AtomicUsize = ATOMIC_USIZE_INIT;
+    static FIRE_D: AtomicUsize = ATOMIC_USIZE_INIT;
+    static FIRE_E: AtomicUsize = ATOMIC_USIZE_INIT;
+
+    fn fire_a() { FIRE_A.fetch_add(1, Ordering::SeqCst); }
+    fn fire_b() { FIRE_B.fetch_add(1, Ordering::SeqCst); }
+    fn fire_c() { FIRE_C.fetch_add(1, Ordering::SeqCst); }
+    fn fire_d() { FIRE_D.fetch_add(1, Ordering::SeqCst); }
+    fn fire_e() { FIRE_E.fetch_add(1, Ordering::SeqCst); }
+    fn nothing() {}
+
+    fn ticks(wheel: &mut TimerWheel, n: usize) {
+        for _ in 0..n {
+            wheel.tick();
+        }
+    }
+
+    #[test]
+    fn test_timer_fires_and_reloads() {
+        let mut slots = [None; 2];
+        let mut wheel = TimerWheel::new(&mut slots);
+        wheel.add_timer(3, fire_a).unwrap();
+
+        ticks(&mut wheel, 2);
+        assert_eq!(FIRE_A.load(Ordering::SeqCst), 0);
+        ticks(&mut wheel, 1);
+        assert_eq!(FIRE_A.load(Ordering::SeqCst), 1);
+        ticks(&mut wheel, 6);
+        assert_eq!(FIRE_A.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_timers_expiring_on_same_tick_all_fire() {
+        let mut slots = [None; 3];
+        let mut wheel = TimerWheel::new(&mut slots);
+        wheel.add_timer(2, fire_b).unwrap();
+        wheel.add_timer(4, fire_c).unwrap();
+
+        ticks(&mut wheel, 4);
+        assert_eq!(FIRE_B.load(Ordering::SeqCst), 2);
+        assert_eq!(FIRE_C.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_cancelled_timer_does_not_fire() {
+        let mut slots = [None; 2];
+        let mut wheel = TimerWheel::new(&mut slots);
+        let id = wheel.add_timer(1, fire_d).unwrap();
+
+        ticks(&mut wheel, 2);
+        wheel.cancel(id);
+        assert!(!wheel.is_active(id));
+        ticks(&mut wheel, 5);
+        assert_eq!(FIRE_D.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_period_of_one_fires_every_tick() {
+        let mut slots = [None; 1];
+        let mut wheel = TimerWheel::new(&mut slots);
+        wheel.add_timer(1, fire_e).unwrap();
+
+        ticks(&mut wheel, 5);
+        assert_eq!(FIRE_E.load(Ordering::SeqCst), 5);
+    }
+
+    #[test]
+    fn test_add_timer_when_full_is_none() {
+        let mut slots = [None; 2];
+        let mut wheel = TimerWheel::new(&mut slots);
+
+        assert!(wheel.add_timer(5, nothing).is_some());
+        assert!(wheel.add_timer(5, nothing).is_some());
+        assert_eq!(wheel.add_timer(5, nothing), None);
+    }
+
+    #[test]
+    fn test_cancel_frees_slot() {
+        let mut slots = [None; 1];
+        let mut wheel = TimerWheel::new(&mut slots);
+        let id = wheel.add_timer(5, nothing).unwrap();
+
+        wheel.cancel(id);
+        assert!(wheel.add_timer(5, nothing).is_some());
+    }
+
+    #[test]
+    fn test_cancel_with_stale_id_keeps_new_timer() {
+        let mut slots = [None; 1];
+        let mut wheel = TimerWheel::new(&mut slots);
+        let stale = wheel.add_timer(5, nothing).unwrap();
+        wheel.cancel(stale);
+
+        // The new timer reuses the slot, but not the id
+        let id = wheel.add_timer(5, nothing).unwrap();
+        assert!(id != stale);
+        assert!(!wheel.is_active(stale));
+
+        wheel.cancel(stale);
+        assert!(wheel.is_active(id));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_add_timer_zero_period_panics() {
+        let mut slots = [None; 1];
+        let mut wheel = TimerWheel::new(&mut slots);
+
+        wheel.add_timer(0, nothing);
+    }
+}
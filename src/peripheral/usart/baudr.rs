@@ -64,4 +64,141 @@ impl BRR {
 
         self.0 = rate;
     }
+
+    /// Set the baud rate from an arbitrary rate in Hz, see `brr_value` for how the register
+    /// value is computed.
+    pub fn set_baud_rate_hz(&mut self, baud: u32, clock_rate: u32, over8: bool) {
+        if baud == 0 {
+            panic!("BRR::set_baud_rate_hz - baud rate must be nonzero!");
+        }
+        self.0 = brr_value(baud, clock_rate, over8);
+    }
+
+    /// Get the baud rate the register is programmed for, rounded to the nearest Hz. Returns 0 if
+    /// the register hasn't been programmed.
+    pub fn get_baud_rate_hz(&self, clock_rate: u32, over8: bool) -> u32 {
+        baud_from_brr(self.0, clock_rate, over8)
+    }
+
+    /// Get the divisor the register is programmed with as its mantissa, USARTDIV[15:4], and
+    /// fraction, USARTDIV[3:0]. When oversampling by 8 the fraction is shifted back into place,
+    /// so it is always in sixteenths.
+    pub fn get_divisor(&self, over8: bool) -> (u16, u8) {
+        let fraction = if over8 { (self.0 & 0b111) << 1 } else { self.0 & DIV_MASK };
+
+        (((self.0 >> 4) & 0xFFF) as u16, fraction as u8)
+    }
+}
+
+/* USARTDIV is the clock rate divided by the baud rate when oversampling by 16, and twice that
+ * when oversampling by 8. The divisor is rounded to the nearest whole value. With OVER8 set,
+ * the low 4 bits of USARTDIV are shifted right by 1 into BRR[2:0] and BRR[3] is left clear.
+ *
+ * The math stays in u32, there is no 64 bit division routine to link against. The F0 tops out
+ * at 48MHz, so doubling the clock can't overflow.
+ */
+fn brr_value(baud: u32, clock_rate: u32, over8: bool) -> u32 {
+    let fck = if over8 { clock_rate * 2 } else { clock_rate };
+    let usartdiv = (fck + baud / 2) / baud;
+
+    if over8 {
+        (usartdiv & !DIV_MASK) | ((usartdiv & DIV_MASK) >> 1)
+    }
+    else {
+        usartdiv
+    }
+}
+
+// The reverse of `brr_value`, the low 3 bits are shifted back into place when oversampling by 8.
+// Like `brr_value` this stays in u32.
+fn baud_from_brr(brr: u32, clock_rate: u32, over8: bool) -> u32 {
+    let (fck, usartdiv) = if over8 {
+        (clock_rate * 2, (brr & !DIV_MASK) | ((brr & 0b111) << 1))
+    }
+    else {
+        (clock_rate, brr & 0xFFFF)
+    };
+    if usartdiv == 0 {
+        return 0;
+    }
+    (fck + usartdiv / 2) / usartdiv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_brr_value_over16() {
+        // 48MHz / 115200 = 416.67
+        assert_eq!(brr_value(115_200, 48_000_000, false), 417);
+    }
+
+    #[test]
+    fn test_brr_value_over8() {
+        // 2 * 48MHz / 115200 = 833.33 = 0x341, BRR = 0x340 | (0x1 >> 1)
+        assert_eq!(brr_value(115_200, 48_000_000, true), 0x340);
+    }
+
+    #[test]
+    fn test_brr_value_over8_shifts_low_bits() {
+        // 2 * 8MHz / 1_000_000 = 16 = 0x10, low bits 0
+        assert_eq!(brr_value(1_000_000, 8_000_000, true), 0x10);
+        // 2 * 8MHz / 888_888 = 18 = 0x12, low bits 0b010 >> 1
+        assert_eq!(brr_value(888_888, 8_000_000, true), 0x11);
+    }
+
+    #[test]
+    fn test_brr_set_baud_rate_hz() {
+        let mut brr = BRR(0);
+
+        brr.set_baud_rate_hz(9_600, 8_000_000, false);
+        assert_eq!(brr.0, 833);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_brr_set_baud_rate_hz_zero_panics() {
+        let mut brr = BRR(0);
+
+        brr.set_baud_rate_hz(0, 8_000_000, false);
+    }
+
+    #[test]
+    fn test_baud_from_brr_round_trip() {
+        // 48MHz / 417 = 115107.9
+        let brr = brr_value(115_200, 48_000_000, false);
+        assert_eq!(baud_from_brr(brr, 48_000_000, false), 115_108);
+
+        // 2 * 48MHz / 832 = 115384.6, the dropped low bit costs some accuracy
+        let brr = brr_value(115_200, 48_000_000, true);
+        assert_eq!(baud_from_brr(brr, 48_000_000, true), 115_385);
+
+        let brr = brr_value(9_600, 8_000_000, true);
+        assert_eq!(baud_from_brr(brr, 8_000_000, true), 9_604);
+    }
+
+    #[test]
+    fn test_brr_get_divisor_over16() {
+        // 8MHz / 9600 = 833 = 0x341
+        let brr = BRR(brr_value(9_600, 8_000_000, false));
+        assert_eq!(brr.get_divisor(false), (0x34, 0x1));
+    }
+
+    #[test]
+    fn test_brr_get_divisor_over8() {
+        // 2 * 8MHz / 888_888 = 18 = 0x12, BRR = 0x11
+        let brr = BRR(brr_value(888_888, 8_000_000, true));
+        assert_eq!(brr.0, 0x11);
+        assert_eq!(brr.get_divisor(true), (0x1, 0x2));
+
+        // BRR = 0x340 holds USARTDIV = 0x340, the dropped low bit doesn't come back
+        assert_eq!(BRR(0x340).get_divisor(true), (0x34, 0x0));
+        assert_eq!(BRR(0x347).get_divisor(true), (0x34, 0xE));
+    }
+
+    #[test]
+    fn test_baud_from_brr_unprogrammed_is_zero() {
+        assert_eq!(BRR(0).get_baud_rate_hz(48_000_000, false), 0);
+    }
 }
@@ -0,0 +1,90 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+/* This submodule contains a decoded view of the Usart configuration for
+ * debug output.
+ */
+
+use core::fmt;
+use super::{Parity, StopLength};
+
+/// The Usart configuration as read back from its registers.
+///
+/// Displays in the usual compact serial notation, e.g. `115200 8N1`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct UsartConfig {
+    /// Baud rate in Hz, 0 if the baud rate hasn't been programmed.
+    pub baud: u32,
+    /// Number of data bits per frame, not counting the parity bit.
+    pub data_bits: u8,
+    /// Parity configuration.
+    pub parity: Parity,
+    /// Number of stop bits.
+    pub stop_bits: StopLength,
+}
+
+impl fmt::Display for UsartConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let parity = match self.parity {
+            Parity::None => 'N',
+            Parity::Even => 'E',
+            Parity::Odd => 'O',
+        };
+        let stop_bits = match self.stop_bits {
+            StopLength::Half => "0.5",
+            StopLength::One => "1",
+            StopLength::OneAndHalf => "1.5",
+            StopLength::Two => "2",
+        };
+        write!(f, "{} {}{}{}", self.baud, self.data_bits, parity, stop_bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usart_config_display() {
+        let config = UsartConfig {
+            baud: 115_200,
+            data_bits: 8,
+            parity: Parity::None,
+            stop_bits: StopLength::One,
+        };
+        assert_eq!(format!("{}", config), "115200 8N1");
+    }
+
+    #[test]
+    fn test_usart_config_display_fractional_stop_bits() {
+        let config = UsartConfig {
+            baud: 9_600,
+            data_bits: 7,
+            parity: Parity::Even,
+            stop_bits: StopLength::OneAndHalf,
+        };
+        assert_eq!(format!("{}", config), "9600 7E1.5");
+
+        let config = UsartConfig {
+            baud: 4_800,
+            data_bits: 8,
+            parity: Parity::Odd,
+            stop_bits: StopLength::Half,
+        };
+        assert_eq!(format!("{}", config), "4800 8O0.5");
+    }
+}
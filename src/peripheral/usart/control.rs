@@ -20,6 +20,7 @@
  * configuration of the Usart.
  */
 
+use super::super::Register;
 use super::defs::*;
 
 /// Defines the possible HardwareFlowControl configurations for the Usart.
@@ -36,7 +37,7 @@ pub enum HardwareFlowControl {
 }
 
 /// Defines the possible Parity configurations for the Usart.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Parity {
     /// No parity configuration set.
     None,
@@ -47,7 +48,7 @@ pub enum Parity {
 }
 
 /// Defines the possible StopLength configurations for the Usart.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum StopLength {
     /// 0.5 stop bit
     Half,
@@ -70,8 +71,47 @@ pub enum WordLength {
     Nine,
 }
 
-/// Defines the possible Mode configurations for the Usart.
+/// Defines the possible methods for waking the Usart from mute mode.
 #[derive(Copy, Clone, Debug)]
+pub enum WakeMethod {
+    /// Wake up when an idle line is detected.
+    IdleLine,
+    /// Wake up when a frame with the Usart's address is received.
+    AddressMark,
+}
+
+/// Defines the events that can wake the Usart, and with it the MCU, from Stop mode.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum WakeupSource {
+    /// Wake up when a frame with the Usart's address is received.
+    AddressMatch,
+    /// Wake up on the start bit of a frame.
+    StartBit,
+    /// Wake up once a full frame has been received.
+    ReceiverNotEmpty,
+}
+
+impl WakeupSource {
+    fn mask(&self) -> u32 {
+        match *self {
+            WakeupSource::AddressMatch => WUS_ADDRESS_MATCH,
+            WakeupSource::StartBit => WUS_START_BIT,
+            WakeupSource::ReceiverNotEmpty => WUS_RXNE,
+        }
+    }
+}
+
+/// Defines the possible address lengths used for address mark wake up.
+#[derive(Copy, Clone, Debug)]
+pub enum AddressLength {
+    /// The address is compared against the low 4 bits of the received frame.
+    Four,
+    /// The address is compared against the low 7 bits of the received frame.
+    Seven,
+}
+
+/// Defines the possible Mode configurations for the Usart.
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Mode {
     /// Transmit and Receive turned off.
     None,
@@ -83,6 +123,38 @@ pub enum Mode {
     All,
 }
 
+/// Defines the possible FIFO thresholds for the Usart, as a fraction of the FIFO depth.
+#[cfg(feature="fifo")]
+#[derive(Copy, Clone, Debug)]
+pub enum FifoThreshold {
+    /// FIFO reaches 1/8 of its depth.
+    Eighth,
+    /// FIFO reaches 1/4 of its depth.
+    Quarter,
+    /// FIFO reaches 1/2 of its depth.
+    Half,
+    /// FIFO reaches 3/4 of its depth.
+    ThreeQuarters,
+    /// FIFO reaches 7/8 of its depth.
+    SevenEighths,
+    /// FIFO becomes full (RX) or empty (TX).
+    Full,
+}
+
+#[cfg(feature="fifo")]
+impl FifoThreshold {
+    fn mask(&self) -> u32 {
+        match *self {
+            FifoThreshold::Eighth => FIFO_THRESHOLD_1_8,
+            FifoThreshold::Quarter => FIFO_THRESHOLD_1_4,
+            FifoThreshold::Half => FIFO_THRESHOLD_1_2,
+            FifoThreshold::ThreeQuarters => FIFO_THRESHOLD_3_4,
+            FifoThreshold::SevenEighths => FIFO_THRESHOLD_7_8,
+            FifoThreshold::Full => FIFO_THRESHOLD_FULL,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct CR1(u32);
 #[derive(Copy, Clone, Debug)]
@@ -90,6 +162,8 @@ pub struct CR2(u32);
 #[derive(Copy, Clone, Debug)]
 pub struct CR3(u32);
 
+impl_register!(CR1, CR2, CR3);
+
 impl CR1 {
     /* Uses bit 0 in CR1 to enables or disable the USARTx based on bool
      * variable passed in.
@@ -103,9 +177,11 @@ impl CR1 {
      *          1: USART enabled
      */
     pub fn enable_usart(&mut self, enable: bool) {
-        self.0 &= !(CR1_UE);
         if enable {
-            self.0 |= CR1_UE;
+            self.set_bits(CR1_UE);
+        }
+        else {
+            self.clear_bits(CR1_UE);
         }
     }
 
@@ -113,7 +189,7 @@ impl CR1 {
      * Returns true if enabled (CR1 bit 0 (UE) = 1), false otherwise
      */
     pub fn is_usart_enabled(&self) -> bool {
-        self.0 & CR1_UE != 0
+        self.read() & CR1_UE != 0
     }
 
     /* Uses bits 2 and 3 in CR1 to set the mode to None, Receive, Transmit or All
@@ -134,8 +210,62 @@ impl CR1 {
             Mode::All => (CR1_RE | CR1_TE),
         };
 
-        self.0 &= !(CR1_RE | CR1_TE);
-        self.0 |= mask;
+        self.modify(CR1_RE | CR1_TE, mask);
+    }
+
+    /* Returns the mode set by bits 2 (RE) and 3 (TE), see `set_mode`.
+     *   Neither bit set is Mode::None, both bits set is Mode::All.
+     */
+    pub fn get_mode(&self) -> Mode {
+        match (self.read() & CR1_RE != 0, self.read() & CR1_TE != 0) {
+            (false, false) => Mode::None,
+            (true, false) => Mode::Receive,
+            (false, true) => Mode::Transmit,
+            (true, true) => Mode::All,
+        }
+    }
+
+    /* Uses bit 2 (RE) in CR1 to enable or disable only the receiver, the
+     * transmitter (TE) is left as it is.
+     */
+    pub fn set_receiver_enabled(&mut self, enable: bool) {
+        if enable {
+            self.set_bits(CR1_RE);
+        }
+        else {
+            self.clear_bits(CR1_RE);
+        }
+    }
+
+    /* Uses bit 3 (TE) in CR1 to enable or disable only the transmitter, the
+     * receiver (RE) is left as it is.
+     *   Clearing TE while a frame is being sent lets the current frame
+     *   finish before the transmitter stops.
+     */
+    pub fn set_transmitter_enabled(&mut self, enable: bool) {
+        if enable {
+            self.set_bits(CR1_TE);
+        }
+        else {
+            self.clear_bits(CR1_TE);
+        }
+    }
+
+    /* Uses bit 4 in CR1 to enable or disable the IDLE interrupt based on bool
+     * variable passed in.
+     *  Bit 4 IDLEIE: IDLE interrupt enable
+     *      This bit is set and cleared by software.
+     *          0: Interrupt is inhibited
+     *          1: A USART interrupt is generated whenever IDLE=1 in the
+     *          USARTx_ISR register
+     */
+    pub fn set_idle_interrupt(&mut self, enable: bool) {
+        if enable {
+            self.set_bits(CR1_IDLEIE);
+        }
+        else {
+            self.clear_bits(CR1_IDLEIE);
+        }
     }
 
     /* Uses bit 5 in CR1 to enable or disable RXNE interrupt based on bool
@@ -151,9 +281,11 @@ impl CR1 {
      *
      */
     pub fn set_receiver_not_empty_interrupt(&mut self, enable: bool) {
-        self.0 &= !(CR1_RXNEIE);
         if enable {
-            self.0 |= CR1_RXNEIE;
+            self.set_bits(CR1_RXNEIE);
+        }
+        else {
+            self.clear_bits(CR1_RXNEIE);
         }
     }
 
@@ -169,9 +301,11 @@ impl CR1 {
      *          USARTx_ISR register
      */
     pub fn set_transmit_complete_interrupt(&mut self, enable: bool) {
-        self.0 &= !(CR1_TCIE);
         if enable {
-            self.0 |= CR1_TCIE;
+            self.set_bits(CR1_TCIE);
+        }
+        else {
+            self.clear_bits(CR1_TCIE);
         }
     }
 
@@ -186,9 +320,11 @@ impl CR1 {
      *          USARTx_ISR register
      */
     pub fn set_transmit_interrupt(&mut self, enable: bool) {
-        self.0 &= !(CR1_TXEIE);
         if enable {
-            self.0 |= CR1_TXEIE;
+            self.set_bits(CR1_TXEIE);
+        }
+        else {
+            self.clear_bits(CR1_TXEIE);
         }
     }
 
@@ -216,8 +352,18 @@ impl CR1 {
             Parity::Odd => CR1_PS | CR1_PCE,
         };
 
-        self.0 &= !(CR1_PS | CR1_PCE);
-        self.0 |= mask;
+        self.modify(CR1_PS | CR1_PCE, mask);
+    }
+
+    /* Returns the parity configuration set by bits 9 (PS) and 10 (PCE).
+     *   PS is ignored while PCE is cleared.
+     */
+    pub fn get_parity(&self) -> Parity {
+        match (self.read() & CR1_PCE != 0, self.read() & CR1_PS != 0) {
+            (false, _) => Parity::None,
+            (true, false) => Parity::Even,
+            (true, true) => Parity::Odd,
+        }
     }
 
     /* Uses bits 12 and 28 to set the word length to Seven, Eight, or Nine
@@ -235,8 +381,64 @@ impl CR1 {
             WordLength::Nine => CR1_M0,
         };
 
-        self.0 &= !(CR1_M0 | CR1_M1);
-        self.0 |= mask;
+        self.modify(CR1_M0 | CR1_M1, mask);
+    }
+
+    /* Sets the word length and parity together from the number of data bits.
+     *   When parity is enabled the parity bit takes the MSB of the word, so
+     *   the word length is one more than the number of data bits.
+     *          Parity::None: 7, 8 or 9 data bits
+     *          Parity::Even/Odd: 6, 7 or 8 data bits
+     *   Any other combination can't be represented and the kernel will panic.
+     */
+    pub fn configure_frame(&mut self, data_bits: u8, parity: Parity) {
+        let frame_bits = match parity {
+            Parity::None => Some(data_bits),
+            Parity::Even | Parity::Odd => data_bits.checked_add(1),
+        };
+        let length = match frame_bits {
+            Some(7) => WordLength::Seven,
+            Some(8) => WordLength::Eight,
+            Some(9) => WordLength::Nine,
+            _ => panic!("CR1::configure_frame - data bits and parity don't fit in a 7, 8 or 9 bit word!"),
+        };
+
+        self.set_word_length(length);
+        self.set_parity(parity);
+    }
+
+    /* Returns the number of bits in the word set by M[1:0], parity bit
+     * included.
+     *      00: 8 bits
+     *      01: 9 bits
+     *      10: 7 bits
+     *   M[1:0] = 11 is reserved and the kernel will panic.
+     */
+    pub fn get_word_bits(&self) -> u8 {
+        match (self.read() & CR1_M1 != 0, self.read() & CR1_M0 != 0) {
+            (true, false) => 7,
+            (false, false) => 8,
+            (false, true) => 9,
+            (true, true) => panic!("CR1::get_word_bits - word length M[1:0] = 11 is reserved!"),
+        }
+    }
+
+    /* Returns the number of bits in a frame on the wire, given the stop
+     * length set in CR2.
+     *   A frame is 1 start bit followed by a 7, 8 or 9 bit word and the stop
+     *   bits. When parity is enabled the parity bit is inserted at the MSB of
+     *   the word in place of a data bit, so parity doesn't change the frame
+     *   length. 0.5 and 1.5 stop bits are rounded up to a whole bit, so the
+     *   result is never shorter than the frame.
+     *   M[1:0] = 11 is reserved and the kernel will panic.
+     */
+    pub fn effective_frame_bits(&self, stop_bits: StopLength) -> u8 {
+        let stop = match stop_bits {
+            StopLength::Half | StopLength::One => 1,
+            StopLength::OneAndHalf | StopLength::Two => 2,
+        };
+
+        1 + self.get_word_bits() + stop
     }
 
     /* Uses bit 15 to enable or disable oversampling by 8 based on the bool
@@ -246,9 +448,11 @@ impl CR1 {
      *          1: Oversampling by 8
      */
     pub fn set_over8(&mut self, enable: bool) {
-        self.0 &= !(CR1_OVER8);
         if enable {
-            self.0 |= CR1_OVER8;
+            self.set_bits(CR1_OVER8);
+        }
+        else {
+            self.clear_bits(CR1_OVER8);
         }
     }
 
@@ -256,7 +460,70 @@ impl CR1 {
      *  Returns true if enabled (CR1 bit 15 (Over8) = 1), false otherwise.
      */
     pub fn get_over8(&self) -> bool {
-        self.0 & CR1_OVER8 != 0
+        self.read() & CR1_OVER8 != 0
+    }
+
+    /* Uses bits 11 and 13 to enable mute mode with the specified wake method.
+     *  Bit 11 WAKE: Receiver wakeup method
+     *      This bit determines the USART wakeup method from Mute mode. It is
+     *      set or cleared by software.
+     *          0: Idle line
+     *          1: Address mark
+     *      This bit field can only be written when the USART is disabled (UE=0).
+     *  Bit 13 MME: Mute mode enable
+     *      This bit activates the mute mode function of the USART. When set,
+     *      the USART can switch between the active and mute modes, as defined
+     *      by the WAKE bit. It is set and cleared by software.
+     *          0: Receiver in active mode permanently
+     *          1: Receiver can switch between mute mode and active mode.
+     */
+    pub fn enable_mute_mode(&mut self, wake: WakeMethod) {
+        let mask = match wake {
+            WakeMethod::IdleLine => CR1_MME,
+            WakeMethod::AddressMark => CR1_MME | CR1_WAKE,
+        };
+
+        self.modify(CR1_MME | CR1_WAKE, mask);
+    }
+
+    /* Bit 1 UESM: USART enable in Stop mode
+     *   When this bit is cleared, the USART is not able to wake up the MCU
+     *   from Stop mode. When this bit is set, the USART is able to wake up
+     *   the MCU from Stop mode, provided that the USART clock selection is HSI
+     *   or LSE in the RCC. This bit is set and cleared by software.
+     *      0: USART not able to wake up the MCU from Stop mode.
+     *      1: USART able to wake up the MCU from Stop mode.
+     */
+    pub fn set_stop_mode_enable(&mut self, enable: bool) {
+        if enable {
+            self.set_bits(CR1_UESM);
+        }
+        else {
+            self.clear_bits(CR1_UESM);
+        }
+    }
+
+    /* Clears bit 13 (MME) so the receiver stays in active mode permanently.
+     */
+    pub fn disable_mute_mode(&mut self) {
+        self.clear_bits(CR1_MME);
+    }
+
+    /* Bit 29 FIFOEN: FIFO mode enable
+     *   This bit is set and cleared by software.
+     *      0: FIFO mode is disabled.
+     *      1: FIFO mode is enabled.
+     *   This bitfield can only be written when the USART is disabled (UE=0).
+     *   Note: Only available on parts with a USART FIFO.
+     */
+    #[cfg(feature="fifo")]
+    pub fn enable_fifo(&mut self, enable: bool) {
+        if enable {
+            self.set_bits(CR1_FIFOEN);
+        }
+        else {
+            self.clear_bits(CR1_FIFOEN);
+        }
     }
 }
 
@@ -280,6 +547,66 @@ impl CR2 {
         self.0 &= !(CR2_STOP_BIT0 | CR2_STOP_BIT1);
         self.0 |= mask;
     }
+
+    /* Returns the stop length set by bits 12 and 13, see `set_stop_bits`
+     * for the encoding.
+     */
+    pub fn get_stop_bits(&self) -> StopLength {
+        match (self.0 & CR2_STOP_BIT1 != 0, self.0 & CR2_STOP_BIT0 != 0) {
+            (false, false) => StopLength::One,
+            (false, true) => StopLength::Half,
+            (true, false) => StopLength::Two,
+            (true, true) => StopLength::OneAndHalf,
+        }
+    }
+
+    /* Uses bit 4 and bits 31:24 to set the address of the USART node.
+     *      Bit 4 ADDM7: 7-bit Address Detection/4-bit Address Detection
+     *          This bit is for selection between 4-bit address detection or
+     *          7-bit address detection.
+     *              0: 4-bit address detection
+     *              1: 7-bit address detection (in 8-bit data mode)
+     *      Bits 31:24 ADD[7:0]: Address of the USART node
+     *          This bit-field gives the address of the USART node or a
+     *          character code to be recognized. This is used in multiprocessor
+     *          communication during Mute mode, for wakeup with address mark
+     *          detection.
+     *      These bits can only be written when the USART is disabled (UE=0).
+     */
+    pub fn set_address(&mut self, address: u8, length: AddressLength) {
+        let mask = match length {
+            AddressLength::Four => {
+                if address > 0xF {
+                    panic!("CR2::set_address - 4-bit address must be a value between [0..15]!");
+                }
+                0
+            },
+            AddressLength::Seven => {
+                if address > 0x7F {
+                    panic!("CR2::set_address - 7-bit address must be a value between [0..127]!");
+                }
+                CR2_ADDM7
+            },
+        };
+
+        self.0 &= !(CR2_ADD_MASK | CR2_ADDM7);
+        self.0 |= mask | ((address as u32) << CR2_ADD_SHIFT);
+    }
+
+    /* Uses bit 16 to invert the RX pin level.
+     *      Bit 16 RXINV: RX pin active level inversion
+     *          0: RX pin signal works using the standard logic levels
+     *              (VDD = 1/idle, Gnd = 0/mark)
+     *          1: RX pin signal values are inverted
+     *              (VDD = 0/mark, Gnd = 1/idle)
+     *      This bit can only be written when the USART is disabled (UE=0).
+     */
+    pub fn set_rx_inversion(&mut self, invert: bool) {
+        self.0 &= !(CR2_RXINV);
+        if invert {
+            self.0 |= CR2_RXINV;
+        }
+    }
 }
 
 /// Defines the possible DMA Mode configurations for the Usart.
@@ -316,6 +643,19 @@ impl CR3 {
         self.0 |= mask;
     }
 
+    /* Clears only the DMAR and/or DMAT bits selected by `mode`, the other one is left as it is.
+     */
+    pub fn disable_dma_mode(&mut self, mode: DMAMode) {
+        let mask = match mode {
+            DMAMode::None => 0,
+            DMAMode::Receive => CR3_DMAR,
+            DMAMode::Transmit => CR3_DMAT,
+            DMAMode::All => (CR3_DMAR | CR3_DMAT),
+        };
+
+        self.0 &= !mask;
+    }
+
     /* Uses bit 8 and 9 in CR3 to set the hardware flow control to None, Rts,
      * Cts, All.
      *      Bit 8 RTSE: RTS enable
@@ -345,12 +685,167 @@ impl CR3 {
         self.0 &= !(CR3_RTSE | CR3_CTSE);
         self.0 |= mask;
     }
+
+    /* Bit 0 EIE: Error interrupt enable
+     *   Error Interrupt Enable Bit is required to enable interrupt generation
+     *   in case of a framing error, overrun error or noise flag (FE=1 or
+     *   ORE=1 or NF=1 in the USART_ISR register) when DMAR=1.
+     *      0: Interrupt is inhibited
+     *      1: An interrupt is generated when FE=1 or ORE=1 or NF=1 in the
+     *         USART_ISR register.
+     */
+    pub fn set_error_interrupt(&mut self, enable: bool) {
+        self.0 &= !CR3_EIE;
+        if enable {
+            self.0 |= CR3_EIE;
+        }
+    }
+
+    /* Bit 10 CTSIE: CTS interrupt enable
+     *      0: Interrupt is inhibited
+     *      1: An interrupt is generated whenever CTSIF=1 in the USART_ISR
+     *         register.
+     */
+    pub fn set_cts_interrupt(&mut self, enable: bool) {
+        self.0 &= !CR3_CTSIE;
+        if enable {
+            self.0 |= CR3_CTSIE;
+        }
+    }
+
+    /* Bits 21:20 WUS[1:0]: Wakeup from Stop mode interrupt flag selection
+     *   This bit-field specify the event which activates the WUF (Wakeup from
+     *   Stop mode flag).
+     *      00: WUF active on address match (as defined by ADD[7:0] and ADDM7)
+     *      01: Reserved.
+     *      10: WUF active on Start bit detection
+     *      11: WUF active on RXNE.
+     *   This bit field can only be written when the USART is disabled (UE=0).
+     */
+    pub fn set_wakeup_source(&mut self, source: WakeupSource) {
+        self.0 &= !CR3_WUS_MASK;
+        self.0 |= source.mask() << CR3_WUS_SHIFT;
+    }
+
+    /* Bit 22 WUFIE: Wakeup from Stop mode interrupt enable
+     *   This bit is set and cleared by software.
+     *      0: Interrupt is inhibited
+     *      1: An USART interrupt is generated whenever WUF=1 in the USART_ISR
+     *         register
+     */
+    pub fn set_wakeup_interrupt(&mut self, enable: bool) {
+        self.0 &= !CR3_WUFIE;
+        if enable {
+            self.0 |= CR3_WUFIE;
+        }
+    }
+
+    /* Bit 1 IREN: IrDA mode enable
+     *   This bit is set and cleared by software.
+     *      0: IrDA disabled
+     *      1: IrDA enabled
+     */
+    pub fn is_irda_enabled(&self) -> bool {
+        self.0 & CR3_IREN != 0
+    }
+
+    /* Bit 5 SCEN: Smartcard mode enable
+     *   This bit is used for enabling Smartcard mode.
+     *      0: Smartcard Mode disabled
+     *      1: Smartcard Mode enabled
+     */
+    pub fn is_smartcard_enabled(&self) -> bool {
+        self.0 & CR3_SCEN != 0
+    }
+
+    /* Smartcard mode needs 1.5 stop bits (0.5 can't be used), and IrDA mode
+     * only works with 1 stop bit (0.5 and 1.5 can't be used).
+     */
+    pub fn supports_stop_bits(&self, length: StopLength) -> bool {
+        match length {
+            StopLength::Half => !self.is_smartcard_enabled() && !self.is_irda_enabled(),
+            StopLength::OneAndHalf => !self.is_irda_enabled(),
+            StopLength::One | StopLength::Two => true,
+        }
+    }
+
+    /* Bit 11 ONEBIT: One sample bit method enable
+     *   This bit allows the user to select the sample method. When the one
+     *   sample bit method is selected the noise detection flag (NF) is
+     *   disabled.
+     *      0: Three sample bit method
+     *      1: One sample bit method
+     *   This bit can only be written when the USART is disabled (UE=0).
+     */
+    pub fn set_one_bit_sampling(&mut self, enable: bool) {
+        self.0 &= !CR3_ONEBIT;
+        if enable {
+            self.0 |= CR3_ONEBIT;
+        }
+    }
+
+    /* Bits 27:25 RXFTCFG: Receive FIFO threshold configuration
+     *   Bit 28 RXFTIE: RXFIFO threshold interrupt enable
+     *      An interrupt is generated when the receive FIFO reaches the
+     *      threshold programmed in RXFTCFG.
+     *          000: Receive FIFO reaches 1/8 of its depth
+     *          001: Receive FIFO reaches 1/4 of its depth
+     *          010: Receive FIFO reaches 1/2 of its depth
+     *          011: Receive FIFO reaches 3/4 of its depth
+     *          100: Receive FIFO reaches 7/8 of its depth
+     *          101: Receive FIFO becomes full
+     *   Note: Only available on parts with a USART FIFO.
+     */
+    #[cfg(feature="fifo")]
+    pub fn set_rx_fifo_threshold(&mut self, threshold: FifoThreshold) {
+        self.0 &= !(CR3_RXFTCFG_MASK);
+        self.0 |= threshold.mask() << CR3_RXFTCFG_SHIFT;
+    }
+
+    /* Bits 31:29 TXFTCFG: Transmit FIFO threshold configuration
+     *   Bit 23 TXFTIE: TXFIFO threshold interrupt enable
+     *      An interrupt is generated when the transmit FIFO reaches the
+     *      threshold programmed in TXFTCFG.
+     *          000: TXFIFO reaches 1/8 of its depth
+     *          001: TXFIFO reaches 1/4 of its depth
+     *          010: TXFIFO reaches 1/2 of its depth
+     *          011: TXFIFO reaches 3/4 of its depth
+     *          100: TXFIFO reaches 7/8 of its depth
+     *          101: TXFIFO becomes empty
+     *   Note: Only available on parts with a USART FIFO.
+     */
+    #[cfg(feature="fifo")]
+    pub fn set_tx_fifo_threshold(&mut self, threshold: FifoThreshold) {
+        self.0 &= !(CR3_TXFTCFG_MASK);
+        self.0 |= threshold.mask() << CR3_TXFTCFG_SHIFT;
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_cr1_single_bit_setters_keep_other_bits() {
+        let mut cr1 = CR1(0xFFFF_FFFF);
+
+        cr1.set_over8(false);
+        assert_eq!(cr1.0, !CR1_OVER8);
+        cr1.set_over8(true);
+        assert_eq!(cr1.0, 0xFFFF_FFFF);
+    }
+
+    #[test]
+    fn test_cr1_set_mode_matches_register_modify() {
+        let mut cr1 = CR1(CR1_UE | CR1_TE);
+        let mut expected = CR1(CR1_UE | CR1_TE);
+
+        cr1.set_mode(Mode::Receive);
+        expected.modify(CR1_RE | CR1_TE, CR1_RE);
+        assert_eq!(cr1.0, expected.0);
+        assert_eq!(cr1.0, CR1_UE | CR1_RE);
+    }
+
     #[test]
     fn test_cr1_enable_disable_usart() {
         let mut cr1 = CR1(0);
@@ -403,6 +898,51 @@ mod tests {
         assert_eq!(cr1.0, 0b11 << 2);
     }
 
+    #[test]
+    fn test_cr1_get_mode_round_trips() {
+        let mut cr1 = CR1(0);
+
+        for &mode in &[Mode::Receive, Mode::Transmit, Mode::All, Mode::None] {
+            cr1.set_mode(mode);
+            assert_eq!(cr1.get_mode(), mode);
+        }
+    }
+
+    #[test]
+    fn test_cr1_get_mode_ignores_other_bits() {
+        let mut cr1 = CR1(!0);
+        cr1.set_mode(Mode::None);
+        assert_eq!(cr1.get_mode(), Mode::None);
+
+        cr1.set_receiver_enabled(true);
+        cr1.set_transmitter_enabled(true);
+        assert_eq!(cr1.get_mode(), Mode::All);
+    }
+
+    #[test]
+    fn test_cr1_set_receiver_enabled() {
+        let mut cr1 = CR1(0);
+
+        cr1.set_receiver_enabled(true);
+        assert_eq!(cr1.0, 0b1 << 2);
+
+        cr1.set_mode(Mode::All);
+        cr1.set_receiver_enabled(false);
+        assert_eq!(cr1.0, 0b1 << 3);
+    }
+
+    #[test]
+    fn test_cr1_set_transmitter_enabled() {
+        let mut cr1 = CR1(0);
+
+        cr1.set_transmitter_enabled(true);
+        assert_eq!(cr1.0, 0b1 << 3);
+
+        cr1.set_mode(Mode::All);
+        cr1.set_transmitter_enabled(false);
+        assert_eq!(cr1.0, 0b1 << 2);
+    }
+
     #[test]
     fn test_cr1_set_parity() {
         //let mut cr1 = test::create_register::<CR1>();
@@ -418,6 +958,32 @@ mod tests {
         assert_eq!(cr1.0, 0b11 << 9);
     }
 
+    #[test]
+    fn test_cr1_get_parity() {
+        let mut cr1 = CR1(0);
+        assert_eq!(cr1.get_parity(), Parity::None);
+
+        cr1.set_parity(Parity::Even);
+        assert_eq!(cr1.get_parity(), Parity::Even);
+
+        cr1.set_parity(Parity::Odd);
+        assert_eq!(cr1.get_parity(), Parity::Odd);
+
+        // PS alone doesn't enable parity
+        assert_eq!(CR1(0b1 << 9).get_parity(), Parity::None);
+    }
+
+    #[test]
+    fn test_cr1_set_idle_interrupt() {
+        let mut cr1 = CR1(0);
+
+        cr1.set_idle_interrupt(true);
+        assert_eq!(cr1.0, 0b1 << 4);
+
+        cr1.set_idle_interrupt(false);
+        assert_eq!(cr1.0, 0);
+    }
+
     #[test]
     fn test_cr1_set_over8() {
         let mut cr1 = CR1(0);
@@ -484,6 +1050,72 @@ mod tests {
         assert_eq!(cr1.0, 0);
     }
 
+    #[test]
+    fn test_cr1_enable_mute_mode_idle_line() {
+        let mut cr1 = CR1(0);
+        cr1.enable_mute_mode(WakeMethod::IdleLine);
+        assert_eq!(cr1.0, 0b1 << 13);
+    }
+
+    #[test]
+    fn test_cr1_enable_mute_mode_address_mark() {
+        let mut cr1 = CR1(0);
+        cr1.enable_mute_mode(WakeMethod::AddressMark);
+        assert_eq!(cr1.0, 0b1 << 13 | 0b1 << 11);
+
+        cr1.enable_mute_mode(WakeMethod::IdleLine);
+        assert_eq!(cr1.0, 0b1 << 13);
+    }
+
+    #[test]
+    fn test_cr1_disable_mute_mode() {
+        let mut cr1 = CR1(0b1 << 13 | 0b1 << 11);
+        cr1.disable_mute_mode();
+        assert_eq!(cr1.0, 0b1 << 11);
+    }
+
+    #[test]
+    fn test_cr2_set_address_four_bit() {
+        let mut cr2 = CR2(0);
+        cr2.set_address(0xA, AddressLength::Four);
+        assert_eq!(cr2.0, 0xA << 24);
+    }
+
+    #[test]
+    fn test_cr2_set_address_seven_bit() {
+        let mut cr2 = CR2(0);
+        cr2.set_address(0x5A, AddressLength::Seven);
+        assert_eq!(cr2.0, 0x5A << 24 | 0b1 << 4);
+
+        cr2.set_address(0x3, AddressLength::Four);
+        assert_eq!(cr2.0, 0x3 << 24);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_cr2_set_address_four_bit_greater_than_15_panics() {
+        let mut cr2 = CR2(0);
+        cr2.set_address(0x10, AddressLength::Four);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_cr2_set_address_seven_bit_greater_than_127_panics() {
+        let mut cr2 = CR2(0);
+        cr2.set_address(0x80, AddressLength::Seven);
+    }
+
+    #[test]
+    fn test_cr2_set_rx_inversion() {
+        let mut cr2 = CR2(CR2_ADDM7);
+
+        cr2.set_rx_inversion(true);
+        assert_eq!(cr2.0, CR2_ADDM7 | 0b1 << 16);
+
+        cr2.set_rx_inversion(false);
+        assert_eq!(cr2.0, CR2_ADDM7);
+    }
+
     #[test]
     fn test_cr2_set_stop_bits() {
         let mut cr2 = CR2(0);
@@ -502,6 +1134,122 @@ mod tests {
         assert_eq!(cr2.0, 0b0);
     }
 
+    #[test]
+    fn test_cr2_get_stop_bits() {
+        let mut cr2 = CR2(0);
+
+        for &length in &[StopLength::Half, StopLength::OneAndHalf, StopLength::Two, StopLength::One] {
+            cr2.set_stop_bits(length);
+            assert_eq!(cr2.get_stop_bits(), length);
+        }
+    }
+
+    #[test]
+    fn test_cr1_configure_frame_without_parity() {
+        let mut cr1 = CR1(0);
+
+        cr1.configure_frame(7, Parity::None);
+        assert_eq!(cr1.0, 0b1 << 28);
+
+        cr1.configure_frame(8, Parity::None);
+        assert_eq!(cr1.0, 0b0);
+    }
+
+    #[test]
+    fn test_cr1_configure_frame_with_parity_adds_a_bit() {
+        let mut cr1 = CR1(0);
+
+        // 7 data bits + parity is an 8 bit word
+        cr1.configure_frame(7, Parity::Even);
+        assert_eq!(cr1.0, 0b1 << 10);
+
+        // 8 data bits + parity is a 9 bit word
+        cr1.configure_frame(8, Parity::Odd);
+        assert_eq!(cr1.0, 0b1 << 12 | 0b11 << 9);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_cr1_configure_frame_9_data_bits_with_parity_panics() {
+        let mut cr1 = CR1(0);
+
+        cr1.configure_frame(9, Parity::Even);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_cr1_configure_frame_6_data_bits_without_parity_panics() {
+        let mut cr1 = CR1(0);
+
+        cr1.configure_frame(6, Parity::None);
+    }
+
+    #[test]
+    #[should_panic(expected = "don't fit")]
+    fn test_cr1_configure_frame_255_data_bits_with_parity_panics() {
+        let mut cr1 = CR1(0);
+
+        // Rejected as a frame that doesn't fit rather than overflowing the bit count
+        cr1.configure_frame(255, Parity::Odd);
+    }
+
+    #[test]
+    fn test_cr1_effective_frame_bits_without_parity() {
+        let mut cr1 = CR1(0);
+
+        cr1.configure_frame(7, Parity::None);
+        assert_eq!(cr1.effective_frame_bits(StopLength::One), 9);
+
+        cr1.configure_frame(8, Parity::None);
+        assert_eq!(cr1.effective_frame_bits(StopLength::One), 10);
+
+        cr1.configure_frame(9, Parity::None);
+        assert_eq!(cr1.effective_frame_bits(StopLength::One), 11);
+    }
+
+    #[test]
+    fn test_cr1_effective_frame_bits_with_parity() {
+        let mut cr1 = CR1(0);
+
+        // The parity bit takes the MSB of the word
+        cr1.configure_frame(6, Parity::Even);
+        assert_eq!(cr1.effective_frame_bits(StopLength::One), 9);
+
+        cr1.configure_frame(7, Parity::Odd);
+        assert_eq!(cr1.effective_frame_bits(StopLength::One), 10);
+
+        cr1.configure_frame(8, Parity::Even);
+        assert_eq!(cr1.effective_frame_bits(StopLength::One), 11);
+    }
+
+    #[test]
+    fn test_cr1_effective_frame_bits_with_2_stop_bits() {
+        let mut cr1 = CR1(0);
+
+        cr1.configure_frame(8, Parity::None);
+        assert_eq!(cr1.effective_frame_bits(StopLength::Two), 11);
+
+        cr1.configure_frame(8, Parity::Even);
+        assert_eq!(cr1.effective_frame_bits(StopLength::Two), 12);
+    }
+
+    #[test]
+    fn test_cr1_effective_frame_bits_rounds_up_half_stop_bits() {
+        let mut cr1 = CR1(0);
+
+        cr1.configure_frame(8, Parity::None);
+        assert_eq!(cr1.effective_frame_bits(StopLength::Half), 10);
+        assert_eq!(cr1.effective_frame_bits(StopLength::OneAndHalf), 11);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_cr1_effective_frame_bits_reserved_word_length_panics() {
+        let cr1 = CR1(0b1 << 28 | 0b1 << 12);
+
+        cr1.effective_frame_bits(StopLength::One);
+    }
+
     #[test]
     fn test_cr3_set_dma_mode() {
         let mut cr3 = CR3(0);
@@ -519,6 +1267,118 @@ mod tests {
         assert_eq!(cr3.0, 0)
     }
 
+    #[test]
+    fn test_cr3_disable_dma_mode() {
+        let mut cr3 = CR3(0);
+        cr3.set_dma_mode(DMAMode::All);
+
+        cr3.disable_dma_mode(DMAMode::Receive);
+        assert_eq!(cr3.0, 0b1 << 7);
+
+        cr3.disable_dma_mode(DMAMode::None);
+        assert_eq!(cr3.0, 0b1 << 7);
+
+        cr3.disable_dma_mode(DMAMode::All);
+        assert_eq!(cr3.0, 0);
+    }
+
+    #[test]
+    fn test_cr3_set_one_bit_sampling() {
+        let mut cr3 = CR3(0b1 << 6);
+
+        cr3.set_one_bit_sampling(true);
+        assert_eq!(cr3.0, 0b1 << 11 | 0b1 << 6);
+
+        cr3.set_one_bit_sampling(false);
+        assert_eq!(cr3.0, 0b1 << 6);
+    }
+
+    #[test]
+    fn test_cr3_set_error_interrupt() {
+        let mut cr3 = CR3(0b1 << 6);
+
+        cr3.set_error_interrupt(true);
+        assert_eq!(cr3.0, 0b1 << 6 | 0b1);
+
+        cr3.set_error_interrupt(false);
+        assert_eq!(cr3.0, 0b1 << 6);
+    }
+
+    #[test]
+    fn test_cr1_set_stop_mode_enable() {
+        let mut cr1 = CR1(0b1);
+
+        cr1.set_stop_mode_enable(true);
+        assert_eq!(cr1.0, 0b11);
+
+        cr1.set_stop_mode_enable(false);
+        assert_eq!(cr1.0, 0b1);
+    }
+
+    #[test]
+    fn test_cr3_set_wakeup_source() {
+        let mut cr3 = CR3(0b1 << 22);
+
+        cr3.set_wakeup_source(WakeupSource::StartBit);
+        assert_eq!(cr3.0, 0b1 << 22 | 0b10 << 20);
+
+        cr3.set_wakeup_source(WakeupSource::ReceiverNotEmpty);
+        assert_eq!(cr3.0, 0b1 << 22 | 0b11 << 20);
+
+        cr3.set_wakeup_source(WakeupSource::AddressMatch);
+        assert_eq!(cr3.0, 0b1 << 22);
+    }
+
+    #[test]
+    fn test_cr3_set_wakeup_interrupt() {
+        let mut cr3 = CR3(0);
+
+        cr3.set_wakeup_interrupt(true);
+        assert_eq!(cr3.0, 0b1 << 22);
+
+        cr3.set_wakeup_interrupt(false);
+        assert_eq!(cr3.0, 0);
+    }
+
+    #[test]
+    fn test_cr3_set_cts_interrupt() {
+        let mut cr3 = CR3(0b1 << 9);
+
+        cr3.set_cts_interrupt(true);
+        assert_eq!(cr3.0, 0b1 << 9 | 0b1 << 10);
+
+        cr3.set_cts_interrupt(false);
+        assert_eq!(cr3.0, 0b1 << 9);
+    }
+
+    #[test]
+    fn test_cr3_supports_stop_bits_normal_mode() {
+        let cr3 = CR3(0);
+
+        for &length in &[StopLength::Half, StopLength::OneAndHalf, StopLength::Two, StopLength::One] {
+            assert!(cr3.supports_stop_bits(length));
+        }
+    }
+
+    #[test]
+    fn test_cr3_supports_stop_bits_smartcard() {
+        let cr3 = CR3(0b1 << 5);
+
+        assert!(cr3.is_smartcard_enabled());
+        assert!(!cr3.supports_stop_bits(StopLength::Half));
+        assert!(cr3.supports_stop_bits(StopLength::OneAndHalf));
+    }
+
+    #[test]
+    fn test_cr3_supports_stop_bits_irda() {
+        let cr3 = CR3(0b1 << 1);
+
+        assert!(cr3.is_irda_enabled());
+        assert!(!cr3.supports_stop_bits(StopLength::Half));
+        assert!(!cr3.supports_stop_bits(StopLength::OneAndHalf));
+        assert!(cr3.supports_stop_bits(StopLength::One));
+    }
+
     #[test]
     fn test_cr3_set_hardware_flow_control() {
         let mut cr3 = CR3(0);
@@ -536,4 +1396,53 @@ mod tests {
         cr3.set_hardware_flow_control(HardwareFlowControl::None);
         assert_eq!(cr3.0, 0b0);
     }
+
+    #[test]
+    #[cfg(feature="fifo")]
+    fn test_cr1_enable_fifo() {
+        let mut cr1 = CR1(0);
+
+        cr1.enable_fifo(true);
+        assert_eq!(cr1.0, 0b1 << 29);
+
+        cr1.enable_fifo(false);
+        assert_eq!(cr1.0, 0b0);
+    }
+
+    #[test]
+    #[cfg(feature="fifo")]
+    fn test_cr3_set_rx_fifo_threshold() {
+        let mut cr3 = CR3(0);
+
+        cr3.set_rx_fifo_threshold(FifoThreshold::Half);
+        assert_eq!(cr3.0, 0b010 << 25);
+
+        cr3.set_rx_fifo_threshold(FifoThreshold::Full);
+        assert_eq!(cr3.0, 0b101 << 25);
+
+        cr3.set_rx_fifo_threshold(FifoThreshold::Eighth);
+        assert_eq!(cr3.0, 0b0);
+    }
+
+    #[test]
+    #[cfg(feature="fifo")]
+    fn test_cr3_set_tx_fifo_threshold() {
+        let mut cr3 = CR3(0);
+
+        cr3.set_tx_fifo_threshold(FifoThreshold::SevenEighths);
+        assert_eq!(cr3.0, 0b100 << 29);
+
+        cr3.set_tx_fifo_threshold(FifoThreshold::Quarter);
+        assert_eq!(cr3.0, 0b001 << 29);
+    }
+
+    #[test]
+    #[cfg(feature="fifo")]
+    fn test_cr3_fifo_thresholds_dont_overlap() {
+        let mut cr3 = CR3(0);
+
+        cr3.set_rx_fifo_threshold(FifoThreshold::ThreeQuarters);
+        cr3.set_tx_fifo_threshold(FifoThreshold::Full);
+        assert_eq!(cr3.0, 0b011 << 25 | 0b101 << 29);
+    }
 }
@@ -0,0 +1,126 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+/* This submodule contains the function implementations for the Usartx_RDR
+ * and Usartx_TDR. These are the data registers that are responsible for
+ * receiving and transmitting data through the serial bus.
+ */
+
+use super::defs::*;
+
+#[derive(Copy, Clone, Debug)]
+pub struct RDR(u32);
+
+impl RDR {
+    /* Bits 31:9 Reserved, must be kept at reset value.
+     * Bits 8:0 RDR[8:0]: Receive data value
+     *   Contains the received data character.
+     * The RDR register provides the parallel interface between the input
+     * shift register and the internal bus.
+     *
+     * When receiving with the parity enabled, the value read in the MSB bit
+     * is the received parity bit.
+     */
+    pub fn load(&self) -> u8 {
+        self.0 as u8
+    }
+
+    /* Bits 8:0 RDR[8:0]: Receive data value
+     *   The full received character, masked with DATA_MASK (0x1FF) so the
+     *   ninth bit is kept when using 9 bit words and the reserved bits
+     *   31:9 are dropped.
+     */
+    pub fn read(&self) -> u16 {
+        (self.0 & DATA_MASK) as u16
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct TDR(u32);
+
+impl TDR {
+    /* Bits 31:9 Reserved, must be kept at reset value.
+     * Bits 8:0 TDR[8:0]: Transmit data value
+     *   Contains the data character to be transmitted.
+     * The TDR register provides the parallel interface between the internal
+     * bus and the output shift register.
+     * When transmitting with the parity enabled (PCE bit set to 1 in the
+     * USARTx_CR1 register), the value written in the MSB (bit 7 or bit 8
+     * depending on the data length) has no effect because it is replaced by
+     * the parity.
+     */
+    pub fn store(&mut self, byte: u8) {
+        self.0 = byte as u32;
+    }
+
+    /* Bits 8:0 TDR[8:0]: Transmit data value
+     *   The full character to transmit, masked with DATA_MASK (0x1FF) so the
+     *   ninth bit is sent when using 9 bit words and nothing is written to
+     *   the reserved bits 31:9.
+     */
+    pub fn write(&mut self, data: u16) {
+        self.0 = data as u32 & DATA_MASK;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tdr_has_ascii_value_of_97_on_store_of_char_a() {
+        let mut tdr = TDR(0);
+        tdr.store(b'a');
+        assert_eq!(tdr.0, 97);
+    }
+
+    #[test]
+    fn test_tdr_has_ascii_value_of_98_on_last_store_of_char_b() {
+        let mut tdr = TDR(0);
+        tdr.store(b'i');
+        tdr.store(b'z');
+        tdr.store(b'b');
+        assert_eq!(tdr.0, 98);
+    }
+
+    #[test]
+    fn test_tdr_write_keeps_ninth_bit() {
+        let mut tdr = TDR(0);
+        tdr.write(0x1A5);
+        assert_eq!(tdr.0, 0x1A5);
+    }
+
+    #[test]
+    fn test_tdr_write_masks_to_9_bits() {
+        let mut tdr = TDR(0);
+        tdr.write(0xFFFF);
+        assert_eq!(tdr.0, 0x1FF);
+    }
+
+    #[test]
+    fn test_rdr_read_keeps_ninth_bit() {
+        let rdr = RDR(0x1A5);
+        assert_eq!(rdr.read(), 0x1A5);
+        assert_eq!(rdr.load(), 0xA5);
+    }
+
+    #[test]
+    fn test_rdr_read_masks_reserved_bits() {
+        let rdr = RDR(0xFFFF_FE01);
+        assert_eq!(rdr.read(), 0x001);
+    }
+}
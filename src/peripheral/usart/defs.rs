@@ -24,9 +24,11 @@
 
 #![allow(missing_docs)]
 
+use peripheral::addresses;
+
 // Base addresses for USART 1 and 2
-pub const USART1_ADDR: *const u32 = 0x4001_3800 as *const _;
-pub const USART2_ADDR: *const u32 = 0x4000_4400 as *const _;
+pub const USART1_ADDR: *const u32 = addresses::USART1_ADDR;
+pub const USART2_ADDR: *const u32 = addresses::USART2_ADDR;
 
 // ------------------------------------
 // USARTx - CR1 Bit definitions
@@ -53,7 +55,9 @@ pub const CR1_OVER8:  u32 = 0b1 << 15;
 pub const CR1_RTOIE:  u32 = 0b1 << 26;
 pub const CR1_EOBIE:  u32 = 0b1 << 27;
 pub const CR1_M1:     u32 = 0b1 << 28;
-// Bits 29 - 31 are reserved and must be kept at reset value.
+pub const CR1_FIFOEN: u32 = 0b1 << 29;
+// Bits 30 - 31 are reserved and must be kept at reset value. Bit 29 is reserved as well on parts
+// without a USART FIFO.
 
 // ------------------------------------
 // USARTx - CR2 bit definitions
@@ -82,14 +86,36 @@ pub const CR2_ABRMOD1:   u32 = 0b1 << 22;
 pub const CR2_RTOEN:     u32 = 0b1 << 23;
 pub const CR2_ADD:       u32 = 0b1111 << 24; // This might need to change
 pub const CR2_ADD1:      u32 = 0b1111 << 28; // This might need to change
+pub const CR2_ADD_SHIFT: u32 = 24;
+pub const CR2_ADD_MASK:  u32 = 0xFF << CR2_ADD_SHIFT;
 
 // ------------------------------------
 // USARTx - CR3 bit definitions
 pub const CR3_OFFSET: u32 = 0x08;
+pub const CR3_EIE:    u32 = 0b1 << 0;
+pub const CR3_IREN:   u32 = 0b1 << 1;
+pub const CR3_SCEN:   u32 = 0b1 << 5;
 pub const CR3_DMAR:   u32 = 0b1 << 6;
 pub const CR3_DMAT:   u32 = 0b1 << 7;
 pub const CR3_RTSE:   u32 = 0b1 << 8;
 pub const CR3_CTSE:   u32 = 0b1 << 9;
+pub const CR3_CTSIE:  u32 = 0b1 << 10;
+pub const CR3_ONEBIT: u32 = 0b1 << 11;
+pub const CR3_WUS_SHIFT: u32 = 20;
+pub const CR3_WUS_MASK: u32 = 0b11 << CR3_WUS_SHIFT;
+pub const CR3_WUFIE:  u32 = 0b1 << 22;
+pub const CR3_TXFTIE: u32 = 0b1 << 23;
+pub const CR3_RXFTCFG_SHIFT: u32 = 25;
+pub const CR3_RXFTCFG_MASK: u32 = 0b111 << CR3_RXFTCFG_SHIFT;
+pub const CR3_RXFTIE: u32 = 0b1 << 28;
+pub const CR3_TXFTCFG_SHIFT: u32 = 29;
+pub const CR3_TXFTCFG_MASK: u32 = 0b111 << CR3_TXFTCFG_SHIFT;
+pub const FIFO_THRESHOLD_1_8: u32 = 0b000;
+pub const FIFO_THRESHOLD_1_4: u32 = 0b001;
+pub const FIFO_THRESHOLD_1_2: u32 = 0b010;
+pub const FIFO_THRESHOLD_3_4: u32 = 0b011;
+pub const FIFO_THRESHOLD_7_8: u32 = 0b100;
+pub const FIFO_THRESHOLD_FULL: u32 = 0b101;
 
 // ------------------------------------
 // USARTx - BRR bit definitions
@@ -101,6 +127,18 @@ pub const DIV_MASK: u32   = 0b1111;
 // USARTx - GTPR bit definitions
 // ------------------------------------
 pub const GTPR_OFFSET: u32 = 0x10;
+pub const GTPR_GT_SHIFT: u32 = 8;
+pub const GTPR_GT_MASK: u32 = 0xFF << GTPR_GT_SHIFT;
+
+// ------------------------------------
+// USARTx - RQR bit definitions
+// ------------------------------------
+pub const RQR_OFFSET: u32 = 0x18;
+pub const RQR_ABRRQ: u32  = 0b1;
+pub const RQR_SBKRQ: u32  = 0b1 << 1;
+pub const RQR_MMRQ: u32   = 0b1 << 2;
+pub const RQR_RXFRQ: u32  = 0b1 << 3;
+pub const RQR_TXFRQ: u32  = 0b1 << 4;
 
 // ------------------------------------
 // USARTx - ISR bit definitions
@@ -114,6 +152,14 @@ pub const ISR_IDLE: u32   = 0b1 << 4;
 pub const ISR_RXNE: u32   = 0b1 << 5;
 pub const ISR_TC: u32     = 0b1 << 6;
 pub const ISR_TXE: u32    = 0b1 << 7;
+pub const ISR_CTSIF: u32  = 0b1 << 9;
+pub const ISR_CTS: u32    = 0b1 << 10;
+pub const ISR_RWU: u32    = 0b1 << 19;
+
+// Wakeup from Stop mode sources, CR3 WUS[1:0]
+pub const WUS_ADDRESS_MATCH: u32 = 0b00;
+pub const WUS_START_BIT: u32 = 0b10;
+pub const WUS_RXNE: u32 = 0b11;
 
 // ------------------------------------
 // USARTx - ICR bit definitions
@@ -142,6 +188,8 @@ pub const ICR_WUCF: u32 = 0b1 << 20;
 // USARTx - RDR bit definitions
 // ------------------------------------
 pub const RDR_OFFSET: u32 = 0x24;
+// Shared by the RDR and TDR, bits 31:9 are reserved.
+pub const DATA_MASK: u32 = 0x1FF;
 
 // ------------------------------------
 // USARTx - TDR bit definitions
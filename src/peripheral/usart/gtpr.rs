@@ -0,0 +1,65 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+/* This submodule contains the function implementations for the Usartx_GTPR.
+ * The GTPR is the guard time and prescaler register, it holds the
+ * smartcard guard time and the IrDA/smartcard prescaler.
+ */
+
+use super::defs::*;
+
+#[derive(Copy, Clone, Debug)]
+pub struct GTPR(u32);
+
+impl GTPR {
+    /* Bits 15:8 GT[7:0]: Guard time value
+     *   This bit-field is used to program the Guard time value in terms of
+     *   number of baud clock periods. The Transmission Complete flag is set
+     *   after this guard time value.
+     *   This bit field can only be written when the USART is disabled (UE=0).
+     */
+    pub fn set_guard_time(&mut self, gt: u8) {
+        self.0 &= !(GTPR_GT_MASK);
+        self.0 |= (gt as u32) << GTPR_GT_SHIFT;
+    }
+
+    pub fn get_guard_time(&self) -> u8 {
+        ((self.0 & GTPR_GT_MASK) >> GTPR_GT_SHIFT) as u8
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gtpr_set_guard_time() {
+        let mut gtpr = GTPR(0);
+        gtpr.set_guard_time(0xA5);
+
+        assert_eq!(gtpr.0, 0xA5 << 8);
+        assert_eq!(gtpr.get_guard_time(), 0xA5);
+    }
+
+    #[test]
+    fn test_gtpr_set_guard_time_keeps_prescaler() {
+        let mut gtpr = GTPR(0xFF12);
+        gtpr.set_guard_time(0x03);
+
+        assert_eq!(gtpr.0, 0x0312);
+    }
+}
@@ -0,0 +1,192 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! `embedded-hal` serial trait implementations for the Usart.
+//!
+//! Both traits are non-blocking, if the Usart is not ready the call returns
+//! `nb::Error::WouldBlock` and the caller is expected to try again later.
+
+use hal::serial::{Read, Write};
+use nb;
+use super::{stats, RawUsart, Usart};
+
+/// Errors that can be reported while receiving data on the Usart.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SerialError {
+    /// A byte was received while the RDR was still full, so data was lost.
+    Overrun,
+    /// A de-synchronization, excessive noise or break character was detected.
+    Framing,
+    /// The parity bit of the received frame did not match.
+    Parity,
+    /// Noise was detected on the received frame.
+    Noise,
+}
+
+impl Read<u8> for RawUsart {
+    type Error = SerialError;
+
+    fn read(&mut self) -> nb::Result<u8, SerialError> {
+        // Report (and clear) any error before handing out data, otherwise a
+        // caller would never learn that the received byte may be corrupt.
+        match take_error(self) {
+            Some(error) => {
+                stats::record_error(error);
+                Err(nb::Error::Other(error))
+            },
+            None if self.is_rx_reg_full() => Ok(self.load_byte()),
+            None => Err(nb::Error::WouldBlock),
+        }
+    }
+}
+
+// Clear and return the first receive error flagged, if there is one.
+fn take_error(usart: &mut RawUsart) -> Option<SerialError> {
+    if usart.is_overrun() {
+        usart.clear_ore_flag();
+        Some(SerialError::Overrun)
+    }
+    else if usart.is_framing_error() {
+        usart.clear_fe_flag();
+        Some(SerialError::Framing)
+    }
+    else if usart.is_parity_error() {
+        usart.clear_pe_flag();
+        Some(SerialError::Parity)
+    }
+    else if usart.is_noise_detected() {
+        usart.clear_nf_flag();
+        Some(SerialError::Noise)
+    }
+    else {
+        None
+    }
+}
+
+impl Write<u8> for RawUsart {
+    type Error = SerialError;
+
+    fn write(&mut self, byte: u8) -> nb::Result<(), SerialError> {
+        if self.is_tx_reg_empty() {
+            self.transmit_byte(byte);
+            Ok(())
+        }
+        else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+
+    fn flush(&mut self) -> nb::Result<(), SerialError> {
+        if self.is_transmission_complete() {
+            Ok(())
+        }
+        else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+}
+
+impl Read<u8> for Usart {
+    type Error = SerialError;
+
+    fn read(&mut self) -> nb::Result<u8, SerialError> {
+        Read::read(&mut **self)
+    }
+}
+
+impl Write<u8> for Usart {
+    type Error = SerialError;
+
+    fn write(&mut self, byte: u8) -> nb::Result<(), SerialError> {
+        Write::write(&mut **self, byte)
+    }
+
+    fn flush(&mut self) -> nb::Result<(), SerialError> {
+        Write::flush(&mut **self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use peripheral::raw_word;
+    use super::super::mock_usart;
+    use super::super::defs::*;
+
+    #[test]
+    fn test_read_would_block_when_rx_reg_empty() {
+        let mut usart = mock_usart(0, 0, 0);
+        assert_eq!(usart.read(), Err(nb::Error::WouldBlock));
+    }
+
+    #[test]
+    fn test_read_returns_byte_when_rx_reg_full() {
+        let mut usart = mock_usart(0, ISR_RXNE, 0x5A);
+        assert_eq!(usart.read(), Ok(0x5A));
+    }
+
+    #[test]
+    fn test_read_reports_and_clears_overrun() {
+        let mut usart = mock_usart(0, ISR_ORE | ISR_RXNE, 0x5A);
+        assert_eq!(usart.read(), Err(nb::Error::Other(SerialError::Overrun)));
+        assert_eq!(raw_word(&usart.icr), ICR_ORECF);
+    }
+
+    #[test]
+    fn test_read_reports_and_clears_framing_error() {
+        let mut usart = mock_usart(0, ISR_FE, 0);
+        assert_eq!(usart.read(), Err(nb::Error::Other(SerialError::Framing)));
+        assert_eq!(raw_word(&usart.icr), ICR_FECF);
+    }
+
+    #[test]
+    fn test_read_reports_and_clears_parity_error() {
+        let mut usart = mock_usart(0, ISR_PE, 0);
+        assert_eq!(usart.read(), Err(nb::Error::Other(SerialError::Parity)));
+        assert_eq!(raw_word(&usart.icr), ICR_PECF);
+    }
+
+    #[test]
+    fn test_read_reports_and_clears_noise() {
+        let mut usart = mock_usart(0, ISR_NF, 0);
+        assert_eq!(usart.read(), Err(nb::Error::Other(SerialError::Noise)));
+        assert_eq!(raw_word(&usart.icr), ICR_NCF);
+    }
+
+    #[test]
+    fn test_write_would_block_when_tx_reg_not_empty() {
+        let mut usart = mock_usart(0, 0, 0);
+        assert_eq!(usart.write(b'a'), Err(nb::Error::WouldBlock));
+        assert_eq!(raw_word(&usart.tdr), 0);
+    }
+
+    #[test]
+    fn test_write_stores_byte_when_tx_reg_empty() {
+        let mut usart = mock_usart(0, ISR_TXE, 0);
+        assert_eq!(usart.write(b'a'), Ok(()));
+        assert_eq!(raw_word(&usart.tdr), b'a' as u32);
+    }
+
+    #[test]
+    fn test_flush_waits_on_transmission_complete() {
+        let mut usart = mock_usart(0, ISR_TXE, 0);
+        assert_eq!(usart.flush(), Err(nb::Error::WouldBlock));
+
+        let mut usart = mock_usart(0, ISR_TXE | ISR_TC, 0);
+        assert_eq!(usart.flush(), Ok(()));
+    }
+}
@@ -33,25 +33,47 @@ pub mod defs;
 
 mod control;
 mod baudr;
-mod tdr;
-mod rdr;
+mod config;
+mod data;
+mod gtpr;
 mod isr;
 mod icr;
+mod rqr;
+mod tx_queue;
+mod stats;
+#[cfg(feature="embedded-hal")]
+mod hal;
 
 use core::ops::{Deref, DerefMut};
 use volatile::Volatile;
 use self::control::{CR1, CR2, CR3};
 use self::baudr::BRR;
-use self::tdr::TDR;
-use self::rdr::RDR;
+use self::data::{RDR, TDR};
+use self::gtpr::GTPR;
 use self::isr::ISR;
 use self::icr::ICR;
+use self::rqr::RQR;
 use self::defs::*;
-use peripheral::{rcc, gpio};
-use interrupt;
+use peripheral::{rcc, gpio, wait_until};
+use interrupt::{self, Interrupt};
+use time::Hertz;
 
 pub use self::control::{WordLength, Mode, Parity, StopLength, HardwareFlowControl, DMAMode};
+pub use self::control::{WakeMethod, AddressLength, WakeupSource};
+#[cfg(feature="fifo")]
+pub use self::control::FifoThreshold;
 pub use self::baudr::BaudRate;
+pub use self::config::UsartConfig;
+pub use self::tx_queue::{TxQueue, Full};
+#[doc(hidden)]
+pub use self::tx_queue::{queue_on_txe, send_queued};
+pub use self::stats::{UsartStats, stats, reset_stats, record_overrun, record_line_errors};
+#[cfg(feature="embedded-hal")]
+pub use self::hal::SerialError;
+
+// How many times to check for TC before giving up, comfortably more than one frame takes at the
+// slowest baud rate.
+const TRANSMISSION_COMPLETE_ATTEMPTS: u32 = 1_000_000;
 
 /// Defines the wake/sleep channel for the TX buffer when full.
 pub const USART2_TX_CHAN: usize = 43;
@@ -68,6 +90,16 @@ pub enum UsartX {
     Usart2,
 }
 
+impl UsartX {
+    /// Get the interrupt raised by the Usart.
+    pub fn interrupt(self) -> Interrupt {
+        match self {
+            UsartX::Usart1 => Interrupt::Usart1,
+            UsartX::Usart2 => Interrupt::Usart2,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 #[repr(C)]
 #[doc(hidden)]
@@ -76,9 +108,9 @@ pub struct RawUsart {
     cr2: CR2,
     cr3: CR3,
     brr: BRR,
-    gtpr: u32,
+    gtpr: GTPR,
     rtor: u32,
-    rqr: u32,
+    rqr: RQR,
     isr: ISR,
     icr: ICR,
     rdr: RDR,
@@ -138,6 +170,23 @@ impl RawUsart {
         self.cr1.set_mode(mode);
     }
 
+    /// Get the current Usart mode, e.g. to restore it after switching direction on a half-duplex
+    /// link.
+    pub fn get_mode(&self) -> Mode {
+        self.cr1.get_mode()
+    }
+
+    /// Enable or disable only the receiver, leaving the transmitter alone. Useful for
+    /// half-duplex links (e.g. RS-485) where the receiver is turned off while transmitting.
+    pub fn set_receiver_enabled(&mut self, enable: bool) {
+        self.cr1.set_receiver_enabled(enable);
+    }
+
+    /// Enable or disable only the transmitter, leaving the receiver alone.
+    pub fn set_transmitter_enabled(&mut self, enable: bool) {
+        self.cr1.set_transmitter_enabled(enable);
+    }
+
     /// Enable the RXNE interrupt. This interrupt occurs when the
     /// receive data register has data in it.
     pub fn enable_receiver_not_empty_interrupt(&mut self) {
@@ -150,6 +199,69 @@ impl RawUsart {
         self.cr1.set_receiver_not_empty_interrupt(false);
     }
 
+    /// Enable the IDLE interrupt. This interrupt occurs when the line goes idle
+    /// after receiving data.
+    pub fn enable_idle_interrupt(&mut self) {
+        self.cr1.set_idle_interrupt(true);
+    }
+
+    /// Disable the IDLE interrupt. This interrupt occurs when the line goes idle
+    /// after receiving data.
+    pub fn disable_idle_interrupt(&mut self) {
+        self.cr1.set_idle_interrupt(false);
+    }
+
+    /// Enable the error interrupt. While DMA reception is enabled, framing, overrun and noise
+    /// errors only raise an interrupt when this is enabled.
+    pub fn enable_error_interrupt(&mut self) {
+        self.cr3.set_error_interrupt(true);
+    }
+
+    /// Disable the error interrupt.
+    pub fn disable_error_interrupt(&mut self) {
+        self.cr3.set_error_interrupt(false);
+    }
+
+    /// Enable the CTS interrupt. This interrupt occurs when the nCTS input changes while CTS
+    /// flow control is enabled.
+    pub fn enable_cts_interrupt(&mut self) {
+        self.cr3.set_cts_interrupt(true);
+    }
+
+    /// Disable the CTS interrupt.
+    pub fn disable_cts_interrupt(&mut self) {
+        self.cr3.set_cts_interrupt(false);
+    }
+
+    /// Let the Usart wake the MCU from Stop mode when `source` happens on the receiver, so the
+    /// MCU can sleep while the line is idle.
+    ///
+    /// The wakeup interrupt is enabled along with it, the Usart's EXTI line still has to be
+    /// unmasked for the wakeup to reach the core. The Usart only keeps running in Stop mode if its
+    /// kernel clock is the HSI or the LSE, selected in the RCC, it can't wake the MCU while clocked
+    /// from PCLK or SYSCLK.
+    ///
+    /// The wakeup source can only be changed while the Usart is disabled, so if it is enabled it
+    /// gets disabled, configured, and then enabled again.
+    pub fn enable_stop_mode_wakeup(&mut self, source: WakeupSource) {
+        let enabled = self.is_usart_enabled();
+        if enabled {
+            self.disable_usart();
+        }
+        self.cr3.set_wakeup_source(source);
+        self.cr3.set_wakeup_interrupt(true);
+        self.cr1.set_stop_mode_enable(true);
+        if enabled {
+            self.enable_usart();
+        }
+    }
+
+    /// Stop the Usart from waking the MCU from Stop mode.
+    pub fn disable_stop_mode_wakeup(&mut self) {
+        self.cr1.set_stop_mode_enable(false);
+        self.cr3.set_wakeup_interrupt(false);
+    }
+
     /// Enable the TC interrupt. This interrupt occurs when complete
     /// transmission of the data is finished.
     pub fn enable_transmit_complete_interrupt(&mut self) {
@@ -185,6 +297,17 @@ impl RawUsart {
         self.cr1.set_word_length(length);
     }
 
+    /// Set the word length and parity from the number of data bits in each frame. When parity is
+    /// enabled the parity bit is added on top of the data bits.
+    ///
+    /// # Panics
+    ///
+    /// Without parity there must be 7, 8 or 9 data bits, with parity there must be 6, 7 or 8 data
+    /// bits, otherwise the kernel will panic.
+    pub fn configure_frame(&mut self, data_bits: u8, parity: Parity) {
+        self.cr1.configure_frame(data_bits, parity);
+    }
+
     /// Enable oversampling by 8.
     pub fn enable_over8(&mut self) {
         self.cr1.set_over8(true);
@@ -196,15 +319,148 @@ impl RawUsart {
     }
 
     /// Set the number of stop bits.
+    ///
+    /// Smartcard mode needs 1.5 stop bits and IrDA mode needs 1 stop bit, in debug builds a
+    /// length that doesn't work in the mode currently enabled will cause the kernel to panic.
     pub fn set_stop_bits(&mut self, length: StopLength) {
+        debug_assert!(self.cr3.supports_stop_bits(length),
+                      "RawUsart::set_stop_bits - stop length not supported in this mode!");
         self.cr2.set_stop_bits(length);
     }
 
+    /// Get the number of bits each frame takes on the wire, start and stop bits included. Half
+    /// stop bits are rounded up to a whole bit.
+    ///
+    /// # Panics
+    ///
+    /// The reserved word length M[1:0] = 11 will cause the kernel to panic.
+    pub fn effective_frame_bits(&self) -> u8 {
+        self.cr1.effective_frame_bits(self.cr2.get_stop_bits())
+    }
+
+    /// Enable mute mode. While muted, the receiver ignores incoming data until
+    /// it is woken by the specified wake method.
+    ///
+    /// The wake method can only be changed while the Usart is disabled.
+    pub fn enable_mute_mode(&mut self, wake: WakeMethod) {
+        self.cr1.enable_mute_mode(wake);
+    }
+
+    /// Disable mute mode, the receiver stays active permanently.
+    pub fn disable_mute_mode(&mut self) {
+        self.cr1.disable_mute_mode();
+    }
+
+    /// Set the address of this Usart node. When using address mark wake up,
+    /// the receiver leaves mute mode when a frame with this address is received.
+    ///
+    /// The address can only be changed while the Usart is disabled.
+    ///
+    /// # Panics
+    ///
+    /// The address must fit within the specified length (4 or 7 bits) or the
+    /// kernel will panic.
+    pub fn set_address(&mut self, address: u8, length: AddressLength) {
+        self.cr2.set_address(address, length);
+    }
+
+    /// Invert the level of the RX pin, for a bus that idles low.
+    ///
+    /// The inversion can only be changed while the Usart is disabled.
+    pub fn set_rx_inversion(&mut self, invert: bool) {
+        self.cr2.set_rx_inversion(invert);
+    }
+
+    /// Set the guard time, in baud clock periods, that the TC flag waits after the last stop bit.
+    ///
+    /// The hardware only applies the guard time in smartcard mode, where it also spaces out the
+    /// characters. Outside of smartcard mode it has no effect, and on RS-485 the gap before the
+    /// transceiver turns around is set by the driver enable deassertion time instead (which is
+    /// counted from the end of the last stop bit, independently of the guard time). Code that
+    /// waits for TC before releasing the bus by hand gets the extra delay in smartcard mode only.
+    ///
+    /// The guard time can only be changed while the Usart is disabled.
+    pub fn set_character_guard_time(&mut self, gt: u8) {
+        self.gtpr.set_guard_time(gt);
+    }
+
+    /// Set the Usart up as a node on a multidrop bus with a 7-bit address.
+    ///
+    /// Mute mode is enabled with address mark wake up, so the receiver ignores frames until one
+    /// carrying this node's address arrives. The RX pin level is inverted if `invert_rx` is set.
+    /// These can only be written while the Usart is disabled, so if it is enabled it gets
+    /// disabled, configured, and then enabled again.
+    ///
+    /// # Panics
+    ///
+    /// The address must be a value between [0..127] or the kernel will panic.
+    pub fn configure_multidrop(&mut self, address: u8, invert_rx: bool) {
+        let enabled = self.is_usart_enabled();
+        if enabled {
+            self.disable_usart();
+        }
+        self.set_address(address, AddressLength::Seven);
+        self.enable_mute_mode(WakeMethod::AddressMark);
+        self.set_rx_inversion(invert_rx);
+        if enabled {
+            self.enable_usart();
+        }
+    }
+
     /// Set the Usart DMA mode for transmit and receive configurations.
     pub fn set_dma_mode(&mut self, dma_mode: DMAMode) {
         self.cr3.set_dma_mode(dma_mode);
     }
 
+    /// Turn off DMA requests for the direction(s) in `dma_mode`, leaving the other direction as
+    /// it is.
+    pub fn disable_dma_mode(&mut self, dma_mode: DMAMode) {
+        self.cr3.disable_dma_mode(dma_mode);
+    }
+
+    /// Enable FIFO mode. This can only be changed while the Usart is disabled.
+    ///
+    /// Only available on parts with a Usart FIFO.
+    #[cfg(feature="fifo")]
+    pub fn enable_fifo(&mut self) {
+        self.cr1.enable_fifo(true);
+    }
+
+    /// Disable FIFO mode. This can only be changed while the Usart is disabled.
+    ///
+    /// Only available on parts with a Usart FIFO.
+    #[cfg(feature="fifo")]
+    pub fn disable_fifo(&mut self) {
+        self.cr1.enable_fifo(false);
+    }
+
+    /// Set the receive FIFO threshold at which the RX FIFO threshold interrupt occurs.
+    ///
+    /// Only available on parts with a Usart FIFO.
+    #[cfg(feature="fifo")]
+    pub fn set_rx_fifo_threshold(&mut self, threshold: FifoThreshold) {
+        self.cr3.set_rx_fifo_threshold(threshold);
+    }
+
+    /// Set the transmit FIFO threshold at which the TX FIFO threshold interrupt occurs.
+    ///
+    /// Only available on parts with a Usart FIFO.
+    #[cfg(feature="fifo")]
+    pub fn set_tx_fifo_threshold(&mut self, threshold: FifoThreshold) {
+        self.cr3.set_tx_fifo_threshold(threshold);
+    }
+
+    /// Sample each bit once in the middle rather than taking the majority of three samples.
+    ///
+    /// Three bit sampling rejects glitches and reports them with the NF flag, one bit sampling
+    /// tolerates more clock deviation so it's more accurate on a clean, fast link. While one bit
+    /// sampling is enabled noise can't be detected, so `is_noise_detected` always returns false.
+    ///
+    /// This can only be changed while the Usart is disabled.
+    pub fn set_one_bit_sampling(&mut self, enable: bool) {
+        self.cr3.set_one_bit_sampling(enable);
+    }
+
     /// Set hardware flow control mode.
     ///
     /// # Note
@@ -217,8 +473,50 @@ impl RawUsart {
     // --------------------------------------------------------------
 
     /// Set baud rate based on clock rate function argument.
-    pub fn set_baud_rate(&mut self, baud_rate: BaudRate, clock_rate: u32) {
-        self.brr.set_baud_rate(baud_rate, clock_rate, self.cr1.get_over8());
+    pub fn set_baud_rate(&mut self, baud_rate: BaudRate, clock_rate: Hertz) {
+        self.brr.set_baud_rate(baud_rate, clock_rate.0, self.cr1.get_over8());
+    }
+
+    /// Set the baud rate in Hz, given the rate of the clock driving the Usart.
+    ///
+    /// The current OVER8 setting is taken into account. The BRR can only be written while the
+    /// Usart is disabled, so if it is enabled it gets disabled, the BRR written, and then enabled
+    /// again.
+    pub fn set_baud_rate_hz(&mut self, baud: u32, clock_rate: Hertz) {
+        let enabled = self.is_usart_enabled();
+        if enabled {
+            self.disable_usart();
+        }
+        let over8 = self.cr1.get_over8();
+        self.brr.set_baud_rate_hz(baud, clock_rate.0, over8);
+        if enabled {
+            self.enable_usart();
+        }
+    }
+
+    /// Get the baud rate divisor programmed into the BRR as its mantissa and fraction, the
+    /// fraction being in sixteenths whatever the OVER8 setting. Intended for debugging marginal
+    /// links, where the rounded baud rate hides the actual error.
+    pub fn get_baud_divisor(&self) -> (u16, u8) {
+        self.brr.get_divisor(self.cr1.get_over8())
+    }
+
+    /// Read back the current baud rate and frame format, given the rate of the clock driving the
+    /// Usart. Intended for debug output, the returned value displays as e.g. `115200 8N1`.
+    pub fn config(&self, clock_rate: Hertz) -> UsartConfig {
+        let parity = self.cr1.get_parity();
+        let parity_bits = match parity {
+            Parity::None => 0,
+            Parity::Even | Parity::Odd => 1,
+        };
+
+        UsartConfig {
+            baud: self.brr.get_baud_rate_hz(clock_rate.0, self.cr1.get_over8()),
+            // The parity bit takes the MSB of the word
+            data_bits: self.cr1.get_word_bits() - parity_bits,
+            parity: parity,
+            stop_bits: self.cr2.get_stop_bits(),
+        }
     }
 
     // --------------------------------------------------------------
@@ -228,6 +526,12 @@ impl RawUsart {
         self.tdr.store(byte);
     }
 
+    /// Move a full character to TDR in order to transmit it, including the ninth bit when using 9
+    /// bit words.
+    pub fn transmit_word(&mut self, data: u16) {
+        self.tdr.write(data);
+    }
+
     // --------------------------------------------------------------
 
     /// Load byte from RDR.
@@ -235,6 +539,11 @@ impl RawUsart {
         self.rdr.load()
     }
 
+    /// Load the full received character from RDR, including the ninth bit when using 9 bit words.
+    pub fn load_word(&self) -> u16 {
+        self.rdr.read()
+    }
+
     // --------------------------------------------------------------
 
     /// Check if RXNE flag is set. RNXE flag is set when the RDR has
@@ -250,14 +559,96 @@ impl RawUsart {
         self.isr.get_tc()
     }
 
+    /// Wait until the last byte written has been shifted out onto the wire. Returns false if it
+    /// still hasn't after a bounded wait, e.g. because the Usart isn't enabled.
+    ///
+    /// Writing to TDR only hands the byte to the shift register, so this is needed before
+    /// anything that depends on the line being quiet, such as switching an RS-485 transceiver
+    /// back to receive.
+    pub fn wait_transmission_complete(&self) -> bool {
+        wait_until(TRANSMISSION_COMPLETE_ATTEMPTS, || self.is_transmission_complete())
+    }
+
     /// Check if TXE flag is set. TXE flag is set when the TDR is empty.
     /// Returns true if TXE flag is set, false otherwise.
     pub fn is_tx_reg_empty(&self) -> bool {
         self.isr.get_txe()
     }
 
+    /// Check if IDLE flag is set. IDLE flag is set when the line goes idle after
+    /// receiving data. Returns true if IDLE flag is set, false otherwise.
+    pub fn is_idle_line_detected(&self) -> bool {
+        self.isr.get_idle()
+    }
+
+    /// Check if PE flag is set. PE flag is set when a parity error is
+    /// detected on a received frame. Returns true if PE flag is set, false otherwise.
+    pub fn is_parity_error(&self) -> bool {
+        self.isr.get_pe()
+    }
+
+    /// Check if FE flag is set. FE flag is set when a framing error or break
+    /// character is detected. Returns true if FE flag is set, false otherwise.
+    pub fn is_framing_error(&self) -> bool {
+        self.isr.get_fe()
+    }
+
+    /// Check if NF flag is set. NF flag is set when noise is detected on a
+    /// received frame. Returns true if NF flag is set, false otherwise.
+    ///
+    /// Noise is never detected while one bit sampling is enabled.
+    pub fn is_noise_detected(&self) -> bool {
+        self.isr.get_nf()
+    }
+
+    /// Check if ORE flag is set. ORE flag is set when data is received while
+    /// the RDR is full. Returns true if ORE flag is set, false otherwise.
+    pub fn is_overrun(&self) -> bool {
+        self.isr.get_ore()
+    }
+
+    /// Check if RWU flag is set. RWU flag is set when the receiver is in
+    /// mute mode. Returns true if RWU flag is set, false otherwise.
+    pub fn is_muted(&self) -> bool {
+        self.isr.get_rwu()
+    }
+
+    /// Check if the nCTS input is asserted (driven low), meaning the other end is ready to
+    /// receive.
+    pub fn is_cts_asserted(&self) -> bool {
+        self.isr.get_cts()
+    }
+
+    /// Check if CTSIF flag is set. CTSIF flag is set when the nCTS input changes while CTS flow
+    /// control is enabled. Returns true if CTSIF flag is set, false otherwise.
+    pub fn is_cts_changed(&self) -> bool {
+        self.isr.get_ctsif()
+    }
+
+    // --------------------------------------------------------------
+
+    /// Put the receiver in mute mode. Mute mode must be enabled first.
+    pub fn request_mute(&mut self) {
+        self.rqr.request_mute();
+    }
+
     // --------------------------------------------------------------
 
+    /// Clear the PE flag. PE flag is set when a parity error is detected.
+    pub fn clear_pe_flag(&mut self) {
+        self.icr.clear_pe();
+    }
+
+    /// Clear the FE flag. FE flag is set when a framing error is detected.
+    pub fn clear_fe_flag(&mut self) {
+        self.icr.clear_fe();
+    }
+
+    /// Clear the NF flag. NF flag is set when noise is detected.
+    pub fn clear_nf_flag(&mut self) {
+        self.icr.clear_nf();
+    }
+
     /// Clear the ORE flag. ORE flag is set when data is received when
     /// the RDR is full.
     pub fn clear_ore_flag(&mut self) {
@@ -270,6 +661,11 @@ impl RawUsart {
         self.icr.clear_tc();
     }
 
+    /// Clear the CTSIF flag. CTSIF flag is set when the nCTS input changes.
+    pub fn clear_cts_flag(&mut self) {
+        self.icr.clear_cts();
+    }
+
     /// Clear the IDLE flag. IDLE flag is set when an idle line is detected. :P
     pub fn clear_idle_flag(&mut self) {
         self.icr.clear_idle();
@@ -284,19 +680,8 @@ pub fn init() {
     let mut rcc = rcc::rcc();
     rcc.enable_peripheral(rcc::Peripheral::USART2);
 
-    gpio::GPIO::enable(gpio::Group::A);
-    let mut pa2 = gpio::Port::new(2, gpio::Group::A);
-    let mut pa15 = gpio::Port::new(15, gpio::Group::A);
-    pa2.set_function(gpio::AlternateFunction::One);
-    pa15.set_function(gpio::AlternateFunction::One);
-    pa2.set_speed(gpio::Speed::High);
-    pa15.set_speed(gpio::Speed::High);
-    pa2.set_mode(gpio::Mode::Alternate);
-    pa15.set_mode(gpio::Mode::Alternate);
-    pa2.set_type(gpio::Type::PushPull);
-    pa15.set_type(gpio::Type::PushPull);
-    pa2.set_pull(gpio::Pull::Up);
-    pa15.set_pull(gpio::Pull::Up);
+    gpio::configure_uart_pins((gpio::Group::A, 2), (gpio::Group::A, 15),
+                              gpio::AlternateFunction::One);
 
     let mut usart2 = Usart::new(UsartX::Usart2);
     usart2.disable_usart();
@@ -315,5 +700,175 @@ pub fn init() {
     usart2.enable_usart();
 
     let mut nvic = interrupt::nvic();
-    nvic.enable_interrupt(interrupt::Hardware::Usart2);
+    nvic.enable_interrupt(UsartX::Usart2.interrupt());
+}
+
+// A Usart register block living in regular memory, with CR3, the ISR and the RDR set to the raw
+// words given. The register types can't be constructed outside of their own modules, so the words
+// are written directly.
+#[cfg(test)]
+fn mock_usart(cr3: u32, isr: u32, rdr: u32) -> RawUsart {
+    use core::{mem, ptr};
+
+    let mut usart: RawUsart = unsafe { mem::zeroed() };
+    unsafe {
+        ptr::write(&mut usart.cr3 as *mut _ as *mut u32, cr3);
+        ptr::write(&mut usart.isr as *mut _ as *mut u32, isr);
+        ptr::write(&mut usart.rdr as *mut _ as *mut u32, rdr);
+    }
+    usart
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use peripheral::raw_word;
+    use core::mem;
+
+    #[test]
+    fn test_set_baud_rate_hz_reenables_usart() {
+        let mut usart: RawUsart = unsafe { mem::zeroed() };
+        usart.enable_usart();
+
+        usart.set_baud_rate_hz(115_200, Hertz(48_000_000));
+        assert_eq!(raw_word(&usart.brr), 417);
+        assert!(usart.is_usart_enabled());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_set_stop_bits_half_in_smartcard_mode_panics() {
+        let mut usart = mock_usart(CR3_SCEN, 0, 0);
+
+        usart.set_stop_bits(StopLength::Half);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_set_stop_bits_one_and_half_in_irda_mode_panics() {
+        let mut usart = mock_usart(CR3_IREN, 0, 0);
+
+        usart.set_stop_bits(StopLength::OneAndHalf);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_set_stop_bits_half_in_irda_mode_panics() {
+        let mut usart = mock_usart(CR3_IREN, 0, 0);
+
+        usart.set_stop_bits(StopLength::Half);
+    }
+
+    #[test]
+    fn test_set_stop_bits_one_and_half_in_smartcard_mode() {
+        let mut usart = mock_usart(CR3_SCEN, 0, 0);
+
+        usart.set_stop_bits(StopLength::OneAndHalf);
+        assert_eq!(usart.cr2.get_stop_bits(), StopLength::OneAndHalf);
+    }
+
+    #[test]
+    fn test_configure_multidrop() {
+        let mut usart: RawUsart = unsafe { mem::zeroed() };
+        usart.enable_usart();
+
+        usart.configure_multidrop(0x5A, true);
+        let cr1 = raw_word(&usart.cr1);
+        let cr2 = raw_word(&usart.cr2);
+        assert_eq!(cr2, 0x5A << CR2_ADD_SHIFT | CR2_ADDM7 | CR2_RXINV);
+        assert_eq!(cr1 & (CR1_MME | CR1_WAKE), CR1_MME | CR1_WAKE);
+        assert!(usart.is_usart_enabled());
+    }
+
+    #[test]
+    fn test_configure_multidrop_without_inversion() {
+        let mut usart: RawUsart = unsafe { mem::zeroed() };
+        usart.set_rx_inversion(true);
+
+        usart.configure_multidrop(0x01, false);
+        let cr2 = raw_word(&usart.cr2);
+        assert_eq!(cr2, 0x01 << CR2_ADD_SHIFT | CR2_ADDM7);
+        assert!(!usart.is_usart_enabled());
+    }
+
+    #[test]
+    fn test_set_baud_rate_hz_leaves_disabled_usart_disabled() {
+        let mut usart: RawUsart = unsafe { mem::zeroed() };
+
+        usart.set_baud_rate_hz(9_600, Hertz(8_000_000));
+        assert_eq!(raw_word(&usart.brr), 833);
+        assert!(!usart.is_usart_enabled());
+    }
+
+    #[test]
+    fn test_set_baud_rate_hz_honors_over8() {
+        let mut usart: RawUsart = unsafe { mem::zeroed() };
+        usart.enable_over8();
+
+        usart.set_baud_rate_hz(115_200, Hertz(48_000_000));
+        assert_eq!(raw_word(&usart.brr), 0x340);
+    }
+
+    #[test]
+    fn test_enable_stop_mode_wakeup_sets_uesm_and_source() {
+        let mut usart: RawUsart = unsafe { mem::zeroed() };
+        usart.enable_usart();
+
+        usart.enable_stop_mode_wakeup(WakeupSource::StartBit);
+        assert_eq!(raw_word(&usart.cr1), CR1_UE | CR1_UESM);
+        assert_eq!(raw_word(&usart.cr3), CR3_WUFIE | WUS_START_BIT << CR3_WUS_SHIFT);
+
+        usart.disable_stop_mode_wakeup();
+        assert_eq!(raw_word(&usart.cr1), CR1_UE);
+        assert_eq!(raw_word(&usart.cr3), WUS_START_BIT << CR3_WUS_SHIFT);
+    }
+
+    #[test]
+    fn test_get_baud_divisor_honors_over8() {
+        let mut usart: RawUsart = unsafe { mem::zeroed() };
+        usart.set_baud_rate_hz(115_200, Hertz(48_000_000));
+        // 48MHz / 115200 = 416.67, USARTDIV = 417 = 0x1A1
+        assert_eq!(usart.get_baud_divisor(), (0x1A, 0x1));
+
+        usart.enable_over8();
+        usart.set_baud_rate_hz(115_200, Hertz(48_000_000));
+        // USARTDIV = 833 = 0x341, the low bit is lost in BRR
+        assert_eq!(usart.get_baud_divisor(), (0x34, 0x0));
+    }
+
+    #[test]
+    fn test_config_reads_back_frame_format() {
+        let mut usart: RawUsart = unsafe { mem::zeroed() };
+        usart.set_baud_rate_hz(115_200, Hertz(48_000_000));
+        assert_eq!(format!("{}", usart.config(Hertz(48_000_000))), "115108 8N1");
+
+        usart.set_word_length(WordLength::Nine);
+        usart.set_parity(Parity::Even);
+        usart.set_stop_bits(StopLength::Two);
+        let config = usart.config(Hertz(48_000_000));
+        assert_eq!(config.data_bits, 8);
+        assert_eq!(config.parity, Parity::Even);
+        assert_eq!(config.stop_bits, StopLength::Two);
+    }
+
+    #[test]
+    fn test_usart_interrupt() {
+        assert_eq!(UsartX::Usart1.interrupt(), Interrupt::Usart1);
+        assert_eq!(UsartX::Usart2.interrupt(), Interrupt::Usart2);
+        assert_eq!(UsartX::Usart2.interrupt().number(), 28);
+    }
+
+    #[test]
+    fn test_wait_transmission_complete_returns_when_tc_set() {
+        let usart = mock_usart(0, ISR_TXE | ISR_TC, 0);
+
+        assert!(usart.wait_transmission_complete());
+    }
+
+    #[test]
+    fn test_wait_transmission_complete_gives_up() {
+        let usart: RawUsart = unsafe { mem::zeroed() };
+
+        assert!(!usart.wait_transmission_complete());
+    }
 }
@@ -15,25 +15,35 @@
 * along with this program. If not, see <http://www.gnu.org/licenses/>.
 */
 
-/* This submodule contains the function implementations for the Usartx_RDR.
- * The RDR is the read data register and is responsible for receiving data
- * through the serial bus.
+/* This submodule contains the function implementations for the Usartx_RQR.
+ * The RQR is the request register and is responsible for triggering
+ * one-shot actions such as entering mute mode. It does so by writing a 1
+ * to specific bits in this register.
  */
 
+use super::defs::*;
+
 #[derive(Copy, Clone, Debug)]
-pub struct RDR(u32);
+pub struct RQR(u32);
 
-impl RDR {
-    /* Bits 31:9 Reserved, must be kept at reset value.
-     * Bits 8:0 RDR[8:0]: Receive data value
-     *   Contains the received data character.
-     * The RDR register provides the parallel interface between the input
-     * shift register and the internal bus.
-     *
-     * When receiving with the parity enabled, the value read in the MSB bit
-     * is the received parity bit.
+impl RQR {
+    /* Bit 2 MMRQ: Mute mode request
+     * Writing 1 to this bit puts the USART in mute mode and sets the RWU flag.
      */
-    pub fn load(&self) -> u8 {
-        self.0 as u8
+    pub fn request_mute(&mut self) {
+        self.0 |= RQR_MMRQ;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rqr_request_mute() {
+        let mut rqr = RQR(0);
+        rqr.request_mute();
+
+        assert_eq!(rqr.0, 0b1 << 2);
     }
 }
@@ -0,0 +1,245 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+/* This submodule keeps count of the receive errors seen on the Usart, so the quality of a link
+ * can be monitored over time rather than only noticed when a read fails.
+ */
+
+use altos_core::sync::CriticalSection;
+use super::RawUsart;
+#[cfg(feature="embedded-hal")]
+use super::SerialError;
+
+/// Counts of the receive errors seen on the Usart.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct UsartStats {
+    /// Bytes lost because the RDR was still full.
+    pub overrun: u32,
+    /// Frames with a framing error or break character.
+    pub framing: u32,
+    /// Frames with a bad parity bit.
+    pub parity: u32,
+    /// Frames with noise detected on them.
+    pub noise: u32,
+}
+
+impl UsartStats {
+    /// Create a new set of counts, all zero.
+    pub const fn new() -> Self {
+        UsartStats {
+            overrun: 0,
+            framing: 0,
+            parity: 0,
+            noise: 0,
+        }
+    }
+
+    /// Get the total number of errors counted.
+    pub fn total(&self) -> u32 {
+        self.overrun
+            .wrapping_add(self.framing)
+            .wrapping_add(self.parity)
+            .wrapping_add(self.noise)
+    }
+
+    // The counters wrap rather than overflow, a link has to run for a long time to get there and
+    // the counts are only used for monitoring.
+    fn add(&mut self, other: UsartStats) {
+        self.overrun = self.overrun.wrapping_add(other.overrun);
+        self.framing = self.framing.wrapping_add(other.framing);
+        self.parity = self.parity.wrapping_add(other.parity);
+        self.noise = self.noise.wrapping_add(other.noise);
+    }
+}
+
+static mut STATS: UsartStats = UsartStats::new();
+
+// The framing, parity and noise flags that were set the last time the receive interrupt looked,
+// as a count of 1 for each flag that was set.
+static mut SEEN_LINE_ERRORS: UsartStats = UsartStats::new();
+
+/// Get the receive errors counted since startup or the last `reset_stats`.
+pub fn stats() -> UsartStats {
+    let _g = CriticalSection::begin();
+    unsafe { STATS }
+}
+
+/// Set all of the receive error counts back to zero.
+pub fn reset_stats() {
+    let _g = CriticalSection::begin();
+    unsafe { STATS = UsartStats::new() };
+}
+
+/// Count a single error reported by a checked read, see `embedded-hal`'s `Read`.
+#[cfg(feature="embedded-hal")]
+pub fn record_error(error: SerialError) {
+    let mut errors = UsartStats::new();
+    match error {
+        SerialError::Overrun => errors.overrun = 1,
+        SerialError::Framing => errors.framing = 1,
+        SerialError::Parity => errors.parity = 1,
+        SerialError::Noise => errors.noise = 1,
+    }
+    record(errors);
+}
+
+/// Count and clear an overrun flagged on the Usart.
+///
+/// This is meant for the receive interrupt, where the overrun flag has to be cleared or the
+/// interrupt keeps firing. The other error flags are left for the reader of the data to see,
+/// they're counted when a checked read reports them.
+pub fn record_overrun(usart: &mut RawUsart) {
+    if take_overrun(usart) {
+        let mut errors = UsartStats::new();
+        errors.overrun = 1;
+        record(errors);
+    }
+}
+
+/// Count the framing, parity and noise errors flagged on the Usart, without clearing them.
+///
+/// This is meant for the receive interrupt, before the RDR is read. The flags are left for the
+/// reader of the data to see and clear, so a flag is only counted when it wasn't set the last
+/// time this looked. A second error of the same kind that arrives before the first one's flag is
+/// cleared can't be told apart from it, and is counted once.
+pub fn record_line_errors(usart: &RawUsart) {
+    let flags = line_errors(usart);
+    let _g = CriticalSection::begin();
+    unsafe {
+        STATS.add(newly_set(flags, SEEN_LINE_ERRORS));
+        SEEN_LINE_ERRORS = flags;
+    }
+}
+
+// Add errors to the running counts.
+fn record(errors: UsartStats) {
+    let _g = CriticalSection::begin();
+    unsafe { STATS.add(errors) };
+}
+
+// Clear the overrun flag if it is set, returning whether it was.
+fn take_overrun(usart: &mut RawUsart) -> bool {
+    let overrun = usart.is_overrun();
+    if overrun {
+        usart.clear_ore_flag();
+    }
+    overrun
+}
+
+// The framing, parity and noise flags set on the Usart, as a count of 1 for each.
+fn line_errors(usart: &RawUsart) -> UsartStats {
+    UsartStats {
+        overrun: 0,
+        framing: usart.is_framing_error() as u32,
+        parity: usart.is_parity_error() as u32,
+        noise: usart.is_noise_detected() as u32,
+    }
+}
+
+// The flags set in `now` that weren't already set in `seen`.
+fn newly_set(now: UsartStats, seen: UsartStats) -> UsartStats {
+    UsartStats {
+        overrun: now.overrun & !seen.overrun,
+        framing: now.framing & !seen.framing,
+        parity: now.parity & !seen.parity,
+        noise: now.noise & !seen.noise,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use peripheral::raw_word;
+    use super::super::mock_usart;
+    use super::super::defs::*;
+
+    #[test]
+    fn test_take_overrun_clears_only_ore() {
+        let mut usart = mock_usart(0, ISR_ORE | ISR_FE | ISR_PE | ISR_NF | ISR_RXNE, 0);
+
+        assert!(take_overrun(&mut usart));
+        assert_eq!(raw_word(&usart.icr), ICR_ORECF);
+    }
+
+    #[test]
+    fn test_take_overrun_leaves_other_errors() {
+        let mut usart = mock_usart(0, ISR_FE | ISR_NF | ISR_RXNE, 0);
+
+        assert!(!take_overrun(&mut usart));
+        assert_eq!(raw_word(&usart.icr), 0);
+    }
+
+    #[test]
+    fn test_line_errors_reads_flags_without_clearing() {
+        let usart = mock_usart(0, ISR_FE | ISR_NF | ISR_ORE, 0);
+
+        assert_eq!(line_errors(&usart), UsartStats { overrun: 0, framing: 1, parity: 0, noise: 1 });
+        assert_eq!(raw_word(&usart.icr), 0);
+    }
+
+    #[test]
+    fn test_newly_set_skips_flags_already_seen() {
+        let seen = UsartStats { overrun: 0, framing: 1, parity: 0, noise: 0 };
+        let now = UsartStats { overrun: 0, framing: 1, parity: 1, noise: 0 };
+
+        let new = UsartStats { overrun: 0, framing: 0, parity: 1, noise: 0 };
+
+        assert_eq!(newly_set(now, seen), new);
+        assert_eq!(newly_set(now, UsartStats::new()), now);
+    }
+
+    // The only test that touches the global counts, so the tests running in parallel can't
+    // disturb each other.
+    #[test]
+    fn test_each_error_increments_its_counter() {
+        reset_stats();
+
+        let mut usart = mock_usart(0, ISR_ORE, 0);
+        record_overrun(&mut usart);
+        assert_eq!(stats(), UsartStats { overrun: 1, framing: 0, parity: 0, noise: 0 });
+
+        record_line_errors(&mock_usart(0, ISR_FE, 0));
+        assert_eq!(stats(), UsartStats { overrun: 1, framing: 1, parity: 0, noise: 0 });
+
+        // The framing flag is still set from the last byte, only the parity error is new
+        record_line_errors(&mock_usart(0, ISR_FE | ISR_PE, 0));
+        assert_eq!(stats(), UsartStats { overrun: 1, framing: 1, parity: 1, noise: 0 });
+
+        record_line_errors(&mock_usart(0, ISR_NF, 0));
+        assert_eq!(stats(), UsartStats { overrun: 1, framing: 1, parity: 1, noise: 1 });
+
+        #[cfg(feature="embedded-hal")]
+        {
+            record_error(SerialError::Overrun);
+            record_error(SerialError::Framing);
+            record_error(SerialError::Parity);
+            record_error(SerialError::Noise);
+            assert_eq!(stats(), UsartStats { overrun: 2, framing: 2, parity: 2, noise: 2 });
+        }
+
+        reset_stats();
+        assert_eq!(stats(), UsartStats::new());
+    }
+
+    #[test]
+    fn test_stats_add_accumulates_and_wraps() {
+        let mut stats = UsartStats { overrun: 0xFFFF_FFFF, framing: 1, parity: 0, noise: 2 };
+
+        stats.add(UsartStats { overrun: 1, framing: 1, parity: 1, noise: 0 });
+        assert_eq!(stats, UsartStats { overrun: 0, framing: 2, parity: 1, noise: 2 });
+    }
+}
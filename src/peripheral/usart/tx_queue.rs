@@ -0,0 +1,283 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! This module provides a transmit queue for the Usart that can be filled from any context,
+//! including interrupt handlers, without blocking.
+//!
+//! Bytes pushed onto the queue are drained one at a time by the TXE interrupt. Pushing a byte
+//! enables the TXE interrupt, and the interrupt disables itself once the queue runs dry.
+
+use altos_core::sync::CriticalSection;
+use altos_core::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
+use super::{Usart, UsartX};
+
+// One slot per Usart, each holding the address of the `TxQueue` attached to it. A value of 0 means
+// no queue is attached.
+static QUEUES: [AtomicUsize; 2] = [
+    ATOMIC_USIZE_INIT,
+    ATOMIC_USIZE_INIT,
+];
+
+/// Error returned when a byte is pushed onto a `TxQueue` that has no space left.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Full;
+
+/// A fixed capacity transmit queue, the capacity is the length of the backing buffer.
+///
+/// Example Usage:
+/// ```
+///   static mut QUEUE_BUF: [u8; 64] = [0; 64];
+///   static mut QUEUE: Option<TxQueue<'static>> = None;
+///
+///   // During initialization
+///   unsafe { QUEUE = Some(TxQueue::new(Usart::new(UsartX::Usart1), &mut QUEUE_BUF)); }
+///
+///   // Have the Usart1 interrupt handler drain the queue
+///   unsafe { QUEUE.as_mut().unwrap().attach(UsartX::Usart1); }
+///
+///   // Anywhere, including interrupt context
+///   unsafe { QUEUE.as_mut().unwrap().push(b'!').ok(); }
+/// ```
+pub struct TxQueue<'a> {
+    usart: Usart,
+    buffer: &'a mut [u8],
+    head: usize,
+    len: usize,
+}
+
+impl<'a> TxQueue<'a> {
+    /// Create a new, empty transmit queue for the Usart, using `buffer` as storage.
+    pub fn new(usart: Usart, buffer: &'a mut [u8]) -> Self {
+        TxQueue {
+            usart: usart,
+            buffer: buffer,
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Queue a byte for transmission and make sure the TXE interrupt is enabled to send it.
+    ///
+    /// Returns `Err(Full)` if there is no space left in the queue, the byte is dropped.
+    pub fn push(&mut self, byte: u8) -> Result<(), Full> {
+        let _g = CriticalSection::begin();
+        if self.len == self.buffer.len() {
+            return Err(Full);
+        }
+        let tail = (self.head + self.len) % self.buffer.len();
+        self.buffer[tail] = byte;
+        self.len += 1;
+        self.usart.enable_transmit_interrupt();
+        Ok(())
+    }
+
+    /// Service a TXE interrupt. The Usart interrupt handlers call this for an attached queue, see
+    /// `attach`.
+    ///
+    /// Moves the next queued byte into the TDR, or disables the TXE interrupt if there is nothing
+    /// left to send.
+    pub fn on_txe(&mut self) {
+        if !self.usart.is_tx_reg_empty() {
+            return;
+        }
+        if !self.send_next() {
+            self.usart.disable_transmit_interrupt();
+        }
+    }
+
+    /// Get the number of bytes waiting to be transmitted.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if there are no bytes waiting to be transmitted.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Move the next queued byte into the TDR, returns false if the queue is empty.
+    fn send_next(&mut self) -> bool {
+        match self.pop() {
+            Some(byte) => {
+                self.usart.transmit_byte(byte);
+                true
+            },
+            None => false,
+        }
+    }
+
+    fn pop(&mut self) -> Option<u8> {
+        if self.len == 0 {
+            return None;
+        }
+        let byte = self.buffer[self.head];
+        self.head = (self.head + 1) % self.buffer.len();
+        self.len -= 1;
+        Some(byte)
+    }
+}
+
+impl TxQueue<'static> {
+    /// Hand the queue to the interrupt handler of the Usart `x`, which drains it from then on.
+    /// The queue must have been created for that Usart. Attaching a new queue replaces the old
+    /// one.
+    pub fn attach(&'static mut self, x: UsartX) {
+        QUEUES[x as usize].store(self as *mut TxQueue<'static> as usize, Ordering::SeqCst);
+    }
+}
+
+// Get the queue attached to the Usart `x`, if there is one.
+fn attached(x: UsartX) -> Option<&'static mut TxQueue<'static>> {
+    match QUEUES[x as usize].load(Ordering::SeqCst) {
+        0 => None,
+        // Only `attach` stores into the slots, and it only takes queues that live forever
+        raw => Some(unsafe { &mut *(raw as *mut TxQueue<'static>) }),
+    }
+}
+
+// Service a TXE interrupt for the queue attached to the Usart `x`. Returns false if no queue is
+// attached. This should only be called from the Usart interrupt handlers.
+#[doc(hidden)]
+pub fn queue_on_txe(x: UsartX) -> bool {
+    match attached(x) {
+        Some(queue) => {
+            queue.on_txe();
+            true
+        },
+        None => false,
+    }
+}
+
+// Move the next byte from the queue attached to the Usart `x` into the TDR, for handlers that
+// share the TXE interrupt with another buffer. Returns false if there was nothing to send, in
+// which case the caller decides whether to disable the interrupt.
+#[doc(hidden)]
+pub fn send_queued(x: UsartX) -> bool {
+    match attached(x) {
+        Some(queue) => queue.send_next(),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use peripheral::raw_word;
+    use super::super::{RawUsart, mock_usart};
+    use super::super::defs::*;
+    use volatile::Volatile;
+
+    // The queue works on a `Usart` handle, point one at a register block in regular memory.
+    fn usart_handle(raw: &mut RawUsart) -> Usart {
+        unsafe { Usart(Volatile::new(raw as *const _)) }
+    }
+
+    #[test]
+    fn test_tx_queue_push_enables_txe_interrupt() {
+        let mut raw = mock_usart(0, ISR_TXE, 0);
+        let mut buffer = [0; 4];
+        let mut queue = TxQueue::new(usart_handle(&mut raw), &mut buffer);
+
+        assert_eq!(queue.push(b'a'), Ok(()));
+        assert_eq!(queue.len(), 1);
+        assert_eq!(raw_word(&raw.cr1) & CR1_TXEIE, CR1_TXEIE);
+    }
+
+    #[test]
+    fn test_tx_queue_push_full_returns_err() {
+        let mut raw = mock_usart(0, ISR_TXE, 0);
+        let mut buffer = [0; 2];
+        let mut queue = TxQueue::new(usart_handle(&mut raw), &mut buffer);
+
+        assert_eq!(queue.push(b'a'), Ok(()));
+        assert_eq!(queue.push(b'b'), Ok(()));
+        assert_eq!(queue.push(b'c'), Err(Full));
+        assert_eq!(queue.len(), 2);
+    }
+
+    #[test]
+    fn test_tx_queue_on_txe_drains_in_order() {
+        let mut raw = mock_usart(0, ISR_TXE, 0);
+        let mut buffer = [0; 4];
+        let mut queue = TxQueue::new(usart_handle(&mut raw), &mut buffer);
+        queue.push(b'a').unwrap();
+        queue.push(b'b').unwrap();
+
+        queue.on_txe();
+        assert_eq!(raw_word(&raw.tdr), b'a' as u32);
+        queue.on_txe();
+        assert_eq!(raw_word(&raw.tdr), b'b' as u32);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_tx_queue_on_txe_disables_interrupt_when_empty() {
+        let mut raw = mock_usart(0, ISR_TXE, 0);
+        let mut buffer = [0; 4];
+        let mut queue = TxQueue::new(usart_handle(&mut raw), &mut buffer);
+        queue.push(b'a').unwrap();
+
+        queue.on_txe();
+        assert_eq!(raw_word(&raw.cr1) & CR1_TXEIE, CR1_TXEIE);
+
+        queue.on_txe();
+        assert_eq!(raw_word(&raw.cr1) & CR1_TXEIE, 0);
+    }
+
+    #[test]
+    fn test_tx_queue_on_txe_waits_for_empty_tdr() {
+        let mut raw = mock_usart(0, 0, 0);
+        let mut buffer = [0; 4];
+        let mut queue = TxQueue::new(usart_handle(&mut raw), &mut buffer);
+        queue.push(b'a').unwrap();
+
+        queue.on_txe();
+        assert_eq!(queue.len(), 1);
+        assert_eq!(raw_word(&raw.tdr), 0);
+    }
+
+    #[test]
+    fn test_tx_queue_wraps_around() {
+        let mut raw = mock_usart(0, ISR_TXE, 0);
+        let mut buffer = [0; 2];
+        let mut queue = TxQueue::new(usart_handle(&mut raw), &mut buffer);
+
+        queue.push(b'a').unwrap();
+        queue.push(b'b').unwrap();
+        queue.on_txe();
+        queue.push(b'c').unwrap();
+
+        queue.on_txe();
+        assert_eq!(raw_word(&raw.tdr), b'b' as u32);
+        queue.on_txe();
+        assert_eq!(raw_word(&raw.tdr), b'c' as u32);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_tx_queue_send_next_empty_leaves_interrupt() {
+        let mut raw = mock_usart(0, ISR_TXE, 0);
+        let mut buffer = [0; 4];
+        let mut queue = TxQueue::new(usart_handle(&mut raw), &mut buffer);
+        queue.push(b'a').unwrap();
+
+        assert!(queue.send_next());
+        assert_eq!(raw_word(&raw.tdr), b'a' as u32);
+        assert!(!queue.send_next());
+        assert_eq!(raw_word(&raw.cr1) & CR1_TXEIE, CR1_TXEIE);
+    }
+}
@@ -15,8 +15,11 @@
 * along with this program. If not, see <http://www.gnu.org/licenses/>.
 */
 
-pub const SCB_ADDR: *const u32 = 0xE000_ED00 as *const _;
+use peripheral::addresses;
+
+pub const SCB_ADDR: *const u32 = addresses::SCB_ADDR;
 
 pub const ICSR_OFFSET: u32 = 0x04;
+pub const ICSR_VECTACTIVE_MASK: u32 = 0b11_1111;
 pub const ICSR_PENDSVCLR: u32 = 0b1 << 27;
 pub const ICSR_PENDSVSET: u32 = 0b1 << 28;
@@ -0,0 +1,122 @@
+/*
+* Copyright (C) 2017 AltOS-Rust Team
+*
+* This program is free software: you can redistribute it and/or modify
+* it under the terms of the GNU General Public License as published by
+* the Free Software Foundation, either version 3 of the License, or
+* (at your option) any later version.
+*
+* This program is distributed in the hope that it will be useful,
+* but WITHOUT ANY WARRANTY; without even the implied warranty of
+* MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+* GNU General Public License for more details.
+*
+* You should have received a copy of the GNU General Public License
+* along with this program. If not, see <http://www.gnu.org/licenses/>.
+*/
+
+//! This module captures the state of the processor when a fault occurs.
+//!
+//! On exception entry the processor pushes R0-R3, R12, LR, PC and xPSR onto the active stack, in
+//! that order. Given the stack pointer at the time of the fault these can be read back to find
+//! out where and why the fault happened.
+
+use super::scb;
+
+const FRAME_R0: isize = 0;
+const FRAME_R1: isize = 1;
+const FRAME_R2: isize = 2;
+const FRAME_R3: isize = 3;
+const FRAME_R12: isize = 4;
+const FRAME_LR: isize = 5;
+const FRAME_PC: isize = 6;
+const FRAME_XPSR: isize = 7;
+
+/// The registers stacked by the processor on exception entry, along with the exception that was
+/// active when they were captured.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FaultContext {
+    /// Stacked R0.
+    pub r0: u32,
+    /// Stacked R1.
+    pub r1: u32,
+    /// Stacked R2.
+    pub r2: u32,
+    /// Stacked R3.
+    pub r3: u32,
+    /// Stacked R12.
+    pub r12: u32,
+    /// Stacked link register, the return address of the function that faulted.
+    pub lr: u32,
+    /// Stacked program counter, the instruction that faulted.
+    pub pc: u32,
+    /// Stacked program status register.
+    pub xpsr: u32,
+    /// The exception number that was active when the context was captured (3 for a HardFault).
+    pub active_vector: u8,
+}
+
+impl FaultContext {
+    /// Decode the exception stack frame at `sp`.
+    ///
+    /// # Safety
+    ///
+    /// `sp` must point to a valid exception stack frame of 8 words.
+    pub unsafe fn from_stack(sp: *const u32, active_vector: u8) -> Self {
+        FaultContext {
+            r0: *sp.offset(FRAME_R0),
+            r1: *sp.offset(FRAME_R1),
+            r2: *sp.offset(FRAME_R2),
+            r3: *sp.offset(FRAME_R3),
+            r12: *sp.offset(FRAME_R12),
+            lr: *sp.offset(FRAME_LR),
+            pc: *sp.offset(FRAME_PC),
+            xpsr: *sp.offset(FRAME_XPSR),
+            active_vector: active_vector,
+        }
+    }
+}
+
+/// Capture the stacked registers at `sp` along with the currently active exception. This is meant
+/// to be called from a fault handler with the stack pointer that was in use when the fault
+/// occurred (MSP or PSP, depending on bit 2 of EXC_RETURN).
+///
+/// # Safety
+///
+/// `sp` must point to a valid exception stack frame of 8 words.
+pub unsafe fn capture_fault_context(sp: *const u32) -> FaultContext {
+    let active_vector = scb().get_active_vector();
+    FaultContext::from_stack(sp, active_vector)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fault_context_decodes_stack_frame() {
+        let frame: [u32; 8] = [0x10, 0x11, 0x12, 0x13, 0x1C, 0x0800_0101, 0x0800_0240, 0x0100_0000];
+
+        let context = unsafe { FaultContext::from_stack(frame.as_ptr(), 3) };
+
+        assert_eq!(context.r0, 0x10);
+        assert_eq!(context.r1, 0x11);
+        assert_eq!(context.r2, 0x12);
+        assert_eq!(context.r3, 0x13);
+        assert_eq!(context.r12, 0x1C);
+        assert_eq!(context.lr, 0x0800_0101);
+        assert_eq!(context.pc, 0x0800_0240);
+        assert_eq!(context.xpsr, 0x0100_0000);
+        assert_eq!(context.active_vector, 3);
+    }
+
+    #[test]
+    fn test_fault_context_only_reads_frame_words() {
+        // Words past the end of the frame belong to whatever was on the stack before
+        let stack: [u32; 10] = [1, 2, 3, 4, 5, 6, 7, 8, 0xDEAD, 0xBEEF];
+
+        let context = unsafe { FaultContext::from_stack(stack.as_ptr(), 3) };
+
+        assert_eq!(context.xpsr, 8);
+    }
+}
@@ -20,6 +20,8 @@ use super::defs::*;
 #[derive(Copy, Clone, Debug)]
 pub struct ICSR(u32);
 
+impl_register!(ICSR);
+
 impl ICSR {
     pub fn set_pend_sv(&mut self) {
         self.0 |= ICSR_PENDSVSET;
@@ -28,6 +30,11 @@ impl ICSR {
     pub fn clear_pend_sv(&mut self) {
         self.0 |= ICSR_PENDSVCLR;
     }
+
+    /// Get the exception number of the currently active exception, 0 if in thread mode.
+    pub fn get_active_vector(&self) -> u8 {
+        (self.0 & ICSR_VECTACTIVE_MASK) as u8
+    }
 }
 
 #[cfg(test)]
@@ -49,4 +56,18 @@ mod tests {
         icsr.clear_pend_sv();
         assert_eq!(icsr.0, 0b1 << 27);
     }
+
+    #[test]
+    fn test_icsr_get_active_vector() {
+        let icsr = ICSR(0b1 << 28 | 0b1 << 22 | 3);
+
+        assert_eq!(icsr.get_active_vector(), 3);
+    }
+
+    #[test]
+    fn test_icsr_get_active_vector_thread_mode() {
+        let icsr = ICSR(0);
+
+        assert_eq!(icsr.get_active_vector(), 0);
+    }
 }
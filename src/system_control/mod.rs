@@ -20,12 +20,15 @@
 
 mod icsr;
 mod defs;
+mod fault;
 
 use core::ops::{Deref, DerefMut};
 use ::volatile::Volatile;
 use self::icsr::ICSR;
 use self::defs::*;
 
+pub use self::fault::{FaultContext, capture_fault_context};
+
 /// Returns instance of the System Control Block.
 pub fn scb() -> SCB {
     SCB::scb()
@@ -85,4 +88,9 @@ impl RawSCB {
     pub fn clear_pend_sv(&mut self) {
         self.icsr.clear_pend_sv();
     }
+
+    /// Get the exception number of the currently active exception, 0 if in thread mode.
+    pub fn get_active_vector(&self) -> u8 {
+        self.icsr.get_active_vector()
+    }
 }
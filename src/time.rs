@@ -26,6 +26,7 @@ use altos_core::sync::RawMutex;
 use altos_core::syscall;
 use altos_core::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
 use core::ops::{Add, AddAssign, Sub};
+use peripheral::{rcc, systick};
 
 // We use a RawMutex here because the system tick function needs to be able to lock the mutex from
 // within an interrupt handler, and so must use the sys_mutex_try_lock call rather than the normal
@@ -48,15 +49,67 @@ pub fn now() -> Time {
 ///
 /// This method takes a `usize` argument for the number of milliseconds to delay the currently
 /// running task.
+///
+/// If the ms resolution hasn't been set yet (during early boot, before the system tick is set
+/// up) this busy waits on a loop counted from the system clock rate instead.
 #[inline(never)]
 pub fn delay_ms(ms: usize) {
     let ms_res = get_resolution();
     if ms_res == 0 {
-        panic!("delay_ms - the time ms_resolution has not been set!");
+        spin_ms(ms);
+        return;
     }
     syscall::sleep_for(syscall::FOREVER_CHAN, ms * ms_res);
 }
 
+// Busy wait one millisecond at a time. This doesn't touch the SysTick, which may not be counting
+// yet this early.
+fn spin_ms(ms: usize) {
+    let loops = spin_loops_for_us(1000, rcc::rcc().get_system_clock_rate());
+    for _ in 0..ms {
+        ::arm::asm::spin_loops(loops);
+    }
+}
+
+// Delays shorter than this many microseconds spin on a counted loop rather than the SysTick, where
+// the overhead of reading the counter would make up most of the delay.
+const SPIN_LOOP_MAX_US: usize = 10;
+
+/// Busy wait for a certain number of microseconds.
+///
+/// Unlike `delay_ms` this does not put the task to sleep, it spins on the system tick counter
+/// without changing its configuration. This is meant for short delays that are below the tick
+/// resolution.
+///
+/// Delays of a few microseconds spin on a loop counted from the system clock rate instead, so
+/// they stay accurate for bit-banged protocols. The loop assumes it runs without flash wait
+/// states, and an interrupt during the loop makes the delay longer.
+pub fn delay_us(us: usize) {
+    let clock_rate = rcc::rcc().get_system_clock_rate();
+    if use_spin_loop(us, clock_rate) {
+        ::arm::asm::spin_loops(spin_loops_for_us(us as u32, clock_rate));
+        return;
+    }
+    let systick = systick::systick();
+    let ticks = systick::ticks_for_us(us as u32, clock_rate, systick.get_clock_source());
+    systick.wait_ticks(ticks);
+}
+
+// The spin loop can only be timed once the clock rate is known.
+fn use_spin_loop(us: usize, clock_rate: Hertz) -> bool {
+    us < SPIN_LOOP_MAX_US && clock_rate.0 != 0
+}
+
+// The number of spin loop iterations that take `us` microseconds, never 0 for a nonzero delay.
+fn spin_loops_for_us(us: u32, clock_rate: Hertz) -> u32 {
+    let cycles = us.saturating_mul(clock_rate.0 / 1_000_000);
+    let loops = cycles / ::arm::asm::CYCLES_PER_SPIN_LOOP;
+    if us != 0 && loops == 0 {
+        return 1;
+    }
+    loops
+}
+
 /// Delay task for a certain number of seconds.
 pub fn delay_s(s: usize) {
     // FIXME: Handle overflow
@@ -97,7 +150,7 @@ pub fn system_tick() {
 }
 
 /// A type containing information about the time passed since the start of the system.
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Time {
     /// Number of seconds that have passed.
     pub sec: usize,
@@ -164,6 +217,36 @@ impl Sub<Time> for Time {
     }
 }
 
+/// A frequency in Hz.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Hertz(pub u32);
+
+/// A frequency in kHz.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Kilohertz(pub u32);
+
+/// A frequency in MHz.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Megahertz(pub u32);
+
+impl From<Kilohertz> for Hertz {
+    fn from(khz: Kilohertz) -> Self {
+        Hertz(khz.0 * 1_000)
+    }
+}
+
+impl From<Megahertz> for Hertz {
+    fn from(mhz: Megahertz) -> Self {
+        Hertz(mhz.0 * 1_000_000)
+    }
+}
+
+impl From<Megahertz> for Kilohertz {
+    fn from(mhz: Megahertz) -> Self {
+        Kilohertz(mhz.0 * 1_000)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -189,4 +272,57 @@ mod tests {
         assert_eq!(time3.sec, 201);
         assert_eq!(time3.msec, 100);
     }
+
+    #[test]
+    fn test_use_spin_loop_below_threshold() {
+        assert!(use_spin_loop(1, Hertz(48_000_000)));
+        assert!(use_spin_loop(9, Hertz(48_000_000)));
+    }
+
+    #[test]
+    fn test_use_spin_loop_threshold_uses_systick() {
+        assert!(!use_spin_loop(10, Hertz(48_000_000)));
+        assert!(!use_spin_loop(1000, Hertz(48_000_000)));
+    }
+
+    #[test]
+    fn test_use_spin_loop_needs_clock_rate() {
+        assert!(!use_spin_loop(1, Hertz(0)));
+    }
+
+    #[test]
+    fn test_spin_loops_for_us() {
+        // 48 cycles per us, 4 cycles per loop
+        assert_eq!(spin_loops_for_us(1, Hertz(48_000_000)), 12);
+        assert_eq!(spin_loops_for_us(5, Hertz(48_000_000)), 60);
+        assert_eq!(spin_loops_for_us(3, Hertz(8_000_000)), 6);
+    }
+
+    #[test]
+    fn test_spin_loops_for_us_never_zero_for_nonzero_delay() {
+        assert_eq!(spin_loops_for_us(1, Hertz(2_000_000)), 1);
+        assert_eq!(spin_loops_for_us(0, Hertz(48_000_000)), 0);
+    }
+
+    #[test]
+    fn test_kilohertz_to_hertz() {
+        assert_eq!(Hertz::from(Kilohertz(115)), Hertz(115_000));
+    }
+
+    #[test]
+    fn test_megahertz_to_hertz() {
+        assert_eq!(Hertz::from(Megahertz(48)), Hertz(48_000_000));
+    }
+
+    #[test]
+    fn test_megahertz_to_kilohertz() {
+        assert_eq!(Kilohertz::from(Megahertz(8)), Kilohertz(8_000));
+    }
+
+    #[test]
+    fn test_times_compare_by_seconds_then_milliseconds() {
+        assert!(Time { sec: 1, msec: 0 } > Time { sec: 0, msec: 999 });
+        assert!(Time { sec: 2, msec: 10 } < Time { sec: 2, msec: 11 });
+        assert!(Time { sec: 3, msec: 5 } == Time { sec: 3, msec: 5 });
+    }
 }